@@ -0,0 +1,164 @@
+//! Running one command across every saved project (`frc run-all`)
+
+use anyhow::Result;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+
+/// What happened in one project
+#[derive(Debug, PartialEq)]
+pub enum Outcome {
+    /// The command ran and exited with this code
+    Exited(i32),
+    /// The command couldn't be started
+    Failed(String),
+    /// Not started because an earlier project failed
+    Skipped,
+}
+
+impl Outcome {
+    fn is_failure(&self) -> bool {
+        matches!(self, Outcome::Exited(code) if *code != 0) || matches!(self, Outcome::Failed(_))
+    }
+}
+
+/// Run `exec(path, memory)` for each `(path, memory)` project on up to
+/// `jobs` threads. Without `keep_going`, projects not yet started
+/// when one fails are skipped. Outcomes keep the order of `projects`.
+pub fn run<F>(projects: &[(String, String)], jobs: usize, keep_going: bool, exec: F) -> Vec<Outcome>
+where
+    F: Fn(&str, &str) -> Result<i32> + Sync,
+{
+    let next = AtomicUsize::new(0);
+    let stop = AtomicBool::new(false);
+    let outcomes: Mutex<Vec<Option<Outcome>>> = Mutex::new(projects.iter().map(|_| None).collect());
+
+    thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, projects.len().max(1)) {
+            scope.spawn(|| {
+                loop {
+                    let index = next.fetch_add(1, Ordering::SeqCst);
+                    let Some((path, memory)) = projects.get(index) else {
+                        break;
+                    };
+
+                    let outcome = if stop.load(Ordering::SeqCst) {
+                        Outcome::Skipped
+                    } else {
+                        match exec(path, memory) {
+                            Ok(code) => Outcome::Exited(code),
+                            Err(e) => Outcome::Failed(e.to_string()),
+                        }
+                    };
+                    if outcome.is_failure() && !keep_going {
+                        stop.store(true, Ordering::SeqCst);
+                    }
+
+                    let mut outcomes = outcomes.lock().unwrap_or_else(|e| e.into_inner());
+                    outcomes[index] = Some(outcome);
+                }
+            });
+        }
+    });
+
+    outcomes
+        .into_inner()
+        .unwrap_or_else(|e| e.into_inner())
+        .into_iter()
+        .map(|outcome| outcome.unwrap_or(Outcome::Skipped))
+        .collect()
+}
+
+/// Exit code for the whole batch: the first failing project's code, 1 if
+/// it couldn't be started, 0 when every project succeeded
+pub fn exit_code(outcomes: &[Outcome]) -> i32 {
+    outcomes
+        .iter()
+        .find_map(|outcome| match outcome {
+            Outcome::Exited(code) if *code != 0 => Some(*code),
+            Outcome::Failed(_) => Some(1),
+            _ => None,
+        })
+        .unwrap_or(0)
+}
+
+/// One summary line per project
+pub fn summary(projects: &[(String, String)], outcomes: &[Outcome]) -> Vec<String> {
+    projects
+        .iter()
+        .zip(outcomes)
+        .map(|((path, _), outcome)| match outcome {
+            Outcome::Exited(0) => format!("✅ {}", path),
+            Outcome::Exited(code) => format!("❌ {} (exit {})", path, code),
+            Outcome::Failed(error) => format!("❌ {}: {}", path, error),
+            Outcome::Skipped => format!("-  {} (skipped)", path),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    fn projects(paths: &[&str]) -> Vec<(String, String)> {
+        paths
+            .iter()
+            .map(|path| (path.to_string(), "2048".to_string()))
+            .collect()
+    }
+
+    /// Exits with the number in the path's name, fails to start on "missing"
+    fn stub(path: &str, memory: &str) -> Result<i32> {
+        assert_eq!(memory, "2048");
+        match path.trim_start_matches("/p/") {
+            "missing" => Err(anyhow!("not found")),
+            code => Ok(code.parse().unwrap()),
+        }
+    }
+
+    #[test]
+    fn test_run_stops_after_failure() {
+        let projects = projects(&["/p/0", "/p/3", "/p/0"]);
+        let outcomes = run(&projects, 1, false, stub);
+        assert_eq!(
+            outcomes,
+            [Outcome::Exited(0), Outcome::Exited(3), Outcome::Skipped]
+        );
+        assert_eq!(exit_code(&outcomes), 3);
+    }
+
+    #[test]
+    fn test_run_keep_going() {
+        let projects = projects(&["/p/missing", "/p/2", "/p/0"]);
+        let outcomes = run(&projects, 1, true, stub);
+        assert_eq!(
+            outcomes,
+            [
+                Outcome::Failed("not found".to_string()),
+                Outcome::Exited(2),
+                Outcome::Exited(0)
+            ]
+        );
+        assert_eq!(exit_code(&outcomes), 1);
+
+        let lines = summary(&projects, &outcomes);
+        assert_eq!(lines[0], "❌ /p/missing: not found");
+        assert_eq!(lines[1], "❌ /p/2 (exit 2)");
+        assert_eq!(lines[2], "✅ /p/0");
+    }
+
+    #[test]
+    fn test_run_parallel_keeps_order() {
+        let ran = AtomicUsize::new(0);
+        let outcomes = run(&projects(&["/p/0"; 20]), 4, false, |path, memory| {
+            ran.fetch_add(1, Ordering::SeqCst);
+            stub(path, memory)
+        });
+        assert_eq!(ran.load(Ordering::SeqCst), 20);
+        assert!(outcomes.iter().all(|o| *o == Outcome::Exited(0)));
+        assert_eq!(exit_code(&outcomes), 0);
+
+        assert!(run(&[], 4, false, stub).is_empty());
+    }
+}
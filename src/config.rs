@@ -1,18 +1,119 @@
-use crate::storage::{ProjectSettings, Storage, StorageData};
-use anyhow::Result;
+use crate::storage::{
+    DefaultSettings, HISTORY_LIMIT, ProjectSettings, RunRecord, RuntimeVersion, Storage,
+    StorageData,
+};
+use crate::timings;
+use anyhow::{Result, bail};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
 
+/// Share of system memory auto-increase never goes beyond
+const MAX_SYSTEM_SHARE_PERCENT: u64 = 90;
+
+/// Outcome of `Config::increase_project_memory`
+#[derive(Debug, PartialEq)]
+pub enum MemoryIncrease {
+    Increased {
+        old: String,
+        new: String,
+    },
+    /// Already at the ceiling, 90% of system memory
+    AtCeiling {
+        memory: String,
+    },
+}
+
+/// Saved projects older than this count as stale in `ProjectStats`
+pub const STALE_DAYS: u64 = 30;
+
+/// Aggregates over the saved projects. Memory figures are in MB and skip
+/// non-numeric values; they are `None` when no project has one.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ProjectStats {
+    pub count: usize,
+    pub by_runtime: BTreeMap<String, usize>,
+    pub average_memory: Option<u64>,
+    pub median_memory: Option<u64>,
+    pub max_memory: Option<u64>,
+    /// Projects not used in the last `STALE_DAYS` days
+    pub stale: usize,
+}
+
+/// Successful runs in a row at the saved memory before a decrease applies
+pub const DECREASE_AFTER_RUNS: usize = 5;
+
+/// `maybe_decrease_project_memory` never goes below this
+const DECREASE_FLOOR_MB: u64 = 1024;
+
+/// Room left above the last measured peak when lowering memory
+const PEAK_HEADROOM_PERCENT: u64 = 25;
+
+#[derive(Default)]
 pub struct Config {
     data: StorageData,
+    /// `data` as it was loaded. Saving writes back only what changed since,
+    /// merged into the file as it is then.
+    loaded: StorageData,
 }
 
 impl Config {
     pub fn load() -> Result<Self> {
-        let data = Storage::load()?;
-        Ok(Self { data })
+        let data = timings::measure("config_load", Storage::load)?;
+        Ok(Self::from_loaded(data))
+    }
+
+    fn from_loaded(data: StorageData) -> Self {
+        Self {
+            loaded: data.clone(),
+            data,
+        }
+    }
+
+    /// Load only the entry for `path`. Listing and cleanup need `load`.
+    /// If there is none, entries saved under `key` at another path are
+    /// kept too, so a moved project still finds its config.
+    pub fn load_scoped(path: &str, key: Option<&str>) -> Result<Self> {
+        let mut data = timings::measure("config_load", || Storage::load_scoped(path))?;
+
+        if let Some(key) = key
+            && data.projects.is_empty()
+        {
+            data = Storage::load()?;
+            data.projects
+                .retain(|_, project| project.key.as_deref() == Some(key));
+        }
+
+        Ok(Self::from_loaded(data))
     }
 
     pub fn save(&self) -> Result<()> {
-        Storage::save(&self.data)
+        self.save_to(&Storage::config_path()?)
+    }
+
+    /// Write the changes made since loading to the config file at `file`.
+    /// The file is re-read and merged under the config lock, so concurrent
+    /// runs saving different projects don't drop each other's entries.
+    fn save_to(&self, file: &Path) -> Result<()> {
+        let _lock = Storage::lock(file)?;
+        let mut full = Storage::load_from(file)?;
+        let (loaded, data) = (&self.loaded, &self.data);
+
+        merge_changes(&mut full.projects, &loaded.projects, &data.projects);
+        merge_changes(&mut full.profiles, &loaded.profiles, &data.profiles);
+        merge_changes(&mut full.defaults, &loaded.defaults, &data.defaults);
+        merge_changes(
+            &mut full.runtime_versions,
+            &loaded.runtime_versions,
+            &data.runtime_versions,
+        );
+        if data.global_default != loaded.global_default {
+            full.global_default = data.global_default.clone();
+        }
+        if data.memory_ceiling != loaded.memory_ceiling {
+            full.memory_ceiling = data.memory_ceiling;
+        }
+        Storage::save_to(&full, file)
     }
 
     fn current_timestamp() -> u64 {
@@ -22,47 +123,535 @@ impl Config {
             .as_secs()
     }
 
-    pub fn get_project(&self, path: &str) -> Option<&ProjectSettings> {
+    /// The entry saved for `path`, else the most recently used entry
+    /// saved under `key` at some other path
+    pub fn get_project(&self, path: &str, key: Option<&str>) -> Option<&ProjectSettings> {
+        let path = self.project_path(path, key)?;
         self.data.projects.get(path)
     }
 
+    fn project_path(&self, path: &str, key: Option<&str>) -> Option<&String> {
+        if let Some((path, _)) = self.data.projects.get_key_value(path) {
+            return Some(path);
+        }
+
+        let key = key?;
+        self.data
+            .projects
+            .iter()
+            .filter(|(_, project)| project.key.as_deref() == Some(key))
+            .max_by_key(|(_, project)| project.last_used)
+            .map(|(path, _)| path)
+    }
+
+    /// Profile `name` saved for `path`
+    pub fn get_profile(&self, path: &str, name: &str) -> Option<&ProjectSettings> {
+        self.data.profiles.get(path)?.get(name)
+    }
+
+    /// Named profiles saved for `path`, by name
+    pub fn list_profiles(&self, path: &str) -> Vec<(&String, &ProjectSettings)> {
+        self.data
+            .profiles
+            .get(path)
+            .map(|profiles| profiles.iter().collect())
+            .unwrap_or_default()
+    }
+
+    /// `StorageData::memory_ceiling`
+    pub fn memory_ceiling(&self) -> Option<u64> {
+        self.data.memory_ceiling
+    }
+
+    pub fn set_memory_ceiling(&mut self, ceiling: Option<u64>) {
+        self.data.memory_ceiling = ceiling;
+    }
+
+    pub fn get_global_default(&self) -> Option<&DefaultSettings> {
+        self.data.global_default.as_ref()
+    }
+
+    /// Set the memory used when no project config applies
+    pub fn set_global_default(&mut self, memory: String) {
+        self.data.global_default = Some(DefaultSettings { memory });
+    }
+
+    /// The user's recommended memory for `runtime`, if they set one
+    pub fn get_runtime_default(&self, runtime: &str) -> Option<&str> {
+        self.data.defaults.get(runtime).map(String::as_str)
+    }
+
+    /// Set the recommended memory for `runtime`
+    pub fn set_runtime_default(&mut self, runtime: String, memory: String) {
+        self.data.defaults.insert(runtime, memory);
+    }
+
+    /// Version saved for the runtime binary at `program`, if the binary
+    /// hasn't changed since (same `modified` time)
+    pub fn runtime_version(&self, program: &str, modified: u64) -> Option<&str> {
+        self.data
+            .runtime_versions
+            .get(program)
+            .filter(|saved| saved.modified == modified)
+            .map(|saved| saved.version.as_str())
+    }
+
+    pub fn set_runtime_version(&mut self, program: String, modified: u64, version: String) {
+        self.data
+            .runtime_versions
+            .insert(program, RuntimeVersion { modified, version });
+    }
+
+    /// Starting point for an entry saved for the first time
+    fn new_entry() -> ProjectSettings {
+        ProjectSettings {
+            created_by_version: env!("CARGO_PKG_VERSION").to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Save `runtime` and `memory` for `path`, keeping its key and run
+    /// history
     pub fn save_project(&mut self, path: String, runtime: String, memory: String) {
-        self.data.projects.insert(
+        let projects = &mut self.data.projects;
+        let previous = projects.remove(&path).unwrap_or_else(Self::new_entry);
+        projects.insert(
             path,
             ProjectSettings {
                 runtime,
                 memory,
                 last_used: Self::current_timestamp(),
+                percent: None,
+                ..previous
             },
         );
     }
 
+    /// Save `runtime` and `memory` as profile `name` of `path`. The
+    /// default profile, saved with `save_project`, is left alone.
+    pub fn save_profile(&mut self, path: &str, name: &str, runtime: String, memory: String) {
+        let profiles = self.data.profiles.entry(path.to_string()).or_default();
+        let previous = profiles.remove(name).unwrap_or_else(Self::new_entry);
+        profiles.insert(
+            name.to_string(),
+            ProjectSettings {
+                runtime,
+                memory,
+                last_used: Self::current_timestamp(),
+                percent: None,
+                ..previous
+            },
+        );
+    }
+
+    /// Count a run of the project saved for `path` and remember the memory
+    /// it got. Returns false if nothing is saved there.
+    pub fn record_run(&mut self, path: &str, memory: Option<&str>, success: bool) -> bool {
+        self.record_run_at(path, memory, success, Self::current_timestamp())
+    }
+
+    fn record_run_at(&mut self, path: &str, memory: Option<&str>, success: bool, now: u64) -> bool {
+        let Some(project) = self.data.projects.get_mut(path) else {
+            return false;
+        };
+
+        project.run_count += 1;
+        project.last_used = now;
+        project.history.push(RunRecord {
+            timestamp: now,
+            memory: memory.unwrap_or(&project.memory).to_string(),
+            success,
+        });
+        let excess = project.history.len().saturating_sub(HISTORY_LIMIT);
+        project.history.drain(..excess);
+        true
+    }
+
+    /// Mark profile `name` of `path` as used, so `cleanup` keeps it.
+    /// Returns false, with nothing to save, unless it is saved for `runtime`
+    /// and wasn't already marked this second.
+    pub fn touch_profile(&mut self, path: &str, name: &str, runtime: &str) -> bool {
+        self.touch_profile_at(path, name, runtime, Self::current_timestamp())
+    }
+
+    fn touch_profile_at(&mut self, path: &str, name: &str, runtime: &str, now: u64) -> bool {
+        let profile = self
+            .data
+            .profiles
+            .get_mut(path)
+            .and_then(|profiles| profiles.get_mut(name));
+        match profile {
+            Some(profile) if profile.runtime == runtime && profile.last_used < now => {
+                profile.last_used = now;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Remember the peak memory the last run of `path` reached
+    pub fn set_project_peak(&mut self, path: &str, peak_mb: u64) {
+        if let Some(project) = self.data.projects.get_mut(path) {
+            project.last_peak_mb = Some(peak_mb);
+        }
+    }
+
+    /// Record the stable key for the entry at `path`, if there is one
+    pub fn set_project_key(&mut self, path: &str, key: Option<String>) {
+        if let Some(project) = self.data.projects.get_mut(path) {
+            project.key = key;
+        }
+    }
+
+    /// Record that the memory saved for `path` came from a percentage
+    pub fn set_project_percent(&mut self, path: &str, percent: Option<u64>) {
+        if let Some(project) = self.data.projects.get_mut(path) {
+            project.percent = percent;
+        }
+    }
+
+    /// Attach `note` to the config saved for `path`; a blank note clears it
+    pub fn set_project_note(&mut self, path: &str, note: &str) -> Result<()> {
+        let Some(project) = self.data.projects.get_mut(path) else {
+            bail!("No config found for '{}'", path);
+        };
+        let note = note.trim();
+        project.note = (!note.is_empty()).then(|| note.to_string());
+        Ok(())
+    }
+
+    /// Switch the runtime saved for `path`, keeping its memory. Returns
+    /// the previous runtime.
+    pub fn set_project_runtime(&mut self, path: &str, runtime: &str) -> Result<String> {
+        let Some(project) = self.data.projects.get_mut(path) else {
+            bail!("No config found for '{}'", path);
+        };
+        let previous = std::mem::replace(&mut project.runtime, runtime.to_string());
+        project.last_used = Self::current_timestamp();
+        Ok(previous)
+    }
+
+    /// Remove the entry for `path` along with its profiles
     pub fn remove_project(&mut self, path: &str) -> bool {
-        self.data.projects.remove(path).is_some()
+        let profiles = self.data.profiles.remove(path).is_some();
+        self.data.projects.remove(path).is_some() || profiles
+    }
+
+    /// Move the entry saved at `from` and its profiles to `to`, marking it
+    /// as used now. An existing entry at `to` is only replaced with `force`.
+    pub fn rename_project(&mut self, from: &str, to: &str, force: bool) -> Result<()> {
+        if !self.data.projects.contains_key(from) {
+            bail!("No config found for '{}'", from);
+        }
+        // Profiles saved for `to` count as an existing config too
+        let taken = self.data.projects.contains_key(to) || self.data.profiles.contains_key(to);
+        if from != to && !force && taken {
+            bail!("Config for '{}' already exists, use --force", to);
+        }
+
+        if let Some(mut project) = self.data.projects.remove(from) {
+            project.last_used = Self::current_timestamp();
+            self.data.projects.insert(to.to_string(), project);
+        }
+        match self.data.profiles.remove(from) {
+            Some(profiles) => self.data.profiles.insert(to.to_string(), profiles),
+            None => self.data.profiles.remove(to),
+        };
+        Ok(())
     }
 
     pub fn list_projects(&self) -> Vec<(&String, &ProjectSettings)> {
         let mut projects: Vec<_> = self.data.projects.iter().collect();
-        projects.sort_by(|a, b| b.1.last_used.cmp(&a.1.last_used));
+        projects.sort_by_key(|(_, proj)| std::cmp::Reverse(proj.last_used));
         projects
     }
 
-    pub fn cleanup_old_projects(&mut self, days: u64) {
+    /// `list_projects` narrowed to paths matching `pattern` (where `*`
+    /// stays within one path component) and to `runtime`, cut off after
+    /// `limit` entries
+    pub fn filter_projects(
+        &self,
+        pattern: Option<&glob::Pattern>,
+        runtime: Option<&str>,
+        limit: Option<usize>,
+    ) -> Vec<(&String, &ProjectSettings)> {
+        let options = glob::MatchOptions {
+            require_literal_separator: true,
+            ..Default::default()
+        };
+        self.list_projects()
+            .into_iter()
+            .filter(|(path, proj)| {
+                pattern.is_none_or(|pattern| pattern.matches_with(path, options))
+                    && runtime.is_none_or(|runtime| proj.runtime == runtime)
+            })
+            .take(limit.unwrap_or(usize::MAX))
+            .collect()
+    }
+
+    /// Projects whose saved memory is strictly above `above` and strictly
+    /// below `below` MB, in `list_projects` order. Unparseable values never match.
+    pub fn find_projects(
+        &self,
+        above: Option<u64>,
+        below: Option<u64>,
+    ) -> Vec<(&String, &ProjectSettings)> {
+        self.list_projects()
+            .into_iter()
+            .filter(|(_, proj)| {
+                proj.memory.parse::<u64>().is_ok_and(|mb| {
+                    above.is_none_or(|min| mb > min) && below.is_none_or(|max| mb < max)
+                })
+            })
+            .collect()
+    }
+
+    /// Configured memory in MB per project, largest first, optionally
+    /// restricted to one runtime. Non-numeric memory values are skipped.
+    pub fn memory_by_project(&self, runtime: Option<&str>) -> Vec<(&String, u64)> {
+        let mut projects: Vec<_> = self
+            .data
+            .projects
+            .iter()
+            .filter(|(_, proj)| runtime.is_none_or(|rt| proj.runtime == rt))
+            .filter_map(|(path, proj)| Some((path, proj.memory.parse::<u64>().ok()?)))
+            .collect();
+        projects.sort_by_key(|(_, mb)| std::cmp::Reverse(*mb));
+        projects
+    }
+
+    pub fn stats(&self) -> ProjectStats {
+        self.stats_at(Self::current_timestamp())
+    }
+
+    fn stats_at(&self, now: u64) -> ProjectStats {
+        let projects = &self.data.projects;
+        let mut by_runtime = BTreeMap::new();
+        for project in projects.values() {
+            *by_runtime.entry(project.runtime.clone()).or_insert(0) += 1;
+        }
+
+        let mut memory: Vec<u64> = projects
+            .values()
+            .filter_map(|project| project.memory.parse().ok())
+            .collect();
+        memory.sort_unstable();
+        let median_memory = match memory.len() {
+            0 => None,
+            n if n % 2 == 1 => Some(memory[n / 2]),
+            n => Some((memory[n / 2 - 1] + memory[n / 2]) / 2),
+        };
+
+        let cutoff = now.saturating_sub(STALE_DAYS * 24 * 60 * 60);
+        ProjectStats {
+            count: projects.len(),
+            by_runtime,
+            average_memory: (!memory.is_empty())
+                .then(|| memory.iter().sum::<u64>() / memory.len() as u64),
+            median_memory,
+            max_memory: memory.last().copied(),
+            stale: projects
+                .values()
+                .filter(|project| project.last_used < cutoff)
+                .count(),
+        }
+    }
+
+    /// Lower the memory saved for `path` to 3/4 (not below
+    /// `DECREASE_FLOOR_MB`, nor below the last peak plus
+    /// `PEAK_HEADROOM_PERCENT`) once its last `DECREASE_AFTER_RUNS` runs all
+    /// succeeded with it. Only changes the entry with `apply`; returns the
+    /// old and new value either way.
+    pub fn maybe_decrease_project_memory(
+        &mut self,
+        path: &str,
+        apply: bool,
+    ) -> Option<(String, String)> {
+        let project = self.data.projects.get_mut(path)?;
+        let current_mb = project.memory.parse::<u64>().ok()?;
+
+        let recent = project.history.iter().rev().take(DECREASE_AFTER_RUNS);
+        let streak = recent
+            .filter(|run| run.success && run.memory == project.memory)
+            .count();
+        if streak < DECREASE_AFTER_RUNS {
+            return None;
+        }
+
+        let peak_mb = project.last_peak_mb.unwrap_or(0) * (100 + PEAK_HEADROOM_PERCENT) / 100;
+        let new_mb = (current_mb * 3 / 4).max(peak_mb).max(DECREASE_FLOOR_MB);
+        if new_mb >= current_mb {
+            return None;
+        }
+
+        let old_memory = project.memory.clone();
+        if apply {
+            project.memory = new_mb.to_string();
+            project.percent = None;
+        }
+        Some((old_memory, new_mb.to_string()))
+    }
+
+    /// Saved project paths that no longer exist on disk, sorted
+    pub fn missing_projects(&self) -> Vec<String> {
+        self.data
+            .projects
+            .keys()
+            .filter(|path| !Path::new(path).exists())
+            .cloned()
+            .collect()
+    }
+
+    /// Everything saved, for `frc export-config`
+    pub fn data(&self) -> &StorageData {
+        &self.data
+    }
+
+    /// Take over `data` from another machine. Without `merge` it replaces
+    /// everything saved; with it, an entry saved at the same path (or
+    /// profile) is kept unless the imported one was used more recently,
+    /// and local defaults win. Returns how many project entries changed.
+    pub fn import(&mut self, data: StorageData, merge: bool) -> usize {
+        if !merge {
+            let count = data.projects.len();
+            self.data = data;
+            return count;
+        }
+
+        let newer = |saved: Option<&ProjectSettings>, incoming: &ProjectSettings| {
+            saved.is_none_or(|saved| incoming.last_used > saved.last_used)
+        };
+
+        let mut changed = 0;
+        for (path, incoming) in data.projects {
+            if newer(self.data.projects.get(&path), &incoming) {
+                self.data.projects.insert(path, incoming);
+                changed += 1;
+            }
+        }
+        for (path, profiles) in data.profiles {
+            let saved = self.data.profiles.entry(path).or_default();
+            for (name, incoming) in profiles {
+                if newer(saved.get(&name), &incoming) {
+                    saved.insert(name, incoming);
+                }
+            }
+        }
+        for (runtime, memory) in data.defaults {
+            self.data.defaults.entry(runtime).or_insert(memory);
+        }
+        if self.data.global_default.is_none() {
+            self.data.global_default = data.global_default;
+        }
+        changed
+    }
+
+    /// Remove entries not used in `days`; with `runtime`, only those
+    /// saved for that runtime
+    pub fn cleanup_old_projects(&mut self, days: u64, runtime: Option<&str>) {
+        let is_old = Self::is_old(days, runtime);
+        self.data.projects.retain(|_, proj| !is_old(proj));
+        self.data.profiles.retain(|_, profiles| {
+            profiles.retain(|_, profile| !is_old(profile));
+            !profiles.is_empty()
+        });
+    }
+
+    /// Paths `cleanup_old_projects` would remove
+    pub fn old_projects(&self, days: u64, runtime: Option<&str>) -> Vec<&String> {
+        let is_old = Self::is_old(days, runtime);
+        self.data
+            .projects
+            .iter()
+            .filter(|(_, proj)| is_old(proj))
+            .map(|(path, _)| path)
+            .collect()
+    }
+
+    fn is_old(days: u64, runtime: Option<&str>) -> impl Fn(&ProjectSettings) -> bool {
         let cutoff = Self::current_timestamp() - (days * 24 * 60 * 60);
-        self.data.projects.retain(|_, proj| proj.last_used > cutoff);
+        move |proj| {
+            proj.last_used <= cutoff && runtime.is_none_or(|runtime| proj.runtime == runtime)
+        }
     }
 
-    pub fn increase_project_memory(&mut self, path: &str) -> Option<(String, String)> {
+    /// Raise the memory saved for `path`, capped at 90% of `system_mb` and
+    /// at `ceiling` MB.
+    /// An entry found through `key` is copied to `path` first, so the
+    /// increase lands on the new location.
+    pub fn increase_project_memory(
+        &mut self,
+        path: &str,
+        key: Option<&str>,
+        system_mb: u64,
+        ceiling: Option<u64>,
+    ) -> Option<MemoryIncrease> {
+        let found = self.project_path(path, key)?;
+        if found != path {
+            let project = self.data.projects[found].clone();
+            self.data.projects.insert(path.to_string(), project);
+        }
+
         let project = self.data.projects.get_mut(path)?;
+        Self::increase_memory(project, system_mb, ceiling)
+    }
+
+    /// Like `increase_project_memory`, for profile `name` of `path`
+    pub fn increase_profile_memory(
+        &mut self,
+        path: &str,
+        name: &str,
+        system_mb: u64,
+        ceiling: Option<u64>,
+    ) -> Option<MemoryIncrease> {
+        let profile = self.data.profiles.get_mut(path)?.get_mut(name)?;
+        Self::increase_memory(profile, system_mb, ceiling)
+    }
+
+    fn increase_memory(
+        project: &mut ProjectSettings,
+        system_mb: u64,
+        ceiling: Option<u64>,
+    ) -> Option<MemoryIncrease> {
         let old_memory = project.memory.clone();
         let current_mb = old_memory.parse::<u64>().ok()?;
 
+        let system_ceiling = system_mb * MAX_SYSTEM_SHARE_PERCENT / 100;
+        let ceiling = ceiling.map_or(system_ceiling, |ceiling| ceiling.min(system_ceiling));
+        if current_mb >= ceiling {
+            return Some(MemoryIncrease::AtCeiling { memory: old_memory });
+        }
+
         let increase_50 = (current_mb as f64 * 1.5) as u64;
         let increase_2gb = current_mb + 2048;
-        let new_memory = increase_50.max(increase_2gb);
+        let new_memory = increase_50.max(increase_2gb).min(ceiling);
 
         project.memory = new_memory.to_string();
-        Some((old_memory, new_memory.to_string()))
+        project.percent = None;
+        Some(MemoryIncrease::Increased {
+            old: old_memory,
+            new: new_memory.to_string(),
+        })
+    }
+}
+
+/// Apply to `target` each entry that differs between `loaded` and
+/// `current`: changed and added entries are written, removed ones removed
+fn merge_changes<V: Clone + PartialEq>(
+    target: &mut BTreeMap<String, V>,
+    loaded: &BTreeMap<String, V>,
+    current: &BTreeMap<String, V>,
+) {
+    for key in loaded.keys().chain(current.keys()) {
+        match current.get(key) {
+            value if value == loaded.get(key) => {}
+            Some(value) => {
+                target.insert(key.clone(), value.clone());
+            }
+            None => {
+                target.remove(key);
+            }
+        }
     }
 }
 
@@ -70,14 +659,128 @@ impl Config {
 mod tests {
     use super::*;
 
+    /// Large enough that the auto-increase cap doesn't apply
+    const SYSTEM_MB: u64 = 1024 * 1024;
+
     fn create_test_config() -> Config {
-        Config {
-            data: StorageData {
-                projects: std::collections::HashMap::new(),
-            },
+        Config::default()
+    }
+
+    #[test]
+    fn test_concurrent_scoped_saves_keep_all_entries() {
+        let dir = std::env::temp_dir().join(format!("frc-lock-{}", std::process::id()));
+        let file = dir.join("config.json");
+
+        let writers: Vec<_> = ["/a", "/b"]
+            .into_iter()
+            .map(|prefix| {
+                let file = file.clone();
+                std::thread::spawn(move || {
+                    for i in 0..20 {
+                        let path = format!("{}/{}", prefix, i);
+                        let mut config = Config::default();
+                        config.save_project(path, "node".to_string(), "2048".to_string());
+                        config.save_to(&file).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        let saved = Storage::load_from(&file).unwrap();
+        assert_eq!(saved.projects.len(), 40);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_concurrent_full_saves_keep_other_entries() {
+        let dir = std::env::temp_dir().join(format!("frc-lock-full-{}", std::process::id()));
+        let file = dir.join("config.json");
+        let mut seed = Config::default();
+        seed.save_project("/stale".to_string(), "node".to_string(), "1024".to_string());
+        seed.save_to(&file).unwrap();
+
+        let scoped = {
+            let file = file.clone();
+            std::thread::spawn(move || {
+                for i in 0..20 {
+                    let mut config = Config::default();
+                    let path = format!("/b/{}", i);
+                    config.save_project(path, "node".to_string(), "2048".to_string());
+                    config.save_to(&file).unwrap();
+                }
+            })
+        };
+        // Loads the whole file, edits it and saves, like `forget` or
+        // `set-default`, while the other writer adds entries
+        let full = {
+            let file = file.clone();
+            std::thread::spawn(move || {
+                for i in 0..20 {
+                    let mut config = Config::from_loaded(Storage::load_from(&file).unwrap());
+                    config.remove_project("/stale");
+                    config.set_runtime_default("node".to_string(), i.to_string());
+                    std::thread::sleep(std::time::Duration::from_millis(2));
+                    config.save_to(&file).unwrap();
+                }
+            })
+        };
+        scoped.join().unwrap();
+        full.join().unwrap();
+
+        let saved = Storage::load_from(&file).unwrap();
+        assert_eq!(saved.projects.len(), 20);
+        assert!(!saved.projects.contains_key("/stale"));
+        assert_eq!(saved.defaults["node"], "19");
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_runtime_version_invalidated_by_modified_time() {
+        let mut config = create_test_config();
+        assert_eq!(config.runtime_version("/usr/bin/node", 100), None);
+
+        config.set_runtime_version("/usr/bin/node".to_string(), 100, "20.11.1".to_string());
+        let saved = config.runtime_version("/usr/bin/node", 100);
+        assert_eq!(saved, Some("20.11.1"));
+        // A reinstalled binary has to be asked again
+        assert_eq!(config.runtime_version("/usr/bin/node", 200), None);
+        assert_eq!(config.runtime_version("/opt/node/bin/node", 100), None);
+    }
+
+    fn increased(old: &str, new: &str) -> MemoryIncrease {
+        MemoryIncrease::Increased {
+            old: old.to_string(),
+            new: new.to_string(),
         }
     }
 
+    #[test]
+    fn test_global_default() {
+        let mut config = create_test_config();
+        assert!(config.get_global_default().is_none());
+
+        config.set_global_default("4096".to_string());
+        config.set_global_default("6144".to_string());
+        assert_eq!(config.get_global_default().unwrap().memory, "6144");
+        assert!(config.list_projects().is_empty());
+    }
+
+    #[test]
+    fn test_runtime_default() {
+        let mut config = create_test_config();
+        assert!(config.get_runtime_default("node").is_none());
+
+        config.set_runtime_default("node".to_string(), "6144".to_string());
+        assert_eq!(config.get_runtime_default("node"), Some("6144"));
+        assert!(config.get_runtime_default("deno").is_none());
+        assert!(config.get_global_default().is_none());
+    }
+
     #[test]
     fn test_save_and_get_project() {
         let mut config = create_test_config();
@@ -88,9 +791,18 @@ mod tests {
             "8192".to_string(),
         );
 
-        let project = config.get_project("/path/to/project").unwrap();
+        let project = config.get_project("/path/to/project", None).unwrap();
         assert_eq!(project.runtime, "node");
         assert_eq!(project.memory, "8192");
+        assert_eq!(project.created_by_version, env!("CARGO_PKG_VERSION"));
+
+        // Re-saving keeps the version that created the entry
+        let path = "/path/to/project".to_string();
+        let saved = config.data.projects.get_mut(&path).unwrap();
+        saved.created_by_version = "0.1.0".to_string();
+        config.save_project(path.clone(), "node".to_string(), "4096".to_string());
+        let project = config.get_project(&path, None).unwrap();
+        assert_eq!(project.created_by_version, "0.1.0");
     }
 
     #[test]
@@ -105,16 +817,24 @@ mod tests {
 
         assert!(config.remove_project("/path/to/project"));
         assert!(!config.remove_project("/path/to/project")); // Already removed
-        assert!(config.get_project("/path/to/project").is_none());
+        assert!(config.get_project("/path/to/project", None).is_none());
     }
 
     #[test]
     fn test_list_projects() {
         let mut config = create_test_config();
 
-        config.save_project("/project-a".to_string(), "node".to_string(), "4096".to_string());
+        config.save_project(
+            "/project-a".to_string(),
+            "node".to_string(),
+            "4096".to_string(),
+        );
         std::thread::sleep(std::time::Duration::from_secs(1));
-        config.save_project("/project-b".to_string(), "deno".to_string(), "8192".to_string());
+        config.save_project(
+            "/project-b".to_string(),
+            "deno".to_string(),
+            "8192".to_string(),
+        );
 
         let projects = config.list_projects();
         assert_eq!(projects.len(), 2);
@@ -124,6 +844,476 @@ mod tests {
         assert_eq!(projects[1].0, "/project-a");
     }
 
+    #[test]
+    fn test_filter_projects() {
+        let mut config = create_test_config();
+        let projects = [
+            ("/work/web", "node"),
+            ("/work/api", "deno"),
+            ("/work/tools/cli", "node"),
+            ("/home/site", "node"),
+        ];
+        for (i, (path, runtime)) in projects.into_iter().enumerate() {
+            config.save_project(path.to_string(), runtime.to_string(), "2048".to_string());
+            config.data.projects.get_mut(path).unwrap().last_used = 1000 + i as u64;
+        }
+
+        let filter = |pattern: Option<&str>, runtime: Option<&str>, limit: Option<usize>| {
+            let pattern = pattern.map(|p| glob::Pattern::new(p).unwrap());
+            config
+                .filter_projects(pattern.as_ref(), runtime, limit)
+                .into_iter()
+                .map(|(path, _)| path.clone())
+                .collect::<Vec<_>>()
+        };
+
+        // Newest first, like `list_projects`
+        assert_eq!(
+            filter(None, None, None),
+            ["/home/site", "/work/tools/cli", "/work/api", "/work/web"]
+        );
+        // `*` doesn't cross into subdirectories, `**` does
+        let work = filter(Some("/work/*"), None, None);
+        assert_eq!(work, ["/work/api", "/work/web"]);
+        assert_eq!(filter(Some("/work/**/*"), None, None).len(), 3);
+        assert_eq!(filter(Some("/work/*"), Some("node"), None), ["/work/web"]);
+        let newest_node = filter(None, Some("node"), Some(2));
+        assert_eq!(newest_node, ["/home/site", "/work/tools/cli"]);
+        assert_eq!(filter(None, None, Some(0)), Vec::<String>::new());
+        assert!(filter(Some("/nowhere/*"), None, None).is_empty());
+    }
+
+    #[test]
+    fn test_memory_by_project() {
+        let mut config = create_test_config();
+
+        config.save_project("/small".to_string(), "node".to_string(), "2048".to_string());
+        config.save_project("/large".to_string(), "node".to_string(), "8192".to_string());
+        config.save_project("/deno".to_string(), "deno".to_string(), "4096".to_string());
+        config.save_project("/bad".to_string(), "node".to_string(), "lots".to_string());
+
+        let all = config.memory_by_project(None);
+        let paths: Vec<_> = all.iter().map(|(path, _)| path.as_str()).collect();
+        assert_eq!(paths, ["/large", "/deno", "/small"]);
+        assert_eq!(all.iter().map(|(_, mb)| mb).sum::<u64>(), 14336);
+
+        let node = config.memory_by_project(Some("node"));
+        assert_eq!(node.len(), 2);
+        assert_eq!(node[0], (&"/large".to_string(), 8192));
+    }
+
+    #[test]
+    fn test_stats() {
+        const DAY: u64 = 24 * 60 * 60;
+        let now = 100 * DAY;
+        let project = |runtime: &str, memory: &str, age_days: u64| ProjectSettings {
+            runtime: runtime.to_string(),
+            memory: memory.to_string(),
+            last_used: now - age_days * DAY,
+            ..Default::default()
+        };
+        let mut data = StorageData::default();
+        data.projects.extend([
+            ("/a".to_string(), project("node", "2048", 1)),
+            ("/b".to_string(), project("node", "4096", 45)),
+            ("/c".to_string(), project("deno", "8192", 2)),
+            ("/d".to_string(), project("bun", "3072", 31)),
+            ("/e".to_string(), project("node", "lots", 0)),
+        ]);
+        let config = Config::from_loaded(data);
+
+        let stats = config.stats_at(now);
+        assert_eq!(stats.count, 5);
+        let runtimes = [("bun", 1), ("deno", 1), ("node", 3)];
+        let by_runtime = runtimes.map(|(rt, n)| (rt.to_string(), n));
+        assert_eq!(stats.by_runtime, BTreeMap::from(by_runtime));
+        assert_eq!(stats.average_memory, Some(4352));
+        assert_eq!(stats.median_memory, Some(3584));
+        assert_eq!(stats.max_memory, Some(8192));
+        assert_eq!(stats.stale, 2);
+    }
+
+    #[test]
+    fn test_stats_empty() {
+        let stats = create_test_config().stats();
+        assert_eq!(stats.count, 0);
+        assert!(stats.by_runtime.is_empty());
+        assert_eq!(stats.average_memory, None);
+        assert_eq!(stats.median_memory, None);
+        assert_eq!(stats.max_memory, None);
+        assert_eq!(stats.stale, 0);
+    }
+
+    #[test]
+    fn test_find_projects() {
+        let mut config = create_test_config();
+
+        config.save_project("/small".to_string(), "node".to_string(), "2048".to_string());
+        config.save_project("/mid".to_string(), "node".to_string(), "4096".to_string());
+        config.save_project("/large".to_string(), "deno".to_string(), "8192".to_string());
+        config.save_project("/bad".to_string(), "node".to_string(), "lots".to_string());
+
+        let paths = |found: Vec<(&String, &ProjectSettings)>| {
+            let mut paths: Vec<_> = found.into_iter().map(|(path, _)| path.clone()).collect();
+            paths.sort();
+            paths
+        };
+
+        assert_eq!(paths(config.find_projects(Some(4096), None)), ["/large"]);
+        assert_eq!(paths(config.find_projects(Some(2047), None)).len(), 3);
+        assert_eq!(paths(config.find_projects(None, Some(4096))), ["/small"]);
+        let between = config.find_projects(Some(2048), Some(8192));
+        assert_eq!(paths(between), ["/mid"]);
+        assert!(config.find_projects(Some(8192), None).is_empty());
+    }
+
+    #[test]
+    fn test_get_project_falls_back_to_key() {
+        let mut config = create_test_config();
+        let key = Some("npm:web");
+
+        config.save_project("/a/web".to_string(), "node".to_string(), "6144".to_string());
+        config.set_project_key("/a/web", key.map(str::to_string));
+
+        // Same key at a new path finds the moved config
+        let project = config.get_project("/b/web", key).unwrap();
+        assert_eq!(project.memory, "6144");
+        assert!(config.get_project("/b/web", None).is_none());
+        assert!(config.get_project("/b/web", Some("npm:api")).is_none());
+
+        // An entry at the exact path wins over the key
+        config.save_project("/b/web".to_string(), "node".to_string(), "2048".to_string());
+        assert_eq!(config.get_project("/b/web", key).unwrap().memory, "2048");
+
+        // Re-saving keeps the key
+        config.save_project("/a/web".to_string(), "node".to_string(), "8192".to_string());
+        let project = config.get_project("/a/web", None).unwrap();
+        assert_eq!(project.key.as_deref(), key);
+    }
+
+    #[test]
+    fn test_increase_project_memory_after_move() {
+        let mut config = create_test_config();
+        config.save_project("/a/web".to_string(), "node".to_string(), "4096".to_string());
+        config.set_project_key("/a/web", Some("npm:web".to_string()));
+
+        let increase = config
+            .increase_project_memory("/b/web", Some("npm:web"), SYSTEM_MB, None)
+            .unwrap();
+        assert_eq!(increase, increased("4096", "6144"));
+        assert_eq!(config.get_project("/b/web", None).unwrap().memory, "6144");
+        assert_eq!(config.get_project("/a/web", None).unwrap().memory, "4096");
+    }
+
+    #[test]
+    fn test_record_run() {
+        let mut config = create_test_config();
+        assert!(!config.record_run("/project", Some("4096"), true));
+
+        config.save_project(
+            "/project".to_string(),
+            "node".to_string(),
+            "4096".to_string(),
+        );
+        assert!(config.record_run_at("/project", None, true, 100));
+        assert!(config.record_run_at("/project", Some("6144"), false, 200));
+
+        let project = config.get_project("/project", None).unwrap();
+        assert_eq!(project.run_count, 2);
+        assert_eq!(project.last_used, 200);
+        let memory: Vec<_> = project.history.iter().map(|r| r.memory.as_str()).collect();
+        assert_eq!(memory, ["4096", "6144"]);
+
+        // Saving new memory keeps the counter and history
+        let (runtime, memory) = ("node".to_string(), "8192".to_string());
+        config.save_project("/project".to_string(), runtime, memory);
+        let project = config.get_project("/project", None).unwrap();
+        assert_eq!(project.run_count, 2);
+        assert_eq!(project.history.len(), 2);
+    }
+
+    #[test]
+    fn test_touch_profile() {
+        let mut config = create_test_config();
+        assert!(!config.touch_profile("/project", "build", "node"));
+
+        let (runtime, memory) = ("node".to_string(), "8192".to_string());
+        config.save_profile("/project", "build", runtime, memory);
+        let saved_at = config.get_profile("/project", "build").unwrap().last_used;
+
+        assert!(!config.touch_profile_at("/project", "build", "deno", saved_at + 60));
+        assert!(!config.touch_profile_at("/project", "build", "node", saved_at));
+        assert!(config.touch_profile_at("/project", "build", "node", saved_at + 60));
+        let profile = config.get_profile("/project", "build").unwrap();
+        assert_eq!(profile.last_used, saved_at + 60);
+        assert_eq!(profile.run_count, 0);
+    }
+
+    #[test]
+    fn test_record_run_caps_history() {
+        let mut config = create_test_config();
+        config.save_project(
+            "/project".to_string(),
+            "node".to_string(),
+            "4096".to_string(),
+        );
+
+        for now in 1..=25 {
+            config.record_run_at("/project", Some(&now.to_string()), true, now);
+        }
+
+        let project = config.get_project("/project", None).unwrap();
+        assert_eq!(project.run_count, 25);
+        assert_eq!(project.history.len(), HISTORY_LIMIT);
+        assert_eq!(project.history[0].timestamp, 16);
+        assert_eq!(project.history.last().unwrap().memory, "25");
+    }
+
+    #[test]
+    fn test_maybe_decrease_project_memory() {
+        let mut config = create_test_config();
+        let (runtime, memory) = ("node".to_string(), "8192".to_string());
+        config.save_project("/project".to_string(), runtime, memory);
+
+        for now in 1..DECREASE_AFTER_RUNS as u64 {
+            config.record_run_at("/project", None, true, now);
+        }
+        assert_eq!(config.maybe_decrease_project_memory("/project", true), None);
+
+        config.record_run_at("/project", None, true, 10);
+        let suggested = config.maybe_decrease_project_memory("/project", false);
+        assert_eq!(suggested, Some(("8192".to_string(), "6144".to_string())));
+        assert_eq!(config.get_project("/project", None).unwrap().memory, "8192");
+
+        let applied = config.maybe_decrease_project_memory("/project", true);
+        assert_eq!(applied, suggested);
+        assert_eq!(config.get_project("/project", None).unwrap().memory, "6144");
+
+        // The streak has to be rebuilt at the new value
+        assert_eq!(config.maybe_decrease_project_memory("/project", true), None);
+    }
+
+    #[test]
+    fn test_maybe_decrease_needs_consecutive_successes() {
+        let mut config = create_test_config();
+        config.save_project(
+            "/project".to_string(),
+            "node".to_string(),
+            "4096".to_string(),
+        );
+
+        for now in 1..=DECREASE_AFTER_RUNS as u64 {
+            let success = now != 3;
+            config.record_run_at("/project", None, success, now);
+        }
+        assert_eq!(config.maybe_decrease_project_memory("/project", true), None);
+        assert_eq!(config.maybe_decrease_project_memory("/missing", true), None);
+    }
+
+    #[test]
+    fn test_maybe_decrease_respects_floor() {
+        let mut config = create_test_config();
+        config.save_project("/small".to_string(), "node".to_string(), "1024".to_string());
+        config.save_project("/mid".to_string(), "node".to_string(), "1200".to_string());
+        for now in 1..=DECREASE_AFTER_RUNS as u64 {
+            config.record_run_at("/small", None, true, now);
+            config.record_run_at("/mid", None, true, now);
+        }
+
+        assert_eq!(config.maybe_decrease_project_memory("/small", true), None);
+        let mid = config.maybe_decrease_project_memory("/mid", true);
+        assert_eq!(mid, Some(("1200".to_string(), "1024".to_string())));
+    }
+
+    #[test]
+    fn test_maybe_decrease_keeps_headroom_over_peak() {
+        let mut config = create_test_config();
+        let (runtime, memory) = ("node".to_string(), "8192".to_string());
+        config.save_project("/project".to_string(), runtime, memory);
+        for now in 1..=DECREASE_AFTER_RUNS as u64 {
+            config.record_run_at("/project", None, true, now);
+        }
+
+        config.set_project_peak("/project", 7000);
+        assert_eq!(config.maybe_decrease_project_memory("/project", true), None);
+
+        config.set_project_peak("/project", 5000);
+        let lowered = config.maybe_decrease_project_memory("/project", true);
+        assert_eq!(lowered, Some(("8192".to_string(), "6250".to_string())));
+        let project = config.get_project("/project", None).unwrap();
+        assert_eq!(project.last_peak_mb, Some(5000));
+    }
+
+    #[test]
+    fn test_profiles() {
+        let mut config = create_test_config();
+        let node = || "node".to_string();
+        config.save_project("/project".to_string(), node(), "2048".to_string());
+        config.save_profile("/project", "build", node(), "8192".to_string());
+        config.save_profile("/project", "dev", node(), "4096".to_string());
+
+        // The default profile is the project entry, untouched by named ones
+        let memory =
+            |config: &Config, name| config.get_profile("/project", name).unwrap().memory.clone();
+        assert_eq!(config.get_project("/project", None).unwrap().memory, "2048");
+        assert_eq!(memory(&config, "build"), "8192");
+        assert!(config.get_profile("/project", "test").is_none());
+        assert!(config.get_profile("/other", "build").is_none());
+
+        let profiles = config.list_profiles("/project");
+        let names: Vec<_> = profiles.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["build", "dev"]);
+        assert!(config.list_profiles("/other").is_empty());
+
+        config.save_profile("/project", "build", node(), "6144".to_string());
+        assert_eq!(memory(&config, "build"), "6144");
+
+        let raised = config.increase_profile_memory("/project", "dev", SYSTEM_MB, None);
+        assert_eq!(raised, Some(increased("4096", "6144")));
+        assert_eq!(config.get_project("/project", None).unwrap().memory, "2048");
+    }
+
+    #[test]
+    fn test_profiles_follow_their_project() {
+        let mut config = create_test_config();
+        let (runtime, memory) = ("node".to_string(), "2048".to_string());
+        config.save_project("/old".to_string(), runtime, memory);
+        config.save_profile("/old", "build", "node".to_string(), "8192".to_string());
+
+        config.rename_project("/old", "/new", false).unwrap();
+        assert!(config.list_profiles("/old").is_empty());
+        assert_eq!(config.get_profile("/new", "build").unwrap().memory, "8192");
+
+        assert!(config.remove_project("/new"));
+        assert!(config.list_profiles("/new").is_empty());
+    }
+
+    fn imported(memory: &str, last_used: u64) -> ProjectSettings {
+        ProjectSettings {
+            runtime: "node".to_string(),
+            memory: memory.to_string(),
+            last_used,
+            ..Default::default()
+        }
+    }
+
+    /// Local config has `/a` (used at 100) and `/b` (used at 300)
+    fn config_for_import() -> (Config, StorageData) {
+        let mut config = create_test_config();
+        let projects = &mut config.data.projects;
+        projects.insert("/a".to_string(), imported("2048", 100));
+        projects.insert("/b".to_string(), imported("4096", 300));
+        config.set_global_default("1024".to_string());
+
+        let mut incoming = StorageData::default();
+        let projects = &mut incoming.projects;
+        projects.insert("/a".to_string(), imported("3072", 200));
+        projects.insert("/b".to_string(), imported("8192", 200));
+        projects.insert("/c".to_string(), imported("6144", 50));
+        incoming.global_default = Some(DefaultSettings {
+            memory: "512".to_string(),
+        });
+        (config, incoming)
+    }
+
+    #[test]
+    fn test_import_replace() {
+        let (mut config, incoming) = config_for_import();
+        assert_eq!(config.import(incoming, false), 3);
+
+        assert_eq!(config.get_project("/a", None).unwrap().memory, "3072");
+        assert_eq!(config.get_project("/b", None).unwrap().memory, "8192");
+        assert_eq!(config.get_project("/c", None).unwrap().memory, "6144");
+        assert_eq!(config.get_global_default().unwrap().memory, "512");
+    }
+
+    #[test]
+    fn test_import_merge_keeps_newer() {
+        let (mut config, mut incoming) = config_for_import();
+        config.save_profile("/a", "build", "node".to_string(), "4096".to_string());
+        incoming
+            .profiles
+            .entry("/a".to_string())
+            .or_default()
+            .insert("build".to_string(), imported("1024", 1));
+        assert_eq!(config.import(incoming, true), 2);
+
+        // Imported entry was used more recently than the local one
+        assert_eq!(config.get_project("/a", None).unwrap().memory, "3072");
+        // Local entry is newer and stays
+        assert_eq!(config.get_project("/b", None).unwrap().memory, "4096");
+        assert_eq!(config.get_project("/c", None).unwrap().memory, "6144");
+        assert_eq!(config.get_profile("/a", "build").unwrap().memory, "4096");
+        assert_eq!(config.get_global_default().unwrap().memory, "1024");
+    }
+
+    #[test]
+    fn test_rename_project() {
+        let mut config = create_test_config();
+        config.data.projects.insert(
+            "/old".to_string(),
+            ProjectSettings {
+                runtime: "deno".to_string(),
+                memory: "4096".to_string(),
+                last_used: 1000,
+                key: Some("npm:web".to_string()),
+                ..Default::default()
+            },
+        );
+
+        config.rename_project("/old", "/new", false).unwrap();
+        assert!(config.get_project("/old", None).is_none());
+        let moved = config.get_project("/new", None).unwrap();
+        assert_eq!(moved.runtime, "deno");
+        assert_eq!(moved.memory, "4096");
+        assert_eq!(moved.key.as_deref(), Some("npm:web"));
+        assert!(moved.last_used > 1000);
+    }
+
+    #[test]
+    fn test_rename_project_missing_source() {
+        let mut config = create_test_config();
+        config.save_project("/other".to_string(), "node".to_string(), "2048".to_string());
+
+        let err = config.rename_project("/old", "/new", false).unwrap_err();
+        assert!(err.to_string().contains("No config found for '/old'"));
+        assert!(config.get_project("/new", None).is_none());
+    }
+
+    #[test]
+    fn test_rename_project_existing_destination() {
+        let mut config = create_test_config();
+        config.save_project("/old".to_string(), "node".to_string(), "4096".to_string());
+        config.save_project("/new".to_string(), "node".to_string(), "2048".to_string());
+
+        let err = config.rename_project("/old", "/new", false).unwrap_err();
+        assert!(err.to_string().contains("--force"));
+        assert_eq!(config.get_project("/old", None).unwrap().memory, "4096");
+        assert_eq!(config.get_project("/new", None).unwrap().memory, "2048");
+
+        config.rename_project("/old", "/new", true).unwrap();
+        assert!(config.get_project("/old", None).is_none());
+        assert_eq!(config.get_project("/new", None).unwrap().memory, "4096");
+    }
+
+    #[test]
+    fn test_rename_project_existing_destination_profiles() {
+        let mut config = create_test_config();
+        config.save_project("/old".to_string(), "node".to_string(), "4096".to_string());
+        let (runtime, memory) = ("node".to_string(), "8192".to_string());
+        config.save_profile("/new", "build", runtime, memory);
+
+        // Only profiles are saved for `/new`, and they survive the refusal
+        let err = config.rename_project("/old", "/new", false).unwrap_err();
+        assert!(err.to_string().contains("--force"));
+        assert!(config.get_project("/old", None).is_some());
+        assert!(config.get_profile("/new", "build").is_some());
+
+        config.rename_project("/old", "/new", true).unwrap();
+        assert_eq!(config.get_project("/new", None).unwrap().memory, "4096");
+        assert!(config.get_profile("/new", "build").is_none());
+    }
+
     #[test]
     fn test_cleanup_old_projects() {
         let mut config = create_test_config();
@@ -135,29 +1325,137 @@ mod tests {
                 runtime: "node".to_string(),
                 memory: "4096".to_string(),
                 last_used: 1000, // Very old timestamp
+                ..Default::default()
             },
         );
 
         // Add recent project
-        config.save_project("/new-project".to_string(), "node".to_string(), "4096".to_string());
+        config.save_project(
+            "/new-project".to_string(),
+            "node".to_string(),
+            "4096".to_string(),
+        );
+
+        config.cleanup_old_projects(1, None); // Remove projects older than 1 day
+
+        assert!(config.get_project("/old-project", None).is_none());
+        assert!(config.get_project("/new-project", None).is_some());
+    }
+
+    #[test]
+    fn test_cleanup_old_projects_by_runtime() {
+        let mut config = create_test_config();
+        let entries = [
+            ("/old-bun", "bun", 1000),
+            ("/old-node", "node", 1000),
+            ("/new-bun", "bun", Config::current_timestamp()),
+        ];
+        for (path, runtime, last_used) in entries {
+            let project = ProjectSettings {
+                runtime: runtime.to_string(),
+                memory: "4096".to_string(),
+                last_used,
+                ..Default::default()
+            };
+            config.data.projects.insert(path.to_string(), project);
+        }
+
+        // Both criteria have to match
+        assert_eq!(config.old_projects(1, Some("bun")), ["/old-bun"]);
+        assert_eq!(config.old_projects(1, None), ["/old-bun", "/old-node"]);
+        config.cleanup_old_projects(1, Some("bun"));
+        let remaining: Vec<_> = config.data.projects.keys().map(String::as_str).collect();
+        assert_eq!(remaining, ["/new-bun", "/old-node"]);
+
+        // Age alone still applies without a runtime
+        config.cleanup_old_projects(1, None);
+        let remaining: Vec<_> = config.data.projects.keys().map(String::as_str).collect();
+        assert_eq!(remaining, ["/new-bun"]);
+
+        // A runtime with nothing old removes nothing
+        config.cleanup_old_projects(1, Some("deno"));
+        assert_eq!(config.data.projects.len(), 1);
+    }
+
+    #[test]
+    fn test_set_project_note() {
+        let mut config = create_test_config();
+        config.save_project("/app".to_string(), "node".to_string(), "16384".to_string());
+
+        let note = " webpack 4 needs it ";
+        config.set_project_note("/app", note).unwrap();
+        let project = config.get_project("/app", None).unwrap();
+        assert_eq!(project.note.as_deref(), Some("webpack 4 needs it"));
+
+        // Saving again keeps the note, an empty one clears it
+        config.save_project("/app".to_string(), "node".to_string(), "12288".to_string());
+        assert!(config.get_project("/app", None).unwrap().note.is_some());
+        config.set_project_note("/app", "").unwrap();
+        assert!(config.get_project("/app", None).unwrap().note.is_none());
+
+        let missing = config.set_project_note("/missing", "x");
+        assert!(missing.unwrap_err().to_string().contains("No config found"));
+    }
+
+    #[test]
+    fn test_set_project_runtime() {
+        let mut config = create_test_config();
+        config.save_project("/app".to_string(), "node".to_string(), "6144".to_string());
+
+        let previous = config.set_project_runtime("/app", "bun").unwrap();
+        assert_eq!(previous, "node");
+        let project = config.get_project("/app", None).unwrap();
+        assert_eq!(project.runtime, "bun");
+        assert_eq!(project.memory, "6144");
+
+        let missing = config.set_project_runtime("/missing", "bun");
+        assert!(missing.unwrap_err().to_string().contains("No config found"));
+        assert!(config.get_project("/missing", None).is_none());
+    }
+
+    #[test]
+    fn test_missing_projects() {
+        let base = std::env::temp_dir().join(format!("frc-prune-{}", std::process::id()));
+        let live = base.join("live");
+        let deleted = base.join("deleted");
+        std::fs::create_dir_all(&live).unwrap();
+        std::fs::create_dir_all(&deleted).unwrap();
+
+        let mut config = create_test_config();
+        for dir in [&live, &deleted] {
+            let path = dir.to_string_lossy().to_string();
+            config.save_project(path, "node".to_string(), "4096".to_string());
+        }
+        assert!(config.missing_projects().is_empty());
 
-        config.cleanup_old_projects(1); // Remove projects older than 1 day
+        std::fs::remove_dir_all(&deleted).unwrap();
+        let missing = config.missing_projects();
+        assert_eq!(missing, [deleted.to_string_lossy()]);
 
-        assert!(config.get_project("/old-project").is_none());
-        assert!(config.get_project("/new-project").is_some());
+        for path in &missing {
+            config.remove_project(path);
+        }
+        let remaining: Vec<_> = config.list_projects().into_iter().map(|(p, _)| p).collect();
+        assert_eq!(remaining, [&live.to_string_lossy().to_string()]);
+
+        std::fs::remove_dir_all(&base).ok();
     }
 
     #[test]
     fn test_increase_project_memory() {
         let mut config = create_test_config();
 
-        config.save_project("/project".to_string(), "node".to_string(), "4096".to_string());
+        config.save_project(
+            "/project".to_string(),
+            "node".to_string(),
+            "4096".to_string(),
+        );
 
-        let (old, new) = config.increase_project_memory("/project").unwrap();
-        assert_eq!(old, "4096");
-        assert_eq!(new, "6144"); // max(4096 * 1.5, 4096 + 2048) = 6144
+        let increase = config.increase_project_memory("/project", None, SYSTEM_MB, None);
+        // max(4096 * 1.5, 4096 + 2048) = 6144
+        assert_eq!(increase.unwrap(), increased("4096", "6144"));
 
-        let project = config.get_project("/project").unwrap();
+        let project = config.get_project("/project", None).unwrap();
         assert_eq!(project.memory, "6144");
     }
 
@@ -165,16 +1463,62 @@ mod tests {
     fn test_increase_project_memory_small_value() {
         let mut config = create_test_config();
 
-        config.save_project("/project".to_string(), "node".to_string(), "1024".to_string());
+        config.save_project(
+            "/project".to_string(),
+            "node".to_string(),
+            "1024".to_string(),
+        );
+
+        let increase = config.increase_project_memory("/project", None, SYSTEM_MB, None);
+        // max(1024 * 1.5, 1024 + 2048) = 3072
+        assert_eq!(increase.unwrap(), increased("1024", "3072"));
+    }
+
+    #[test]
+    fn test_increase_project_memory_is_capped() {
+        let mut config = create_test_config();
+
+        // 90% of 16 GB is 14745 MB
+        config.save_project(
+            "/project".to_string(),
+            "node".to_string(),
+            "12288".to_string(),
+        );
+        let increase = config.increase_project_memory("/project", None, 16384, None);
+        assert_eq!(increase.unwrap(), increased("12288", "14745"));
+
+        let increase = config.increase_project_memory("/project", None, 16384, None);
+        assert_eq!(
+            increase.unwrap(),
+            MemoryIncrease::AtCeiling {
+                memory: "14745".to_string()
+            }
+        );
+        let saved = config.get_project("/project", None).unwrap();
+        assert_eq!(saved.memory, "14745");
+    }
+
+    #[test]
+    fn test_increase_project_memory_stops_at_memory_ceiling() {
+        let mut config = create_test_config();
+        let (path, runtime) = ("/project".to_string(), "node".to_string());
+        config.save_project(path, runtime, "6144".to_string());
+
+        // 9216 MB would be next, but the ceiling is lower than 90% of the system
+        let increase = config.increase_project_memory("/project", None, SYSTEM_MB, Some(8192));
+        assert_eq!(increase.unwrap(), increased("6144", "8192"));
+        let increase = config.increase_project_memory("/project", None, SYSTEM_MB, Some(8192));
+        assert!(matches!(increase, Some(MemoryIncrease::AtCeiling { .. })));
 
-        let (old, new) = config.increase_project_memory("/project").unwrap();
-        assert_eq!(old, "1024");
-        assert_eq!(new, "3072"); // max(1024 * 1.5, 1024 + 2048) = 3072
+        // A ceiling above 90% of the system doesn't lift that limit
+        let increase = config.increase_project_memory("/project", None, 8192, Some(65536));
+        assert!(matches!(increase, Some(MemoryIncrease::AtCeiling { .. })));
     }
 
     #[test]
     fn test_increase_project_memory_nonexistent() {
         let mut config = create_test_config();
-        assert!(config.increase_project_memory("/nonexistent").is_none());
+        let result = config.increase_project_memory("/nonexistent", None, SYSTEM_MB, None);
+        assert!(result.is_none());
     }
 }
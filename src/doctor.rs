@@ -0,0 +1,202 @@
+//! Environment checks behind `frc doctor`
+
+use crate::runtime::{Runtime, Version};
+use anyhow::Result;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Status {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// Outcome of one check, printed as a single line
+#[derive(Debug, PartialEq)]
+pub struct Check {
+    pub status: Status,
+    pub name: String,
+    pub detail: String,
+}
+
+impl Check {
+    pub fn new(status: Status, name: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self {
+            status,
+            name: name.into(),
+            detail: detail.into(),
+        }
+    }
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let label = match self.status {
+            Status::Pass => "✅ pass",
+            Status::Warn => "⚠️  warn",
+            Status::Fail => "❌ fail",
+        };
+        write!(f, "{}  {}: {}", label, self.name, self.detail)
+    }
+}
+
+/// `runtime` with the version found on PATH, if any
+pub fn check_runtime(runtime: &Runtime, version: Option<Version>) -> Check {
+    let Some(version) = version else {
+        return Check::new(Status::Warn, runtime.name(), "not found on PATH");
+    };
+    match runtime.version_warning(version) {
+        Some(_) => Check::new(
+            Status::Warn,
+            runtime.name(),
+            format!("{} is too old for the memory settings", version),
+        ),
+        None => Check::new(Status::Pass, runtime.name(), version.to_string()),
+    }
+}
+
+/// At least one runtime has to be installed for frc to do anything
+pub fn check_any_runtime(found: usize) -> Option<Check> {
+    (found == 0).then(|| {
+        Check::new(
+            Status::Fail,
+            "runtimes",
+            "none of node, deno or bun is installed",
+        )
+    })
+}
+
+/// The directory holding the config file. A missing one is created on
+/// the first save, so only an unwritable location fails.
+pub fn check_config_dir(dir: &Path) -> Check {
+    let name = "config directory";
+    if !dir.exists() {
+        return Check::new(
+            Status::Warn,
+            name,
+            format!("{} is missing, created on first save", dir.display()),
+        );
+    }
+    if !dir.is_dir() {
+        return Check::new(
+            Status::Fail,
+            name,
+            format!("{} is not a directory", dir.display()),
+        );
+    }
+
+    let probe = dir.join(format!(".frc-doctor-{}", std::process::id()));
+    match fs::write(&probe, b"") {
+        Ok(()) => {
+            fs::remove_file(&probe).ok();
+            Check::new(Status::Pass, name, format!("{} is writable", dir.display()))
+        }
+        Err(e) => Check::new(
+            Status::Fail,
+            name,
+            format!("{} is not writable: {}", dir.display(), e),
+        ),
+    }
+}
+
+pub fn check_project(project: Result<String>) -> Check {
+    match project {
+        Ok(root) => Check::new(Status::Pass, "project", root),
+        Err(e) => Check::new(Status::Warn, "project", format!("none detected ({})", e)),
+    }
+}
+
+/// The memory a run of `runtime` would use here, or why it can't be resolved
+pub fn check_memory(runtime: &Runtime, memory: Result<Option<String>>) -> Check {
+    let name = format!("memory ({})", runtime.name());
+    match memory {
+        Ok(Some(memory)) => Check::new(Status::Pass, name, format!("{} MB", memory)),
+        Err(e) => Check::new(Status::Fail, name, e.to_string()),
+        Ok(None) => Check::new(
+            Status::Warn,
+            name,
+            "nothing configured, the runtime's own default applies",
+        ),
+    }
+}
+
+/// The saved memory ceiling, when there is one
+pub fn check_memory_ceiling(ceiling: Option<u64>) -> Option<Check> {
+    ceiling.map(|ceiling| {
+        let detail = format!("{} MB, no run gets more", ceiling);
+        Check::new(Status::Pass, "memory ceiling", detail)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_runtime() {
+        let found = check_runtime(&Runtime::Node, Some(Version(20, 11, 1)));
+        assert_eq!(found.status, Status::Pass);
+        assert_eq!(found.detail, "20.11.1");
+
+        let old = check_runtime(&Runtime::Node, Some(Version(10, 0, 0)));
+        assert_eq!(old.status, Status::Warn);
+
+        let missing = check_runtime(&Runtime::Deno, None);
+        assert_eq!(missing.status, Status::Warn);
+        assert!(missing.detail.contains("not found"));
+    }
+
+    #[test]
+    fn test_check_any_runtime() {
+        assert_eq!(check_any_runtime(0).unwrap().status, Status::Fail);
+        assert!(check_any_runtime(1).is_none());
+    }
+
+    #[test]
+    fn test_check_config_dir() {
+        let dir = std::env::temp_dir().join(format!("frc-doctor-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        assert_eq!(check_config_dir(&dir).status, Status::Pass);
+        assert_eq!(check_config_dir(&dir.join("missing")).status, Status::Warn);
+
+        let file = dir.join("file");
+        fs::write(&file, "").unwrap();
+        assert_eq!(check_config_dir(&file).status, Status::Fail);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_check_project() {
+        let found = check_project(Ok("/work/app".to_string()));
+        assert_eq!(found.status, Status::Pass);
+        assert_eq!(found.detail, "/work/app");
+
+        let missing = check_project(Err(anyhow::anyhow!("no package.json")));
+        assert_eq!(missing.status, Status::Warn);
+        assert!(missing.detail.contains("no package.json"));
+    }
+
+    #[test]
+    fn test_check_memory() {
+        let configured = check_memory(&Runtime::Node, Ok(Some("4096".to_string())));
+        assert_eq!(configured.status, Status::Pass);
+        assert_eq!(configured.to_string(), "✅ pass  memory (node): 4096 MB");
+
+        assert_eq!(check_memory(&Runtime::Node, Ok(None)).status, Status::Warn);
+        let invalid = check_memory(&Runtime::Node, Err(anyhow::anyhow!("too low")));
+        assert_eq!(invalid.status, Status::Fail);
+        assert_eq!(invalid.detail, "too low");
+    }
+
+    #[test]
+    fn test_check_memory_ceiling() {
+        assert!(check_memory_ceiling(None).is_none());
+        let ceiling = check_memory_ceiling(Some(8192)).unwrap();
+        assert_eq!(ceiling.status, Status::Pass);
+        assert_eq!(ceiling.detail, "8192 MB, no run gets more");
+    }
+}
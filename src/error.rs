@@ -0,0 +1,46 @@
+//! Failures callers may want to tell apart. Everything else stays an
+//! `anyhow` error with context.
+
+use std::path::PathBuf;
+
+#[derive(Debug, thiserror::Error)]
+pub enum FrcError {
+    /// A command or `--runtime` value that maps to no runtime
+    #[error("Unknown runtime: {0}")]
+    UnknownRuntime(String),
+
+    #[error("Memory limit ({memory_mb} MB) exceeds system memory ({system_gb} GB)")]
+    MemoryExceedsSystem { memory_mb: u64, system_gb: u64 },
+
+    #[error("Memory limit ({memory_mb} MB) exceeds the memory ceiling ({ceiling_mb} MB)")]
+    MemoryExceedsCeiling { memory_mb: u64, ceiling_mb: u64 },
+
+    #[error(
+        "Memory limit ({memory_mb} MB) is below the minimum for {runtime} ({minimum_mb} MB); \
+         it would run out of memory almost immediately"
+    )]
+    MemoryBelowMinimum {
+        memory_mb: u64,
+        runtime: String,
+        minimum_mb: u64,
+    },
+
+    /// The runtime's executable isn't on PATH
+    #[error(
+        "runtime '{runtime}' not found on PATH; install it or use --runtime\n   Install: {hint}"
+    )]
+    RuntimeNotFound { runtime: String, hint: String },
+
+    /// Reading or writing a config file failed
+    #[error("Failed to {action} {}", path.display())]
+    ConfigIo {
+        action: &'static str,
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// A config file exists but isn't valid
+    #[error("Invalid {}: {reason}", path.display())]
+    ConfigParse { path: PathBuf, reason: String },
+}
@@ -0,0 +1,39 @@
+//! Memory resolution behind the `frc` binary, for tools that want to
+//! embed it instead of shelling out.
+//!
+//! [`Manager::resolve_memory`] is the entry point: it picks the memory a
+//! [`Runtime`] should get from `-m`, saved configs and defaults, the same
+//! way `frc` does before starting the runtime.
+//!
+//! ```no_run
+//! use fe_runtime_container::{Manager, RunOptions, Runtime};
+//!
+//! let runtime = Runtime::from_command("npm")?;
+//! let mut manager = Manager::for_current_project()?;
+//! let memory = manager.resolve_memory(&runtime, &RunOptions::default(), Manager::system_memory_gb())?;
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+pub mod config;
+pub mod error;
+pub mod manager;
+pub mod memory;
+pub mod output;
+pub mod project;
+pub mod runtime;
+pub mod storage;
+pub mod timings;
+pub mod verbose;
+
+mod batch;
+mod doctor;
+mod signals;
+mod usage;
+mod watch;
+
+pub use config::Config;
+pub use error::FrcError;
+pub use manager::{Manager, RunOptions};
+pub use project::Project;
+pub use runtime::{ExecOptions, Runtime};
+pub use signals::Signal;
@@ -1,13 +1,15 @@
-mod config;
-mod manager;
-mod project;
-mod runtime;
-mod storage;
-
 use anyhow::Result;
-use clap::{Parser, Subcommand};
-use manager::Manager;
-use runtime::Runtime;
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
+use fe_runtime_container::memory::MemoryValue;
+use fe_runtime_container::runtime::ExportShell;
+use fe_runtime_container::storage::{ConfigFormat, Storage};
+use fe_runtime_container::{
+    Manager, Project, RunOptions, Runtime, Signal, output, timings, verbose,
+};
+use std::io::{IsTerminal, Write};
+use std::path::PathBuf;
+use std::time::Instant;
 
 #[derive(Parser)]
 #[command(name = "frc")]
@@ -25,36 +27,272 @@ struct Cli {
     #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
     args: Vec<String>,
 
-    /// Memory limit in MB (e.g., 4096 for 4GB)
-    /// When specified, it will be saved for this project
+    /// Memory limit in MB (e.g., 4096), with a unit (512M, 4GB), or as a
+    /// share of system memory (e.g., 40%). When specified, it will be saved for this project.
+    /// FRC_MEMORY sets a value the same way but is never saved
     #[arg(short, long)]
     memory: Option<String>,
 
-    /// Explicitly specify runtime (node, deno, bun)
-    /// Useful for commands where runtime cannot be auto-detected
+    /// Explicitly specify runtime (node, deno, bun, or auto)
+    /// Useful for commands where runtime cannot be auto-detected;
+    /// `auto` forces detection from the command
     #[arg(short, long, value_name = "RUNTIME")]
     runtime: Option<String>,
+
+    /// Fail instead of warning when --runtime doesn't match the command
+    /// (e.g. -r deno npm install) or memory is above --memory-max
+    #[arg(long)]
+    strict: bool,
+
+    /// Never give the runtime more than this much memory (MB or a unit,
+    /// e.g. 8GB), whatever -m, the saved config or auto-increase say
+    #[arg(long, value_name = "MB", value_parser = parse_memory_max)]
+    memory_max: Option<u64>,
+
+    /// Use the memory resolved by a parent frc process (FRC_RESOLVED_MEMORY)
+    /// Useful when a tool launched through frc invokes frc again
+    #[arg(long)]
+    inherit_parent_memory: bool,
+
+    /// Print a breakdown of frc's own overhead to stderr after the run
+    #[arg(long)]
+    timings: bool,
+
+    /// Log how the memory value was chosen to stderr
+    #[arg(long, global = true)]
+    verbose: bool,
+
+    /// Print plain text without colors or emoji (also set by NO_COLOR, and
+    /// the default when output isn't a terminal)
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// Print nothing of frc's own on stdout; warnings and errors go to stderr
+    #[arg(short, long, global = true)]
+    quiet: bool,
+
+    /// Also set Node's --max-semi-space-size (1/32 of the memory, 16-128 MB)
+    #[arg(long)]
+    semi_space: bool,
+
+    /// On out-of-memory, fail without raising the saved memory (for CI)
+    #[arg(long)]
+    no_auto_increase: bool,
+
+    /// On out-of-memory, also write a JSON record of the event to this file
+    #[arg(long, value_name = "PATH")]
+    report_file: Option<PathBuf>,
+
+    /// Extra V8 flag for Deno, merged into its --v8-flags (repeatable),
+    /// e.g. --v8-flag=--stack-size=2000
+    #[arg(long = "v8-flag", value_name = "FLAG", allow_hyphen_values = true, value_parser = parse_v8_flag)]
+    v8_flags: Vec<String>,
+
+    /// Run this binary instead of the runtime on PATH, e.g. a node from
+    /// nvm or volta; memory is still configured for the detected runtime
+    #[arg(long, value_name = "PATH", value_parser = parse_runtime_path)]
+    runtime_path: Option<PathBuf>,
+
+    /// Set an environment variable for the runtime (repeatable). A
+    /// NODE_OPTIONS given here is merged with the memory flags.
+    #[arg(long = "env", value_name = "KEY=VALUE", value_parser = parse_env_var)]
+    env: Vec<(String, String)>,
+
+    /// Stop the runtime if it runs longer than this many seconds
+    #[arg(long, value_name = "SECONDS")]
+    timeout: Option<u64>,
+
+    /// Signal to send the runtime when frc is interrupted (Ctrl-C), e.g.
+    /// SIGTERM for tools that shut down cleanly on it. Unix only.
+    #[arg(long, value_name = "SIGNAL", ignore_case = true)]
+    on_interrupt: Option<Signal>,
+
+    /// Replace a --max-old-space-size already in NODE_OPTIONS instead of
+    /// keeping it
+    #[arg(long)]
+    override_env: bool,
+
+    /// Lower the saved memory after a streak of successful runs
+    #[arg(long)]
+    auto_tune: bool,
+
+    /// Print the command and environment that would run, without running it
+    /// or saving config
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Save -m to the project's committed .frc.json instead of the user config
+    #[arg(long)]
+    local: bool,
+
+    /// Save the memory used: -m even for inline snippets (-e/--eval), or
+    /// without -m the resolved or recommended value
+    #[arg(long)]
+    save: bool,
+
+    /// Apply -m for this run only, without saving it
+    #[arg(long, conflicts_with = "save")]
+    no_save: bool,
+
+    /// Use and save memory under a named profile (e.g. dev, build) instead
+    /// of the project's default config
+    #[arg(long, value_name = "NAME", conflicts_with = "local")]
+    profile: Option<String>,
+
+    /// Print info, project and list output as JSON
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// Config file format (json, yaml, toml); defaults to FRC_CONFIG_FORMAT,
+    /// then to the format of the existing config file
+    #[arg(long, global = true, value_enum, value_name = "FORMAT")]
+    config_format: Option<ConfigFormat>,
+
+    /// Print the config file's absolute path and exit, without reading it
+    #[arg(long)]
+    print_config_path: bool,
+
+    /// Print the supported runtimes and their aliases, then exit
+    #[arg(long)]
+    list_runtimes: bool,
+
+    /// Operate as if started in this directory (project detection and child cwd)
+    #[arg(long, global = true, value_name = "PATH")]
+    cwd: Option<PathBuf>,
+
+    /// Inside a pnpm/nx/lerna monorepo, use the workspace root as the project
+    /// instead of the nearest package
+    #[arg(long, global = true)]
+    workspace_root: bool,
 }
 
 #[derive(Subcommand)]
 enum Commands {
-    /// Show memory recommendations for a runtime
+    /// Show memory recommendations for one or more runtimes
     Info {
-        /// Runtime (node, deno, bun)
-        runtime: String,
+        /// Runtimes (node, deno, bun)
+        #[arg(required_unless_present = "all", conflicts_with = "all")]
+        runtimes: Vec<String>,
+
+        /// Show every supported runtime
+        #[arg(long)]
+        all: bool,
     },
 
     /// Show current project's saved configuration
     Project,
 
-    /// List all saved project configurations
+    /// List saved project configurations, newest first
     #[command(name = "list")]
-    ListProjects,
+    ListProjects {
+        /// Only projects whose path matches this glob, e.g. "~/work/*"
+        pattern: Option<String>,
+
+        /// Only projects using this runtime (node, deno, bun)
+        #[arg(short, long)]
+        runtime: Option<String>,
+
+        /// Show at most this many projects
+        #[arg(short, long, value_name = "N")]
+        limit: Option<usize>,
+    },
+
+    /// Summarize saved projects and their memory
+    Stats,
+
+    /// Save a memory default used when no project config applies, or the
+    /// most memory any run may get
+    #[command(group(
+        clap::ArgGroup::new("setting")
+            .args(["memory", "memory_max", "no_memory_max"])
+            .required(true)
+            .multiple(true)
+    ))]
+    SetDefault {
+        /// Memory limit in MB, or with a unit (512MB, 4GB) or as a share of
+        /// system memory (40%)
+        #[arg(short, long, value_name = "MEMORY")]
+        memory: Option<String>,
+
+        /// Only set the recommended memory for this runtime (node, deno, bun)
+        #[arg(short, long, requires = "memory")]
+        runtime: Option<String>,
+
+        /// Save a memory ceiling (MB or a unit, e.g. 8GB) that caps every
+        /// run, like --memory-max on each one
+        #[arg(long, value_name = "MB", value_parser = parse_memory_max)]
+        memory_max: Option<u64>,
+
+        /// Remove the saved memory ceiling
+        #[arg(long, conflicts_with = "memory_max")]
+        no_memory_max: bool,
+    },
+
+    /// Find saved projects by memory, e.g. to spot over-provisioned ones
+    #[command(group(
+        clap::ArgGroup::new("threshold")
+            .args(["memory_above", "memory_below"])
+            .required(true)
+            .multiple(true)
+    ))]
+    Find {
+        /// Only projects with more than this many MB
+        #[arg(long, value_name = "MB")]
+        memory_above: Option<u64>,
+
+        /// Only projects with less than this many MB
+        #[arg(long, value_name = "MB")]
+        memory_below: Option<u64>,
+    },
 
     /// Remove saved configuration for current or specified project
     Forget {
         /// Optional project path (uses current directory if not specified)
         path: Option<String>,
+        /// Remove without asking (required when not run from a terminal)
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Switch the current project's saved runtime, keeping its memory
+    Use {
+        /// Runtime to use from now on (node, deno, bun)
+        runtime: String,
+    },
+
+    /// Attach a note to the current project's saved config
+    Note {
+        /// Why the config is the way it is; "" removes the note
+        text: String,
+    },
+
+    /// Move a saved config to a new project path
+    Rename {
+        /// Path the config is saved under
+        from: String,
+        /// New project path
+        to: String,
+        /// Replace a config already saved at the new path
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Open the config file in $VISUAL/$EDITOR and check it afterwards
+    Edit,
+
+    /// Write all saved configs to a file (JSON, or YAML/TOML by extension)
+    ExportConfig {
+        /// File to write
+        path: PathBuf,
+    },
+
+    /// Load saved configs from a file written by export-config
+    ImportConfig {
+        /// File to read
+        path: PathBuf,
+        /// Keep existing configs; on conflict the more recently used wins
+        #[arg(long)]
+        merge: bool,
     },
 
     /// Clean up old project configurations
@@ -62,70 +300,478 @@ enum Commands {
         /// Remove configs older than this many days (default: 30)
         #[arg(short, long, default_value = "30")]
         days: u64,
+        /// Only remove configs using this runtime (node, deno, bun)
+        #[arg(short, long)]
+        runtime: Option<String>,
+        /// Remove without asking (required when not run from a terminal)
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Remove configs for projects whose directory no longer exists
+    Prune {
+        /// Only list the configs that would be removed
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Run a command in every saved project for its runtime, with memory
+    /// resolved as a run in that project would
+    RunAll {
+        /// Keep going after a project fails and report every failure at
+        /// the end
+        #[arg(long, alias = "continue-on-error")]
+        keep_going: bool,
+
+        /// Number of projects to run at once
+        #[arg(short, long, default_value = "1", value_parser = clap::value_parser!(u32).range(1..))]
+        jobs: u32,
+
+        /// Command and its arguments, e.g. `npm install`
+        #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
+    /// Run a command, then restart it whenever project files change
+    Watch {
+        /// File or directory to watch (repeatable; default: the project root)
+        #[arg(long, value_name = "PATH")]
+        path: Vec<PathBuf>,
+
+        /// Wait this long after a change for further changes before restarting
+        #[arg(long, value_name = "MS", default_value = "300")]
+        debounce: u64,
+
+        /// Command and its arguments, e.g. `node server.js`
+        #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
+    /// Explain how the configured heap limit relates to total process memory
+    ExplainMemory {
+        /// Runtime to explain (node, deno, bun); defaults to the project's
+        /// saved runtime, else node
+        #[arg(short, long)]
+        runtime: Option<String>,
+
+        /// Start a node process with the configured limit and measure it
+        /// (Node only)
+        #[arg(long)]
+        probe: bool,
+    },
+
+    /// Measure frc's startup overhead without running a runtime
+    #[command(hide = true)]
+    BenchmarkStartup {
+        /// Number of iterations
+        #[arg(short, long, default_value = "20", value_parser = clap::value_parser!(u32).range(1..))]
+        iterations: u32,
+    },
+
+    /// Check whether running all saved projects at once would overcommit memory
+    CheckOvercommit {
+        /// Only include projects using this runtime
+        #[arg(short, long)]
+        runtime: Option<String>,
+    },
+
+    /// Check installed runtimes, the config directory and the project
+    Doctor,
+
+    /// Print shell lines that set the project's memory, for use without frc
+    Export {
+        /// Runtime (node, deno, bun)
+        runtime: String,
+
+        /// Shell syntax to print
+        #[arg(long, value_enum, default_value_t = ExportShell::Posix)]
+        shell: ExportShell,
+    },
+
+    /// Print a shell completion script to stdout
+    Completions {
+        /// Shell to generate for (bash, zsh, fish, powershell, elvish)
+        shell: Shell,
     },
 }
 
 fn main() -> Result<()> {
+    let start = Instant::now();
     let cli = Cli::parse();
 
+    if cli.timings {
+        timings::enable();
+    }
+    if cli.verbose {
+        verbose::enable();
+    }
+    output::init(cli.no_color);
+    if cli.quiet {
+        output::quiet();
+    }
+
+    let result = run(cli);
+    timings::report(start.elapsed());
+
+    // Mirror the runtime's exit code so callers can branch on it
+    let code = result?;
+    if code != 0 {
+        std::process::exit(code);
+    }
+    Ok(())
+}
+
+/// Returns the exit code for frc: the runtime's own code when one ran
+fn run(cli: Cli) -> Result<i32> {
+    if let Some(format) = cli.config_format {
+        Storage::set_format(format);
+    }
+    if let Some(dir) = &cli.cwd {
+        Project::set_working_dir(dir)?;
+    }
+    if cli.workspace_root {
+        Project::use_workspace_root();
+    }
+
+    if cli.print_config_path {
+        let path = std::path::absolute(Storage::config_path()?)?;
+        println!("{}", path.display());
+        return Ok(0);
+    }
+    if cli.list_runtimes {
+        Manager::list_runtimes(cli.json)?;
+        return Ok(0);
+    }
+
     match cli.command {
-        Some(Commands::Info { runtime }) => {
-            let rt = Runtime::from_command(&runtime)?;
+        Some(Commands::Info { runtimes, all }) => {
+            let mut rts = Vec::new();
+            for name in &runtimes {
+                let rt = Runtime::from_command(name)?;
+                if !rts.contains(&rt) {
+                    rts.push(rt);
+                }
+            }
+            if all {
+                rts = Runtime::ALL.to_vec();
+            }
             let manager = Manager::new()?;
-            manager.show_recommendations(&rt)?;
+            manager.show_recommendations(&rts, cli.json)?;
         }
         Some(Commands::Project) => {
             let manager = Manager::new()?;
-            manager.show_project()?;
+            manager.show_project(cli.json)?;
+        }
+        Some(Commands::ListProjects {
+            pattern,
+            runtime,
+            limit,
+        }) => {
+            let rt = runtime.as_deref().map(Runtime::from_command).transpose()?;
+            let manager = Manager::new()?;
+            manager.list_projects(pattern.as_deref(), rt.as_ref(), limit, cli.json)?;
         }
-        Some(Commands::ListProjects) => {
+        Some(Commands::Stats) => {
             let manager = Manager::new()?;
-            manager.list_projects()?;
+            manager.show_stats(cli.json)?;
+        }
+        Some(Commands::SetDefault {
+            memory,
+            runtime,
+            memory_max,
+            no_memory_max,
+        }) => {
+            let rt = runtime.as_deref().map(Runtime::from_command).transpose()?;
+            let mut manager = Manager::new()?;
+            if let Some(memory) = &memory {
+                manager.set_default(rt.as_ref(), memory)?;
+            }
+            if memory_max.is_some() || no_memory_max {
+                manager.set_memory_ceiling(memory_max)?;
+            }
+        }
+        Some(Commands::Find {
+            memory_above,
+            memory_below,
+        }) => {
+            let manager = Manager::new()?;
+            manager.find_projects(memory_above, memory_below, cli.json)?;
+        }
+        Some(Commands::Forget { path, force }) => {
+            let mut manager = Manager::new()?;
+            manager.forget_project(path, force)?;
+        }
+        Some(Commands::Use { runtime }) => {
+            let rt = Runtime::from_command(&runtime)?;
+            let mut manager = Manager::new()?;
+            manager.use_runtime(&rt)?;
+        }
+        Some(Commands::Note { text }) => {
+            let mut manager = Manager::new()?;
+            manager.set_note(&text)?;
+        }
+        Some(Commands::Rename { from, to, force }) => {
+            let mut manager = Manager::new()?;
+            manager.rename_project(&from, &to, force)?;
+        }
+        Some(Commands::Edit) => {
+            Manager::edit_config()?;
+        }
+        Some(Commands::ExportConfig { path }) => {
+            let manager = Manager::new()?;
+            manager.export_config(&path)?;
+        }
+        Some(Commands::ImportConfig { path, merge }) => {
+            let mut manager = Manager::new()?;
+            manager.import_config(&path, merge)?;
+        }
+        Some(Commands::Cleanup {
+            days,
+            runtime,
+            force,
+        }) => {
+            let rt = runtime.as_deref().map(Runtime::from_command).transpose()?;
+            let mut manager = Manager::new()?;
+            manager.cleanup(days, rt.as_ref(), force)?;
         }
-        Some(Commands::Forget { path }) => {
+        Some(Commands::Prune { dry_run }) => {
             let mut manager = Manager::new()?;
-            manager.forget_project(path)?;
+            manager.prune(dry_run)?;
         }
-        Some(Commands::Cleanup { days }) => {
+        Some(Commands::Doctor) => {
             let mut manager = Manager::new()?;
-            manager.cleanup(days)?;
+            if !manager.doctor()? {
+                return Ok(1);
+            }
+        }
+        Some(Commands::ExplainMemory { ref runtime, probe }) => {
+            let rt = runtime.as_deref().map(Runtime::from_command).transpose()?;
+            let mut manager = Manager::new()?;
+            let options = run_options(&cli, false, false);
+            manager.explain_memory(rt.as_ref(), &options, probe)?;
+        }
+        Some(Commands::BenchmarkStartup { iterations }) => {
+            Manager::benchmark_startup(iterations)?;
+        }
+        Some(Commands::RunAll {
+            keep_going,
+            jobs,
+            ref args,
+        }) => {
+            let (cmd, rest) = args.split_first().expect("clap requires a command");
+            let (runtime, exec_args) = command_args(&cli, cmd.clone(), rest.to_vec())?;
+            let options = run_options(&cli, false, false);
+
+            let mut manager = Manager::new()?;
+            let jobs = jobs as usize;
+            return manager.run_all(&runtime, &exec_args, &options, jobs, keep_going);
+        }
+        Some(Commands::Watch {
+            ref path,
+            debounce,
+            ref args,
+        }) => {
+            let (cmd, rest) = args.split_first().expect("clap requires a command");
+            let (runtime, exec_args) = command_args(&cli, cmd.clone(), rest.to_vec())?;
+            let save = cli.save || (!cli.no_save && cli.memory.is_some());
+            let interactive = !cli.dry_run && std::io::stdin().is_terminal();
+            let options = run_options(&cli, save, interactive);
+
+            let working_dir = Project::working_dir()?;
+            let paths = if path.is_empty() {
+                vec![Project::get_id().map_or(working_dir, PathBuf::from)]
+            } else {
+                path.iter().map(|p| working_dir.join(p)).collect()
+            };
+
+            let mut manager = Manager::for_current_project()?;
+            let debounce = std::time::Duration::from_millis(debounce);
+            manager.watch(&runtime, &exec_args, options, &paths, debounce)?;
+        }
+        Some(Commands::CheckOvercommit { runtime }) => {
+            let rt = runtime.as_deref().map(Runtime::from_command).transpose()?;
+            let manager = Manager::new()?;
+            manager.check_overcommit(rt.as_ref())?;
+        }
+        Some(Commands::Export { runtime, shell }) => {
+            let rt = Runtime::from_command(&runtime)?;
+            let manager = Manager::for_current_project()?;
+            manager.export(&rt, shell)?;
+        }
+        Some(Commands::Completions { shell }) => {
+            write_completions(shell, &mut std::io::stdout())?;
         }
         None => {
             // Direct command execution
-            if let Some(cmd) = cli.runtime_cmd {
-                // Detect runtime: use explicit runtime flag or auto-detect from command
-                let runtime_specified = cli.runtime.is_some();
-                let runtime = if let Some(rt) = &cli.runtime {
-                    Runtime::from_command(rt)?
-                } else {
-                    Runtime::from_command(&cmd)?
-                };
-
-                let mut manager = Manager::new()?;
-
-                // Prepare arguments - include the original command if it's not the base runtime
-                let mut exec_args = Vec::new();
-
-                // If runtime was explicitly specified, include the full command
-                if runtime_specified {
-                    exec_args.push(cmd);
-                } else if cmd != runtime.as_str() {
-                    // For commands like npm, npx, vite, etc.
-                    exec_args.push(cmd);
-                }
-
-                exec_args.extend(cli.args);
+            if let Some(cmd) = cli.runtime_cmd.clone() {
+                let (runtime, exec_args) = command_args(&cli, cmd, cli.args.clone())?;
+                let mut manager = Manager::for_current_project()?;
 
-                // If memory is explicitly provided, save it to project config
-                let save_to_project = cli.memory.is_some();
+                // If memory is explicitly provided, save it to project config;
+                // inline snippets are transient and only saved on request
+                let eval = runtime.is_eval(&exec_args);
+                let save = cli.save || (!cli.no_save && cli.memory.is_some() && !eval);
+                // Snippets aren't saved, so there is nothing to pick for
+                let interactive = !cli.dry_run && !eval && std::io::stdin().is_terminal();
+                let options = run_options(&cli, save, interactive);
 
-                manager.run(&runtime, &exec_args, cli.memory, save_to_project)?;
+                return manager.run(&runtime, &exec_args, &options);
             } else {
                 print_usage();
             }
         }
     }
 
+    Ok(0)
+}
+
+/// The runtime for `cmd` and the arguments to start it with: the original
+/// command is kept unless it is the runtime itself
+fn command_args(cli: &Cli, cmd: String, args: Vec<String>) -> Result<(Runtime, Vec<String>)> {
+    // Detect runtime: use explicit runtime flag or auto-detect from command
+    let explicit = match &cli.runtime {
+        Some(rt) => Runtime::from_explicit(rt)?,
+        None => None,
+    };
+    check_runtime_conflict(explicit.as_ref(), &cmd, cli.strict)?;
+    let runtime_specified = explicit.is_some();
+    let runtime = match explicit {
+        Some(rt) => rt,
+        None => Runtime::from_command(&cmd)?,
+    };
+
+    let mut exec_args = Vec::new();
+
+    // If runtime was explicitly specified, include the full command
+    if runtime_specified {
+        exec_args.push(cmd);
+    } else if Runtime::command_name(&cmd) != runtime.as_str() {
+        // For commands like npm, npx, vite, etc. Scripts detected
+        // by their shebang keep its runtime arguments.
+        exec_args.extend(Runtime::shebang_args(&cmd));
+        exec_args.push(cmd);
+    }
+
+    exec_args.extend(args);
+    Ok((runtime, exec_args))
+}
+
+fn run_options(cli: &Cli, save: bool, interactive: bool) -> RunOptions {
+    RunOptions {
+        save,
+        local: cli.local,
+        memory: cli.memory.clone(),
+        memory_max: cli.memory_max,
+        strict: cli.strict,
+        inherit_parent_memory: cli.inherit_parent_memory,
+        semi_space: cli.semi_space,
+        no_auto_increase: cli.no_auto_increase,
+        override_env: cli.override_env,
+        auto_tune: cli.auto_tune,
+        profile: cli.profile.clone(),
+        report_file: cli.report_file.clone(),
+        timeout: cli.timeout,
+        on_interrupt: cli.on_interrupt,
+        v8_flags: cli.v8_flags.clone(),
+        runtime_path: cli.runtime_path.clone(),
+        env: cli.env.clone(),
+        dry_run: cli.dry_run,
+        interactive,
+    }
+}
+
+/// Runtimes offered for the positional command and `-r` in completions
+const COMPLETION_RUNTIMES: [&str; 3] = ["node", "deno", "bun"];
+
+/// The CLI as seen by shell completion. The positional command only
+/// completes the documented runtimes (alongside subcommands), and the
+/// pass-through arguments are hidden so nothing is offered after it.
+fn completion_command() -> clap::Command {
+    Cli::command()
+        .mut_arg("runtime_cmd", |arg| arg.value_parser(COMPLETION_RUNTIMES))
+        .mut_arg("runtime", |arg| {
+            arg.value_parser(["node", "deno", "bun", Runtime::AUTO])
+        })
+        .mut_arg("args", |arg| arg.hide(true))
+}
+
+/// `--v8-flag` values must be V8 options themselves
+fn parse_v8_flag(value: &str) -> Result<String, String> {
+    match value.strip_prefix("--") {
+        Some(name) if !name.is_empty() && !name.contains(',') => Ok(value.to_string()),
+        _ => Err(format!(
+            "'{}' is not a V8 flag; expected a single option starting with --, e.g. --stack-size=2000",
+            value
+        )),
+    }
+}
+
+/// Warn (or with `strict`, fail) when the explicit runtime isn't the
+/// one `cmd` implies. The explicit runtime is used either way.
+fn check_runtime_conflict(explicit: Option<&Runtime>, cmd: &str, strict: bool) -> Result<()> {
+    let Some(explicit) = explicit else {
+        return Ok(());
+    };
+    let Some(detected) = explicit.conflicting_runtime(cmd) else {
+        return Ok(());
+    };
+
+    let message = format!(
+        "'{}' is a {} command, but --runtime is {}",
+        cmd,
+        detected.name(),
+        explicit.name()
+    );
+    if strict {
+        anyhow::bail!("{}", message);
+    }
+    let note = format!("{}; running it under {} anyway", message, explicit.name());
+    output::eprint(format_args!("⚠️  {}", note));
+    Ok(())
+}
+
+/// `--memory-max` takes absolute values only; a share of system memory
+/// would defeat its purpose
+fn parse_memory_max(value: &str) -> Result<u64, String> {
+    match MemoryValue::parse(value).map_err(|e| e.to_string())? {
+        MemoryValue::Mb(mb) => Ok(mb),
+        MemoryValue::Percent(_) => Err("expected MB or a unit like 8GB, not a percentage".into()),
+    }
+}
+
+fn parse_env_var(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        Some(_) => Err("the variable name is empty".to_string()),
+        None => Err(format!("'{}' is not KEY=VALUE", value)),
+    }
+}
+
+fn parse_runtime_path(value: &str) -> Result<PathBuf, String> {
+    let path = PathBuf::from(value);
+    let metadata = std::fs::metadata(&path).map_err(|e| format!("'{}': {}", value, e))?;
+    if !metadata.is_file() || !is_executable(&metadata) {
+        return Err(format!("'{}' is not an executable file", value));
+    }
+    Ok(path)
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_metadata: &std::fs::Metadata) -> bool {
+    true
+}
+
+fn write_completions(shell: Shell, out: &mut dyn Write) -> Result<()> {
+    let mut cmd = completion_command();
+    let name = cmd.get_name().to_string();
+    clap_complete::generate(shell, &mut cmd, name, out);
+    out.flush()?;
     Ok(())
 }
 
@@ -137,16 +783,71 @@ fn print_usage() {
     println!();
     println!("OPTIONS:");
     println!("  -m, --memory <MB>       Set memory limit in MB (saves to project config)");
-    println!("  -r, --runtime <RUNTIME> Specify runtime (node/deno/bun) explicitly");
+    println!("                          also 512M, 4GB, or a share of system memory like 40%");
+    println!("                          (FRC_MEMORY env: same, below -m, never saved)");
+    println!("  -r, --runtime <RUNTIME> Specify runtime (node/deno/bun/auto) explicitly");
+    println!("  --strict                Fail when --runtime doesn't match the command or");
+    println!("                          memory is above --memory-max");
+    println!("  --memory-max <MB>       Never give the runtime more memory than this");
+    println!("  --inherit-parent-memory Use the memory resolved by a parent frc process");
+    println!("  --semi-space            Also tune Node's young generation from -m");
+    println!("  --no-auto-increase      Fail on OOM without raising the saved memory");
+    println!("  --report-file <PATH>    Write a JSON record to PATH on OOM");
+    println!("  --timeout <SECONDS>     Stop the runtime after SECONDS");
+    println!("  --on-interrupt <SIGNAL> Send SIGNAL (e.g. SIGTERM) to the runtime on Ctrl-C");
+    println!("  --v8-flag <FLAG>        Extra V8 flag for Deno, e.g. --stack-size=2000");
+    println!("                          (repeatable, merged into one --v8-flags)");
+    println!("  --override-env          Replace a heap size already set in NODE_OPTIONS");
+    println!("  --auto-tune             Lower saved memory after 5 successful runs in a row");
+    println!("  --local                 Save -m to the project's .frc.json");
+    println!("  --runtime-path <PATH>   Run this binary instead of the runtime on PATH");
+    println!("  --env <KEY=VALUE>       Set an environment variable for the runtime (repeatable)");
+    println!("  --save                  Save the memory used, even without -m or for -e/--eval");
+    println!("  --no-save               Apply -m for this run only");
+    println!("  --profile <NAME>        Use and save memory under a named profile");
+    println!("  --dry-run               Show the command and env without running it");
+    println!("  --json                  JSON output for info, project, list and stats");
+    println!("  --timings               Print frc's own overhead to stderr after the run");
+    println!("  --verbose               Log how the memory value was chosen to stderr");
+    println!("  --no-color              Plain output without colors or emoji");
+    println!("  -q, --quiet             Only the runtime's output on stdout (warnings to stderr)");
+    println!("  --config-format <FMT>   Config file format: json (default), yaml or toml");
+    println!("  --print-config-path     Print where the config file lives and exit");
+    println!("  --list-runtimes         Print supported runtimes and their aliases (--json)");
+    println!("  --cwd <PATH>            Operate as if started in PATH");
+    println!("  --workspace-root        Bind to the monorepo root, not the nearest package");
     println!("  -h, --help              Show help information");
     println!("  -V, --version           Show version");
     println!();
     println!("COMMANDS:");
-    println!("  info <runtime>       Show memory recommendations");
+    println!("  info <runtime>...    Show memory recommendations (--all for every runtime)");
     println!("  project              Show current project's saved config");
-    println!("  list                 List all saved project configs");
-    println!("  forget [path]        Remove saved config for project");
-    println!("  cleanup --days <N>   Remove configs older than N days");
+    println!("  list [pattern]       List saved project configs (--runtime, --limit N)");
+    println!("  stats                Summarize saved projects and memory");
+    println!("  set-default -m <MB>  Memory to use when no project config applies");
+    println!("                       (with --runtime: that runtime's recommended memory)");
+    println!("  set-default --memory-max <MB>");
+    println!("                       Save a ceiling for every run (--no-memory-max removes it)");
+    println!("  find --memory-above <MB> / --memory-below <MB>");
+    println!("                       List projects above/below a memory threshold");
+    println!("  forget [path]        Remove saved config for project (--force to skip the prompt)");
+    println!("  use <runtime>        Switch the project's saved runtime, keeping memory");
+    println!("  note <text>          Attach a note to the project's config (\"\" clears it)");
+    println!("  rename <from> <to>   Move a saved config to a new path (--force to replace)");
+    println!("  edit                 Open the config file in $VISUAL/$EDITOR");
+    println!("  export-config <file> Write all saved configs to a file");
+    println!("  import-config <file> Load configs from a file (--merge keeps existing ones)");
+    println!("  cleanup --days <N>   Remove configs older than N days (--runtime, --force)");
+    println!("  prune [--dry-run]    Remove configs for deleted project directories");
+    println!("  run-all <command>    Run a command in every saved project (--jobs N,");
+    println!("                       --keep-going)");
+    println!("  watch <command>      Restart the command when files change (--path,");
+    println!("                       --debounce MS)");
+    println!("  explain-memory       Heap limit vs. total RSS (-r runtime, --probe for Node)");
+    println!("  check-overcommit     Compare total saved memory to system memory");
+    println!("  doctor               Check runtimes, config directory and project setup");
+    println!("  export <runtime>     Print env lines for eval (--shell fish for fish)");
+    println!("  completions <shell>  Print a completion script (bash/zsh/fish/powershell)");
     println!();
     println!("EXAMPLES:");
     println!("  # First time in a project - saves 4GB config");
@@ -158,6 +859,7 @@ fn print_usage() {
     println!("  # Explicitly specify runtime for unknown commands");
     println!("  frc -r node -m 4096 my-custom-script");
     println!("  frc --runtime deno tsx build.ts");
+    println!("  frc --runtime auto node index.js");
     println!();
     println!("  # View current project config");
     println!("  frc project");
@@ -170,14 +872,112 @@ fn print_usage() {
     println!();
     println!("SUPPORTED RUNTIMES:");
     println!("  Node.js: node, npm, npx, pnpm, yarn    [Memory config: ✓]");
+    println!("           tsx, ts-node, vite, webpack, rollup, esbuild, next");
     println!("  Deno:    deno                          [Memory config: ✓]");
-    println!("  Bun:     bun                           [Memory config: ✗]");
+    println!("  Bun:     bun, bunx                     [Memory config: ✓]");
     println!();
     println!("HOW IT WORKS:");
     println!("  1. When you run with -m flag, the memory config is saved for this project");
     println!("  2. Future runs without -m will use the saved config automatically");
-    println!("  3. If no saved config exists, you'll see recommended values");
+    println!("  3. Without a saved config you pick one in a terminal, or see a recommendation");
     println!("  4. Configs are project-specific (detected via package.json, .git, etc.)");
     println!();
-    println!("NOTE: Bun uses JavaScriptCore and manages memory automatically.");
+    println!("NOTE: Bun memory is set via BUN_JSC_forceRAMSize (+ --smol below 1024 MB).");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_completions_for_each_shell() {
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish, Shell::PowerShell] {
+            let mut out = Vec::new();
+            write_completions(shell, &mut out).unwrap();
+            let script = String::from_utf8(out).unwrap();
+
+            assert!(!script.is_empty(), "{shell} script is empty");
+            assert!(script.contains("explain-memory"), "{shell}: no subcommands");
+            assert!(script.contains("deno"), "{shell} lacks runtimes");
+        }
+    }
+
+    #[test]
+    fn test_completion_command_is_valid() {
+        completion_command().debug_assert();
+    }
+
+    #[test]
+    fn test_v8_flags_are_repeatable_and_validated() {
+        let cli = Cli::try_parse_from([
+            "frc",
+            "--v8-flag",
+            "--stack-size=2000",
+            "--v8-flag=--max-heap-size=8192",
+            "deno",
+            "run",
+            "main.ts",
+        ])
+        .unwrap();
+        assert_eq!(cli.v8_flags, ["--stack-size=2000", "--max-heap-size=8192"]);
+        assert_eq!(cli.runtime_cmd.as_deref(), Some("deno"));
+
+        for bad in ["stack-size=2000", "--", "--a,--b"] {
+            let parsed = Cli::try_parse_from(["frc", "--v8-flag", bad, "deno"]);
+            assert!(parsed.is_err(), "{bad} accepted");
+        }
+    }
+
+    #[test]
+    fn test_env_is_repeatable_and_validated() {
+        let cli = Cli::try_parse_from([
+            "frc",
+            "--env",
+            "NODE_ENV=production",
+            "--env=GREETING=a=b",
+            "--env",
+            "EMPTY=",
+            "node",
+            "index.js",
+        ])
+        .unwrap();
+        let env: Vec<_> = cli
+            .env
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+            .collect();
+        let expected = [
+            ("NODE_ENV", "production"),
+            ("GREETING", "a=b"),
+            ("EMPTY", ""),
+        ];
+        assert_eq!(env, expected);
+
+        for bad in ["NODE_ENV", "=production", ""] {
+            let parsed = Cli::try_parse_from(["frc", "--env", bad, "node"]);
+            assert!(parsed.is_err(), "{bad} accepted");
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_runtime_path_must_be_executable() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("frc-runtime-path-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let binary = dir.join("node");
+        std::fs::write(&binary, "#!/bin/sh\n").unwrap();
+        let path = binary.to_string_lossy().to_string();
+
+        std::fs::set_permissions(&binary, std::fs::Permissions::from_mode(0o644)).unwrap();
+        let err = parse_runtime_path(&path).unwrap_err();
+        assert!(err.contains("not an executable"));
+        std::fs::set_permissions(&binary, std::fs::Permissions::from_mode(0o755)).unwrap();
+        assert_eq!(parse_runtime_path(&path).unwrap(), binary);
+
+        assert!(parse_runtime_path(&dir.to_string_lossy()).is_err());
+        assert!(parse_runtime_path(&dir.join("missing").to_string_lossy()).is_err());
+        std::fs::remove_dir_all(&dir).ok();
+    }
 }
@@ -1,8 +1,165 @@
-use crate::config::Config;
+use crate::batch;
+use crate::config::{Config, DECREASE_AFTER_RUNS, MemoryIncrease, STALE_DAYS};
+use crate::doctor::{self, Check, Status};
+use crate::error::FrcError;
+use crate::memory::MemoryValue;
+use crate::output::{self, errln, outln};
 use crate::project::Project;
-use crate::runtime::Runtime;
-use anyhow::Result;
+use crate::runtime::{self, ExecOptions, ExportShell, Runtime, Version};
+use crate::signals::{self, Signal};
+use crate::storage::{
+    CONFIG_HOME_ENV, LOCAL_FILE_NAME, LocalSettings, PackageSettings, ProjectSettings, Storage,
+    StorageData,
+};
+use crate::timings;
+use crate::usage;
+use crate::verbose::verbose;
+use crate::watch;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use sysinfo::{MemoryRefreshKind, System};
+
+/// Forces a memory value like `-m` does, but is never saved
+const MEMORY_ENV: &str = "FRC_MEMORY";
+
+/// Prints a fresh Node process's memory breakdown, in MB, as JSON
+const PROBE_SCRIPT: &str = "const v8 = require('v8'); \
+    const m = process.memoryUsage(); \
+    const mb = (b) => Math.round(b / 1048576); \
+    console.log(JSON.stringify({ \
+      heap_limit_mb: mb(v8.getHeapStatistics().heap_size_limit), \
+      rss_mb: mb(m.rss), heap_used_mb: mb(m.heapUsed), external_mb: mb(m.external) }))";
+
+/// cgroup v2, then v1, memory limit files
+#[cfg(target_os = "linux")]
+const CGROUP_LIMIT_FILES: &[&str] = &[
+    "/sys/fs/cgroup/memory.max",
+    "/sys/fs/cgroup/memory/memory.limit_in_bytes",
+];
+
+/// Total system memory in GB, probed on first use
+static SYSTEM_MEMORY_GB: OnceLock<u64> = OnceLock::new();
+
+/// Memory of an idle Node process started with the configured limit
+#[derive(Debug, Deserialize, PartialEq)]
+struct MemoryProbe {
+    heap_limit_mb: u64,
+    rss_mb: u64,
+    heap_used_mb: u64,
+    external_mb: u64,
+}
+
+/// Options for a single `Manager::run` invocation
+#[derive(Debug, Default)]
+pub struct RunOptions {
+    /// Memory from `-m`; takes precedence over everything else
+    pub memory: Option<String>,
+    /// Most memory in MB to give the run (`--memory-max`), overriding the
+    /// config's `memory_ceiling`
+    pub memory_max: Option<u64>,
+    /// Fail instead of clamping memory above the ceiling
+    pub strict: bool,
+    /// Save the memory used to the project config. Without `memory`, the
+    /// resolved value (or the recommendation) is saved.
+    pub save: bool,
+    /// Save to the project's `.frc.json` instead of the user config
+    pub local: bool,
+    /// Use the memory resolved by a parent frc process, if any
+    pub inherit_parent_memory: bool,
+    /// Also tune Node's young generation (`--max-semi-space-size`)
+    pub semi_space: bool,
+    /// Report OOM without bumping the saved memory
+    pub no_auto_increase: bool,
+    /// Replace a heap size already set in NODE_OPTIONS
+    pub override_env: bool,
+    /// Lower the saved memory after a streak of successful runs
+    pub auto_tune: bool,
+    /// Named profile to resolve and save memory under, instead of the
+    /// project's default config
+    pub profile: Option<String>,
+    /// Write a JSON record here when the run runs out of memory
+    pub report_file: Option<PathBuf>,
+    /// Stop the child after this many seconds
+    pub timeout: Option<u64>,
+    /// Signal the child gets when frc is interrupted, instead of SIGINT
+    pub on_interrupt: Option<Signal>,
+    /// Extra V8 flags for Deno (`--v8-flag`)
+    pub v8_flags: Vec<String>,
+    /// Run this binary instead of the runtime on PATH
+    pub runtime_path: Option<PathBuf>,
+    /// Extra environment variables for the child (`--env`)
+    pub env: Vec<(String, String)>,
+    /// Print the command and environment instead of running it
+    pub dry_run: bool,
+    /// Ask for a memory value when nothing is saved; only set when stdin
+    /// is a terminal, so CI never blocks on the prompt
+    pub interactive: bool,
+}
+
+/// `--report-file` record written when a run runs out of memory
+#[derive(Debug, Serialize)]
+struct OomReport<'a> {
+    event: &'static str,
+    /// Memory the run had, if frc set one
+    old_mb: Option<u64>,
+    /// Memory saved for the next run; `None` when it wasn't raised
+    new_mb: Option<u64>,
+    runtime: &'a str,
+    /// Project path, if one was detected
+    project: Option<String>,
+}
+
+/// `info --json` payload
+#[derive(Debug, Serialize)]
+struct RecommendationReport<'a> {
+    system_gb: u64,
+    runtime: &'a str,
+    supports_memory_config: bool,
+    recommended_mb: u64,
+    range_mb: MemoryRange,
+}
+
+#[derive(Debug, Serialize)]
+struct MemoryRange {
+    low: u64,
+    high: u64,
+}
+
+/// `--list-runtimes --json` entry
+#[derive(Debug, Serialize)]
+struct RuntimeReport<'a> {
+    name: &'a str,
+    supports_memory_config: bool,
+    aliases: Vec<&'static str>,
+}
+
+/// `project --json` and `list --json` entry
+#[derive(Debug, Serialize)]
+struct ProjectReport<'a> {
+    name: String,
+    path: &'a str,
+    /// Enclosing monorepo root, when it isn't the project itself
+    #[serde(skip_serializing_if = "Option::is_none")]
+    workspace_root: Option<String>,
+    /// Committed `.frc.json`, which takes precedence over `config`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    local: Option<LocalSettings>,
+    /// The `frc` key of package.json, next in line after `local`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    package: Option<PackageSettings>,
+    config: Option<&'a ProjectSettings>,
+    /// Named profiles, used with `--profile`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    profiles: Vec<(&'a String, &'a ProjectSettings)>,
+    /// Saved memory ceiling, which caps every source above
+    #[serde(skip_serializing_if = "Option::is_none")]
+    memory_ceiling_mb: Option<u64>,
+}
 
 pub struct Manager {
     config: Config,
@@ -15,231 +172,2390 @@ impl Manager {
         })
     }
 
-    pub fn run(
+    /// Work with `config` instead of the one on disk
+    pub fn with_config(config: Config) -> Self {
+        Self { config }
+    }
+
+    /// Only loads the current project's saved entry, which is all `run`
+    /// needs. Falls back to a full load when no project can be detected.
+    pub fn for_current_project() -> Result<Self> {
+        let config = match Project::get_id() {
+            Ok(project_id) => {
+                Config::load_scoped(&project_id, Project::get_key(&project_id).as_deref())?
+            }
+            Err(_) => Config::load()?,
+        };
+        Ok(Self { config })
+    }
+
+    /// Run the runtime and return its exit code. Out-of-memory is an error.
+    pub fn run(&mut self, runtime: &Runtime, args: &[String], options: &RunOptions) -> Result<i32> {
+        let system_gb = Self::system_memory_gb();
+        let final_memory = self.resolve_memory(runtime, options, system_gb)?;
+        self.run_resolved(runtime, args, options, final_memory, None)
+    }
+
+    /// Run with `final_memory` already resolved. A run stopped through
+    /// `stop` returns its exit code without being recorded.
+    fn run_resolved(
         &mut self,
         runtime: &Runtime,
         args: &[String],
-        memory: Option<String>,
-        save: bool,
-    ) -> Result<()> {
-        let system_gb = Self::system_memory_gb();
-        let final_memory = self.resolve_memory(runtime, &memory, system_gb)?;
+        options: &RunOptions,
+        final_memory: Option<String>,
+        stop: Option<&signals::Stop>,
+    ) -> Result<i32> {
+        let exec_options = Self::exec_options(options, final_memory, Project::working_dir()?);
+        Self::warn_v8_flags(runtime, options);
+
+        if options.dry_run {
+            Self::print_dry_run(runtime, args, &exec_options);
+            return Ok(0);
+        }
+
+        let program = exec_options.program.as_deref();
+        if exec_options.memory.is_some()
+            && let Some(version) = timings::measure("runtime_version", || {
+                self.installed_version(runtime, program)
+            })
+            && let Some(warning) = runtime.version_warning(version)
+        {
+            outln!("{}", warning);
+        }
+
+        if runtime.is_eval(args)
+            && let Some(mem) = exec_options.memory.as_ref()
+        {
+            let note = if options.memory.is_some() && !options.save {
+                " (not saved, pass --save to keep it)"
+            } else {
+                ""
+            };
+            outln!("⚡ Eval snippet: heap limit {} MB{}", mem, note);
+        } else if !options.save
+            && options.memory.is_some()
+            && let Some(mem) = exec_options.memory.as_ref()
+        {
+            outln!("⚙️  Applying {} MB (not saved)", mem);
+        }
 
-        if save && memory.is_some() {
-            self.save_project_config(runtime, memory.as_ref().unwrap())?;
+        // Percentages are saved resolved, as on this machine. A value
+        // resolved from the config it would be saved to is left alone.
+        if options.save
+            && let Some(mem) = exec_options.memory.as_ref()
+            && (options.memory.is_some() || !self.is_saved(runtime, mem, options)?)
+        {
+            let value = options
+                .memory
+                .as_deref()
+                .and_then(|m| MemoryValue::parse(m).ok());
+            let percent = value.and_then(MemoryValue::percent);
+            let saving = if options.local {
+                self.save_local_config(runtime, mem, percent)
+            } else {
+                self.save_project_config(runtime, mem, percent, options.profile.as_deref())
+            };
+            match Self::unless_read_only(saving)? {
+                Some(saved_to) => outln!("{}", Self::applying_message(mem, saved_to.as_deref())),
+                None => outln!("⚙️  Applying {} MB (not saved)", mem),
+            }
         }
 
-        println!("Running {} with args: {:?}", runtime.name(), args);
+        outln!("Running {} with args: {:?}", runtime.name(), args);
 
         // Start the child process and wait for completion
-        let child = runtime.execute(args, final_memory.as_deref())?;
-        let output = child.wait_with_output()?;
+        let child = runtime.execute(args, &exec_options)?;
+        if let Some(stop) = stop {
+            stop.attach(&child);
+        }
+        let forwarding = signals::forward_to(&child);
+        let watchdog = options
+            .timeout
+            .map(|secs| signals::watchdog(&child, Duration::from_secs(secs)));
+        let output = timings::measure(timings::CHILD, || child.wait_with_output())?;
+        let timed_out = watchdog.is_some_and(signals::Watchdog::finish);
+        let stopped = stop.is_some_and(signals::Stop::detach);
+        drop(forwarding);
 
         // Print stderr
         let stderr = String::from_utf8_lossy(&output.stderr);
         eprint!("{}", stderr);
 
+        if stopped {
+            return Ok(Self::exit_code(output.status));
+        }
+
+        // A timed-out child was killed by us, so only its output can tell
+        // whether it also ran out of memory
+        let oom = if timed_out {
+            runtime.is_oom_error(&stderr)
+        } else {
+            runtime.check_oom_from_output(&stderr, output.status)
+        };
+        let success = output.status.success() && !oom && !timed_out;
+        if let Ok(project_id) = Project::get_id() {
+            let memory = exec_options.memory.as_deref();
+            let peak_mb = usage::children_peak_mb();
+            let recorded = self.record_run(runtime, &project_id, memory, success, peak_mb, options);
+            Self::unless_read_only(recorded)?;
+        }
+
         // Check for OOM error
-        if runtime.check_oom_from_output(&stderr) {
-            self.handle_oom(runtime)?;
-            return Err(anyhow::anyhow!(
-                "Out of Memory - Config updated, please retry"
-            ));
+        if oom {
+            let increased = if options.no_auto_increase {
+                outln!("\n🔴 Out of Memory Detected!");
+                outln!("   Auto-increase disabled, saved config left unchanged");
+                None
+            } else {
+                let ceiling = self.memory_ceiling(options);
+                let handled = Project::get_id().map_or(Ok(None), |project_id| {
+                    self.handle_oom(runtime, &project_id, options.profile.as_deref(), ceiling)
+                });
+                Self::unless_read_only(handled)?.flatten()
+            };
+            if let Some(path) = &options.report_file {
+                let used = exec_options.memory.as_deref();
+                Self::write_oom_report(path, runtime, used, increased.as_deref())?;
+            }
+            if increased.is_some() {
+                return Err(anyhow::anyhow!(
+                    "Out of Memory - Config updated, please retry"
+                ));
+            }
+            return Err(anyhow::anyhow!("Out of Memory"));
+        }
+
+        if let (true, Some(secs)) = (timed_out, options.timeout) {
+            errln!("⏱️  Timed out after {}s, runtime stopped", secs);
+            return Err(anyhow::anyhow!("Timed out after {} seconds", secs));
         }
 
         // Check if command succeeded
         if !output.status.success() {
-            return Err(anyhow::anyhow!("Command failed: {}", output.status));
+            errln!("❌ Command failed: {}", output.status);
         }
 
-        Ok(())
+        Ok(Self::exit_code(output.status))
     }
 
-    fn resolve_memory(
-        &self,
+    /// How to launch the runtime in `cwd` with `memory` for a run with `options`
+    fn exec_options(options: &RunOptions, memory: Option<String>, cwd: PathBuf) -> ExecOptions {
+        ExecOptions {
+            memory,
+            semi_space: options.semi_space,
+            cwd: Some(cwd),
+            override_env: options.override_env,
+            v8_flags: options.v8_flags.clone(),
+            program: options.runtime_path.clone(),
+            env: options.env.clone(),
+            capture_stdout: false,
+            on_interrupt: options.on_interrupt,
+        }
+    }
+
+    fn warn_v8_flags(runtime: &Runtime, options: &RunOptions) {
+        if !options.v8_flags.is_empty() && *runtime != Runtime::Deno {
+            errln!(
+                "⚠️  --v8-flag only applies to Deno, ignored for {}",
+                runtime.name()
+            );
+        }
+    }
+
+    /// Run the runtime, then run it again whenever files under `paths`
+    /// change, stopping a run that is still going. Memory is resolved once
+    /// and kept for every run, until one fails with an error such as
+    /// running out of memory. Runs until frc is interrupted.
+    pub fn watch(
+        &mut self,
         runtime: &Runtime,
-        explicit_memory: &Option<String>,
-        system_gb: u64,
-    ) -> Result<Option<String>> {
-        if let Some(mem) = explicit_memory.as_ref() {
-            if let Ok(mem_mb) = mem.parse::<u64>() {
-                match runtime.validate_memory(mem_mb, system_gb) {
-                    Ok(warning) if !warning.is_empty() => println!("{}", warning),
-                    Err(e) => {
-                        eprintln!("❌ Error: {}", e);
-                        eprintln!("\n{}", runtime.recommend_memory(system_gb));
-                        return Err(e);
-                    }
-                    _ => {}
+        args: &[String],
+        mut options: RunOptions,
+        paths: &[PathBuf],
+        debounce: Duration,
+    ) -> Result<()> {
+        let stop = signals::Stop::default();
+        let (_watcher, changes) = watch::on_change(paths, debounce, stop.clone())?;
+        let mut memory = None;
+
+        loop {
+            let resolved = match memory.take() {
+                Some(memory) => Ok(memory),
+                None => self.resolve_memory(runtime, &options, Self::system_memory_gb()),
+            };
+            let result = resolved.and_then(|resolved| {
+                memory = Some(resolved.clone());
+                self.run_resolved(runtime, args, &options, resolved, Some(&stop))
+            });
+            match result {
+                // Ctrl-C reached the runtime, so the user wants out
+                Ok(code) if Self::interrupted(code) => return Ok(()),
+                Ok(_) => {}
+                Err(e) => {
+                    errln!("❌ {:#}", e);
+                    memory = None;
+                }
+            }
+            // -m is applied (and saved) by the first run; later ones reuse
+            // the memory without asking or saving again
+            options.memory = None;
+            options.save = false;
+            options.interactive = false;
+
+            if changes.try_recv().is_err() {
+                outln!(
+                    "\n👀 Waiting for changes in {}",
+                    Self::describe_paths(paths)
+                );
+                if changes.recv().is_err() {
+                    return Ok(());
                 }
             }
-            return Ok(Some(mem.to_string()));
+            while changes.try_recv().is_ok() {}
+            outln!("\n🔄 Change detected, restarting {}", runtime.name());
         }
+    }
 
-        if let Ok(project_id) = Project::get_id() {
-            if let Some(project_config) = self.config.get_project(&project_id) {
-                if project_config.runtime == runtime.name() {
-                    let name = Project::get_name(&project_id);
-                    println!(
-                        "📌 Using saved config for '{}': {} MB",
-                        name, project_config.memory
+    /// Whether `code` is how a runtime stopped by Ctrl-C exits
+    fn interrupted(code: i32) -> bool {
+        const STATUS_CONTROL_C_EXIT: u32 = 0xC000013A;
+        code == 128 + 2 || code == STATUS_CONTROL_C_EXIT as i32
+    }
+
+    fn describe_paths(paths: &[PathBuf]) -> String {
+        paths
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// `None` instead of an error when the config couldn't be written
+    /// for lack of permission, so the run goes on with what it resolved.
+    /// The first such failure is explained on stderr.
+    fn unless_read_only<T>(result: Result<T>) -> Result<Option<T>> {
+        static WARNED: OnceLock<()> = OnceLock::new();
+        match result {
+            Ok(value) => Ok(Some(value)),
+            Err(e) if Storage::is_read_only(&e) => {
+                if WARNED.set(()).is_ok() {
+                    errln!("⚠️  Could not save config: {:#}", e);
+                    errln!(
+                        "   Set {} to a writable directory to keep settings",
+                        CONFIG_HOME_ENV
                     );
-                    return Ok(Some(project_config.memory.clone()));
                 }
-            } else if runtime.supports_memory_config() {
-                let recommended = Runtime::default_memory(system_gb);
-                println!("💡 No saved config. Recommended: {} MB", recommended);
-                println!("   Run with -m {} to use and save this value", recommended);
+                Ok(None)
             }
+            Err(e) => Err(e),
         }
-
-        Ok(None)
     }
 
-    fn save_project_config(&mut self, runtime: &Runtime, memory: &str) -> Result<()> {
-        if let Ok(project_id) = Project::get_id() {
-            let project_name = Project::get_name(&project_id);
+    /// Count the run against `project_id`'s saved config for `runtime`
+    /// and store its peak memory, if known. After enough successful runs,
+    /// suggest or (`--auto-tune`) apply a lower value. Profile runs aren't
+    /// counted, only marked as used so `cleanup` keeps the profile.
+    fn record_run(
+        &mut self,
+        runtime: &Runtime,
+        project_id: &str,
+        memory: Option<&str>,
+        success: bool,
+        peak_mb: Option<u64>,
+        options: &RunOptions,
+    ) -> Result<()> {
+        if let Some(name) = options.profile.as_deref() {
+            if self.config.touch_profile(project_id, name, runtime.name()) {
+                self.config.save()?;
+            }
+            return Ok(());
+        }
+        let saved = self.config.get_project(project_id, None);
+        if saved.is_none_or(|project| project.runtime != runtime.name())
+            || !self.config.record_run(project_id, memory, success)
+        {
+            return Ok(());
+        }
+        if let Some(peak_mb) = peak_mb {
+            verbose!("peak memory: {} MB", peak_mb);
+            self.config.set_project_peak(project_id, peak_mb);
+        }
 
-            self.config
-                .save_project(project_id, runtime.name().to_string(), memory.to_string());
-            self.config.save()?;
+        let decrease = self
+            .config
+            .maybe_decrease_project_memory(project_id, options.auto_tune);
+        self.config.save()?;
 
-            println!(
-                "💾 Saved config for '{}': {} {} MB",
-                project_name,
-                runtime.name(),
-                memory
-            );
+        if let Some((old, new)) = decrease {
+            if options.auto_tune {
+                outln!(
+                    "📉 Auto-tuned: {} MB → {} MB after {} successful runs",
+                    old,
+                    new,
+                    DECREASE_AFTER_RUNS
+                );
+            } else {
+                outln!(
+                    "💡 The last {} runs succeeded with {} MB; --auto-tune would lower it to {} MB",
+                    DECREASE_AFTER_RUNS,
+                    old,
+                    new
+                );
+            }
         }
         Ok(())
     }
 
-    fn handle_oom(&mut self, _runtime: &Runtime) -> Result<()> {
-        if let Ok(project_id) = Project::get_id()
-            && let Some((old, new)) = self.config.increase_project_memory(&project_id)
+    /// The code a shell would report for `status`: 128 + N when the child
+    /// was killed by signal N (e.g. 137 for SIGKILL)
+    fn exit_code(status: std::process::ExitStatus) -> i32 {
+        if let Some(code) = status.code() {
+            return code;
+        }
+
+        #[cfg(unix)]
         {
-            self.config.save()?;
+            use std::os::unix::process::ExitStatusExt;
+            if let Some(signal) = status.signal() {
+                return 128 + signal;
+            }
+        }
+
+        1
+    }
 
-            let name = Project::get_name(&project_id);
-            println!("\n🔴 Out of Memory Detected!");
-            println!("📈 Auto-increased: {} MB → {} MB", old, new);
-            println!("💾 Saved for project '{}'", name);
-            println!("\n💡 Run the same command again to use {} MB", new);
+    /// Version of the binary a run of `runtime` starts. It is kept in the
+    /// config by the binary's path and modification time and written with
+    /// the run's own save, so a saved project only runs `--version` again
+    /// after the runtime is reinstalled or upgraded. Runs that don't save
+    /// leave the config file alone.
+    fn installed_version(&mut self, runtime: &Runtime, program: Option<&Path>) -> Option<Version> {
+        let path = Runtime::locate(program.unwrap_or(Path::new(runtime.name())))?;
+        let modified = std::fs::metadata(&path).ok()?.modified().ok()?;
+        let modified = modified
+            .duration_since(std::time::UNIX_EPOCH)
+            .ok()?
+            .as_secs();
+        let key = path.to_string_lossy().to_string();
+        if let Some(version) = self.config.runtime_version(&key, modified) {
+            verbose!("runtime version: {} (saved)", version);
+            return Version::parse(version);
         }
-        Ok(())
+
+        let version = Runtime::version_of(path.as_os_str())?;
+        self.config
+            .set_runtime_version(key, modified, version.to_string());
+        Some(version)
     }
 
-    pub fn show_project(&self) -> Result<()> {
-        let project_id = Project::get_id()?;
-        let project_name = Project::get_name(&project_id);
+    fn print_dry_run(runtime: &Runtime, args: &[String], exec_options: &ExecOptions) {
+        let cmd = runtime.build_command(args, exec_options);
+        let (line, env) = Runtime::describe_command(&cmd);
 
-        println!("📂 Project: {}", project_name);
-        println!("   Path: {}", project_id);
+        outln!("\n🔍 Dry run, nothing executed:");
+        outln!("   Command: {}", line);
+        for var in env {
+            outln!("   Env: {}", var);
+        }
+        if let Some(cwd) = cmd.get_current_dir() {
+            outln!("   Cwd: {}", cwd.display());
+        }
+    }
 
-        if let Some(config) = self.config.get_project(&project_id) {
-            let datetime = Self::format_timestamp(config.last_used);
-            println!("\n⚙️  Saved Configuration:");
-            println!("   Runtime: {}", config.runtime);
-            println!("   Memory: {} MB", config.memory);
-            println!("   Last used: {}", datetime);
-        } else {
-            println!("\n❌ No saved configuration");
-            println!("   Run with -m <memory> to save a config");
+    /// Memory from `-m`, else from `FRC_MEMORY` (`forced`), with its source
+    fn explicit_memory(
+        options: &RunOptions,
+        forced: Option<String>,
+    ) -> Option<(&'static str, String)> {
+        match &options.memory {
+            Some(mem) => Some(("-m", mem.clone())),
+            None => forced
+                .filter(|mem| !mem.trim().is_empty())
+                .map(|mem| (MEMORY_ENV, mem)),
         }
+    }
 
-        Ok(())
+    /// Memory in MB for `runtime`, from the first source that applies:
+    /// `-m` or FRC_MEMORY, a parent frc, the profile, `.frc.json`,
+    /// package.json, the saved config, then the global default. `None`
+    /// leaves the runtime's own default. Prints which source was used. The
+    /// value is validated against the runtime's minimum and system memory
+    /// before the memory ceiling applies.
+    pub fn resolve_memory(
+        &mut self,
+        runtime: &Runtime,
+        options: &RunOptions,
+        system_gb: u64,
+    ) -> Result<Option<String>> {
+        let project_id = Project::get_id().ok();
+        self.resolve_memory_for(project_id.as_deref(), runtime, options, system_gb)
     }
 
-    pub fn list_projects(&self) -> Result<()> {
-        let projects = self.config.list_projects();
+    /// `resolve_memory` for the project at `project_id` rather than the
+    /// current one
+    fn resolve_memory_for(
+        &mut self,
+        project_id: Option<&str>,
+        runtime: &Runtime,
+        options: &RunOptions,
+        system_gb: u64,
+    ) -> Result<Option<String>> {
+        let memory = self.resolve_uncapped(project_id, runtime, options, system_gb)?;
+        // Whatever the source, the value must suit the runtime and system
+        if let Some(memory_mb) = memory {
+            match runtime.validate_memory(memory_mb, system_gb) {
+                Ok(warning) if !warning.is_empty() => outln!("{}", warning),
+                Err(e) => {
+                    verbose!("validation: failed against {} GB", system_gb);
+                    errln!("❌ Error: {}", e);
+                    errln!("\n{}", runtime.recommend_memory(system_gb));
+                    return Err(e.into());
+                }
+                _ => {}
+            }
+            verbose!("validation: ok against {} GB", system_gb);
+        }
+        let Some(ceiling) = self.memory_ceiling(options) else {
+            return Ok(memory.map(|mb| mb.to_string()));
+        };
 
-        if projects.is_empty() {
-            println!("No saved project configurations");
-            return Ok(());
+        match memory {
+            Some(memory_mb) if memory_mb > ceiling => {
+                let error = FrcError::MemoryExceedsCeiling {
+                    memory_mb,
+                    ceiling_mb: ceiling,
+                };
+                if options.strict {
+                    errln!("❌ Error: {}", error);
+                    return Err(error.into());
+                }
+                verbose!("ceiling: clamped {} MB to {} MB", memory_mb, ceiling);
+                errln!("⚠️  {}; using {} MB", error, ceiling);
+                Ok(Some(ceiling.to_string()))
+            }
+            _ => Ok(memory.map(|mb| mb.to_string())),
+        }
+    }
+
+    /// `--memory-max`, else the ceiling saved in the config
+    fn memory_ceiling(&self, options: &RunOptions) -> Option<u64> {
+        options.memory_max.or(self.config.memory_ceiling())
+    }
+
+    /// The memory `resolve_memory_for` starts from, in MB, before
+    /// validation and the ceiling
+    fn resolve_uncapped(
+        &mut self,
+        project_id: Option<&str>,
+        runtime: &Runtime,
+        options: &RunOptions,
+        system_gb: u64,
+    ) -> Result<Option<u64>> {
+        let forced = std::env::var(MEMORY_ENV).ok();
+        if let Some((source, mem)) = Self::explicit_memory(options, forced) {
+            let value = if source == MEMORY_ENV {
+                MemoryValue::parse(&mem).with_context(|| format!("Invalid {}", MEMORY_ENV))?
+            } else {
+                MemoryValue::parse(&mem)?
+            };
+            let mem_mb = value.to_mb(system_gb);
+            verbose!("source: {} {} ({} MB)", source, mem, mem_mb);
+            if source == MEMORY_ENV && !options.save {
+                outln!("🌐 Using {} (not saved): {} MB", MEMORY_ENV, mem_mb);
+            } else if source == MEMORY_ENV {
+                outln!("🌐 Using {}: {} MB", MEMORY_ENV, mem_mb);
+            }
+            if let Some(percent) = value.percent() {
+                outln!("📐 {}% of system memory: {} MB", percent, mem_mb);
+            }
+            return Ok(Some(mem_mb));
         }
 
-        println!("📚 Saved Project Configurations:\n");
+        if options.inherit_parent_memory
+            && let Some(mem) = Self::parent_memory()
+        {
+            verbose!("source: parent frc ({} MB)", mem);
+            outln!("🔗 Using memory inherited from parent frc: {} MB", mem);
+            return Ok(Some(mem));
+        }
 
-        for (path, config) in projects {
-            let name = Project::get_name(path);
-            let datetime = Self::format_timestamp(config.last_used);
+        match project_id {
+            Some(id) => verbose!("project: {}", id),
+            None => verbose!("project: none detected"),
+        }
 
-            println!("  📂 {}", name);
-            println!("     Path: {}", path);
-            println!(
-                "     Runtime: {} | Memory: {} MB | Last used: {}",
-                config.runtime, config.memory, datetime
+        if let Some(name) = options.profile.as_deref()
+            && let Some(id) = project_id
+        {
+            match self.config.get_profile(id, name) {
+                Some(profile) if profile.runtime == runtime.name() => {
+                    let memory_mb = MemoryValue::parse(&profile.memory)
+                        .with_context(|| format!("Invalid memory in profile '{}'", name))?
+                        .to_mb(system_gb);
+                    verbose!("source: profile '{}' ({} MB)", name, memory_mb);
+                    outln!(
+                        "📌 Using profile '{}' for '{}': {} MB",
+                        name,
+                        Project::get_name(id),
+                        memory_mb
+                    );
+                    return Ok(Some(memory_mb));
+                }
+                Some(profile) => verbose!("profile '{}': for {}, not used", name, profile.runtime),
+                None => verbose!("profile '{}': not saved yet", name),
+            }
+        }
+
+        if let Some(id) = project_id
+            && let Some(local) = Storage::load_local(Path::new(id))?
+            && local.runtime == runtime.name()
+        {
+            let file = Path::new(id).join(LOCAL_FILE_NAME);
+            let memory_mb = Self::file_memory(&local.memory, &file, system_gb)?;
+            verbose!("source: {} ({} MB)", LOCAL_FILE_NAME, memory_mb);
+            outln!(
+                "📌 Using {} for '{}': {} MB",
+                LOCAL_FILE_NAME,
+                Project::get_name(id),
+                memory_mb
             );
-            println!();
+            return Ok(Some(memory_mb));
         }
 
-        Ok(())
+        if let Some(id) = project_id
+            && let Some(package) = Storage::load_package(Path::new(id))?
+            && package
+                .runtime
+                .as_deref()
+                .is_none_or(|r| r == runtime.name())
+        {
+            let file = Path::new(id).join("package.json");
+            let memory_mb = Self::file_memory(&package.memory, &file, system_gb)?;
+            verbose!("source: package.json ({} MB)", memory_mb);
+            let name = Project::get_name(id);
+            outln!("📦 Using package.json for '{}': {} MB", name, memory_mb);
+            return Ok(Some(memory_mb));
+        }
+
+        let project_config = project_id.and_then(|id| {
+            let key = Project::get_key(id);
+            Some((id, self.config.get_project(id, key.as_deref())?))
+        });
+
+        if let Some((_, saved)) = project_config
+            && saved.runtime != runtime.name()
+        {
+            verbose!("saved config: for {}, not used", saved.runtime);
+        }
+
+        if let Some((_, project_config)) = project_config
+            && project_config.runtime == runtime.name()
+        {
+            let memory_mb = MemoryValue::parse(&project_config.memory)
+                .context("Invalid memory in the saved config")?
+                .to_mb(system_gb);
+            verbose!("source: saved config ({} MB)", memory_mb);
+            outln!("{}", Self::using_saved_message(&memory_mb.to_string()));
+            return Ok(Some(memory_mb));
+        }
+
+        if let Some(default) = self.config.get_global_default() {
+            let memory_mb = MemoryValue::parse(&default.memory)
+                .context("Invalid global default memory")?
+                .to_mb(system_gb);
+            verbose!("source: global default ({} MB)", memory_mb);
+            outln!("🌐 Using global default: {} MB", memory_mb);
+            return Ok(Some(memory_mb));
+        }
+
+        if project_id.is_some() && project_config.is_none() {
+            let recommended = self.recommended_memory(runtime, system_gb);
+            if options.save {
+                verbose!("source: recommended ({} MB)", recommended);
+                outln!("💡 No saved config, using recommended: {} MB", recommended);
+                return Ok(Some(recommended));
+            }
+            if options.interactive {
+                let mut input = std::io::stdin().lock();
+                let picked = Self::prompt_memory(runtime, recommended, system_gb, &mut input)?;
+                if let Some(memory_mb) = picked {
+                    let memory = memory_mb.to_string();
+                    let profile = options.profile.as_deref();
+                    let saved_to = self.save_project_config(runtime, &memory, None, profile)?;
+                    outln!("{}", Self::applying_message(&memory, saved_to.as_deref()));
+                }
+                return Ok(picked);
+            }
+            outln!("💡 No saved config. Recommended: {} MB", recommended);
+            outln!("   Run with -m {} to use and save this value", recommended);
+        }
+
+        verbose!("source: none, runtime default applies");
+        Ok(None)
     }
 
-    pub fn forget_project(&mut self, path: Option<String>) -> Result<()> {
-        let project_id = path.unwrap_or_else(|| Project::get_id().unwrap());
-        let project_name = Project::get_name(&project_id);
+    /// `memory` as written in a project file, with plain numbers shown as MB
+    fn written_memory(memory: &str) -> String {
+        match MemoryValue::parse(memory) {
+            Ok(MemoryValue::Mb(mb)) => format!("{} MB", mb),
+            _ => memory.to_string(),
+        }
+    }
 
-        if self.config.remove_project(&project_id) {
-            self.config.save()?;
-            println!("✅ Removed config for '{}'", project_name);
-        } else {
-            println!("❌ No config found for '{}'", project_name);
+    /// Memory written in `file`, in MB. Takes the same forms as `-m`.
+    fn file_memory(value: &str, file: &Path, system_gb: u64) -> Result<u64> {
+        let value = MemoryValue::parse(value)
+            .with_context(|| format!("Invalid memory in {}", file.display()))?;
+        Ok(value.to_mb(system_gb))
+    }
+
+    /// Ask whether to use `recommended`, a custom value or nothing.
+    /// Invalid answers are asked again; end of input skips.
+    fn prompt_memory(
+        runtime: &Runtime,
+        recommended: u64,
+        system_gb: u64,
+        input: &mut impl BufRead,
+    ) -> Result<Option<u64>> {
+        outln!("💡 No saved config for this project.");
+        loop {
+            print!(
+                "   Use {} MB? [Enter] accept, a number for custom MB, or 's' to skip: ",
+                recommended
+            );
+            std::io::stdout().flush()?;
+
+            let mut answer = String::new();
+            if input.read_line(&mut answer)? == 0 {
+                outln!();
+                return Ok(None);
+            }
+
+            let answer = answer.trim().to_lowercase();
+            match answer.as_str() {
+                "" | "y" | "yes" => return Ok(Some(recommended)),
+                "s" | "skip" | "n" | "no" => return Ok(None),
+                _ => {}
+            }
+
+            let Some(memory_mb) = answer.parse::<u64>().ok().filter(|mb| *mb > 0) else {
+                outln!("   Invalid answer '{}': expected MB, e.g. 4096", answer);
+                continue;
+            };
+            // Warnings are printed once the value is resolved
+            if let Err(e) = runtime.validate_memory(memory_mb, system_gb) {
+                outln!("   {}", e);
+                continue;
+            }
+            return Ok(Some(memory_mb));
         }
+    }
 
-        Ok(())
+    /// Memory resolved by an enclosing frc process, passed down via the environment
+    fn parent_memory() -> Option<u64> {
+        let value = std::env::var(runtime::RESOLVED_MEMORY_ENV).ok()?;
+        value.trim().parse::<u64>().ok()
     }
 
-    pub fn cleanup(&mut self, days: u64) -> Result<()> {
-        let before = self.config.list_projects().len();
-        self.config.cleanup_old_projects(days);
-        let after = self.config.list_projects().len();
+    /// Printed when an explicit value is used, and saved to `saved_to`
+    /// unless no project was detected
+    fn applying_message(memory: &str, saved_to: Option<&str>) -> String {
+        match saved_to {
+            Some(saved_to) => format!("💾 Applying {} MB (saved to {})", memory, saved_to),
+            None => format!(
+                "⚙️  Applying {} MB (not saved, no project detected)",
+                memory
+            ),
+        }
+    }
 
-        self.config.save()?;
+    /// Whether `memory` is what `--save` would find where it saves for
+    /// `runtime`
+    fn is_saved(&self, runtime: &Runtime, memory: &str, options: &RunOptions) -> Result<bool> {
+        let Ok(id) = Project::get_id() else {
+            return Ok(false);
+        };
+        let saved = if options.local {
+            Storage::load_local(Path::new(&id))?
+                .filter(|local| local.runtime == runtime.name())
+                .map(|local| local.memory)
+        } else {
+            let settings = match options.profile.as_deref() {
+                Some(name) => self.config.get_profile(&id, name),
+                None => self
+                    .config
+                    .get_project(&id, Project::get_key(&id).as_deref()),
+            };
+            settings
+                .filter(|settings| settings.runtime == runtime.name())
+                .map(|settings| settings.memory.clone())
+        };
+        Ok(saved.as_deref() == Some(memory))
+    }
 
-        println!(
-            "🧹 Cleaned up {} config(s) older than {} days",
-            before - after,
-            days
+    /// Printed when a run uses the project's saved value
+    fn using_saved_message(memory: &str) -> String {
+        format!("📌 Using saved {} MB", memory)
+    }
+
+    /// Returns where the value went, for `applying_message`. A project
+    /// directory that can't be written to, such as a read-only mount,
+    /// gets the value in the user config instead.
+    fn save_local_config(
+        &mut self,
+        runtime: &Runtime,
+        memory: &str,
+        percent: Option<u64>,
+    ) -> Result<Option<String>> {
+        let project_id = Project::get_id()?;
+        if !Storage::is_writable(Path::new(&project_id)) {
+            errln!(
+                "⚠️  Can't write {} in {}; saving to the user config instead",
+                LOCAL_FILE_NAME,
+                project_id
+            );
+            return self.save_project_config(runtime, memory, percent, None);
+        }
+        let local = LocalSettings {
+            runtime: runtime.name().to_string(),
+            memory: memory.to_string(),
+        };
+        Storage::save_local(Path::new(&project_id), &local)?;
+        Ok(Some(LOCAL_FILE_NAME.to_string()))
+    }
+
+    /// Save to the project's default config, or to `profile`. Returns
+    /// where the value went, for `applying_message`.
+    fn save_project_config(
+        &mut self,
+        runtime: &Runtime,
+        memory: &str,
+        percent: Option<u64>,
+        profile: Option<&str>,
+    ) -> Result<Option<String>> {
+        let Ok(project_id) = Project::get_id() else {
+            return Ok(None);
+        };
+
+        if let Some(name) = profile {
+            let (runtime, memory) = (runtime.name(), memory.to_string());
+            self.config
+                .save_profile(&project_id, name, runtime.to_string(), memory);
+            self.config.save()?;
+            return Ok(Some(format!("profile '{}'", name)));
+        }
+
+        self.config.save_project(
+            project_id.clone(),
+            runtime.name().to_string(),
+            memory.to_string(),
         );
-        Ok(())
+        self.config
+            .set_project_key(&project_id, Project::get_key(&project_id));
+        self.config.set_project_percent(&project_id, percent);
+        self.config.save()?;
+        Ok(Some("project".to_string()))
     }
 
-    pub fn show_recommendations(&self, runtime: &Runtime) -> Result<()> {
-        let system_gb = Self::system_memory_gb();
+    /// Raise `project_id`'s saved memory after it ran out. Returns the new
+    /// memory if the saved one was raised.
+    fn handle_oom(
+        &mut self,
+        runtime: &Runtime,
+        project_id: &str,
+        profile: Option<&str>,
+        ceiling: Option<u64>,
+    ) -> Result<Option<String>> {
+        if let Some(name) = profile
+            && self
+                .config
+                .get_profile(project_id, name)
+                .is_some_and(|saved| saved.runtime == runtime.name())
+        {
+            let system_mb = Self::system_memory_gb() * 1024;
+            let increase = self
+                .config
+                .increase_profile_memory(project_id, name, system_mb, ceiling);
+            let label = format!("profile '{}'", name);
+            return self.report_increase(increase, &label, ceiling);
+        }
 
-        println!("\n📊 System: {} GB", system_gb);
-        println!("\n💡 Recommendations for {}:", runtime.name());
-        println!("   {}", runtime.recommend_memory(system_gb));
+        // A committed file wins over the user config, so raising the latter
+        // would have no effect
+        if let Some(local) = Storage::load_local(Path::new(project_id))?
+            && local.runtime == runtime.name()
+        {
+            outln!("\n🔴 Out of Memory Detected!");
+            outln!(
+                "📝 Memory comes from {} ({}); raise it there or run with -m <MB> --local",
+                LOCAL_FILE_NAME,
+                Self::written_memory(&local.memory)
+            );
+            return Ok(None);
+        }
+
+        let key = Project::get_key(project_id);
+        let system_mb = Self::system_memory_gb() * 1024;
+        let increase =
+            self.config
+                .increase_project_memory(project_id, key.as_deref(), system_mb, ceiling);
+        let label = format!("project '{}'", Project::get_name(project_id));
+        self.report_increase(increase, &label, ceiling)
+    }
+
+    /// Save and announce an OOM increase of the config named by `label`
+    fn report_increase(
+        &self,
+        increase: Option<MemoryIncrease>,
+        label: &str,
+        ceiling: Option<u64>,
+    ) -> Result<Option<String>> {
+        match increase {
+            Some(MemoryIncrease::Increased { old, new }) => {
+                self.config.save()?;
+
+                outln!("\n🔴 Out of Memory Detected!");
+                outln!("📈 Auto-increased: {} MB → {} MB", old, new);
+                outln!("💾 Saved for {}", label);
+                outln!("\n💡 Run the same command again to use {} MB", new);
+                Ok(Some(new))
+            }
+            Some(MemoryIncrease::AtCeiling { memory }) => {
+                let limit = match (memory.parse::<u64>(), ceiling) {
+                    (Ok(mb), Some(ceiling)) if mb >= ceiling => "the memory ceiling",
+                    _ => "90% of system memory",
+                };
+                outln!("\n🔴 Out of Memory Detected!");
+                outln!(
+                    "⛔ Already at {} MB ({}), cannot increase further",
+                    memory,
+                    limit
+                );
+                outln!("💡 Consider splitting the build");
+                Ok(None)
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Write the `--report-file` record for an out-of-memory run that got
+    /// `used` MB; `increased` is the newly saved value, if any
+    fn write_oom_report(
+        path: &Path,
+        runtime: &Runtime,
+        used: Option<&str>,
+        increased: Option<&str>,
+    ) -> Result<()> {
+        let report = OomReport {
+            event: "oom",
+            old_mb: used.and_then(|mb| mb.parse().ok()),
+            new_mb: increased.and_then(|mb| mb.parse().ok()),
+            runtime: runtime.name(),
+            project: Project::get_id().ok(),
+        };
+        let mut content = serde_json::to_string_pretty(&report)?;
+        content.push('\n');
+        std::fs::write(path, content)
+            .with_context(|| format!("Failed to write report {}", path.display()))
+    }
+
+    /// The user's default for `runtime` if set, else the built-in ladder
+    fn recommended_memory(&self, runtime: &Runtime, system_gb: u64) -> u64 {
+        self.config
+            .get_runtime_default(runtime.name())
+            .and_then(|memory| memory.parse().ok())
+            .unwrap_or_else(|| Runtime::default_memory(system_gb))
+    }
 
-        if runtime.supports_memory_config() {
-            let recommended = Runtime::default_memory(system_gb);
-            println!("\n📝 Examples:");
-            println!("   frc -m {} {} script.js", recommended, runtime.name());
+    /// Save the memory used when no project config applies, or with
+    /// `runtime`, the memory recommended for that runtime
+    pub fn set_default(&mut self, runtime: Option<&Runtime>, memory: &str) -> Result<()> {
+        // Same forms as `-m`; a percentage is saved as MB on this machine
+        let system_gb = Self::system_memory_gb();
+        let memory_mb = MemoryValue::parse(memory)?.to_mb(system_gb);
+        if memory_mb > system_gb * 1024 {
+            return Err(FrcError::MemoryExceedsSystem {
+                memory_mb,
+                system_gb,
+            }
+            .into());
         }
 
+        match runtime {
+            Some(runtime) => {
+                self.config
+                    .set_runtime_default(runtime.name().to_string(), memory_mb.to_string());
+                self.config.save()?;
+                outln!("💾 Saved {} default: {} MB", runtime.name(), memory_mb);
+            }
+            None => {
+                self.config.set_global_default(memory_mb.to_string());
+                self.config.save()?;
+                outln!("💾 Saved global default: {} MB", memory_mb);
+            }
+        }
         Ok(())
     }
 
-    fn system_memory_gb() -> u64 {
-        if let Ok(output) = Command::new("sh")
-            .arg("-c")
-            .arg("sysctl -n hw.memsize 2>/dev/null || grep MemTotal /proc/meminfo 2>/dev/null | awk '{print $2}'")
-            .output()
-            && let Ok(stdout) = String::from_utf8(output.stdout)
-                && let Ok(bytes) = stdout.trim().parse::<u64>() {
-                    return if bytes > 1_000_000_000 {
-                        bytes / (1024 * 1024 * 1024)
-                    } else {
-                        bytes / (1024 * 1024)
-                    };
-                }
-        16
+    /// Save the most memory any run may get (`set-default --memory-max`),
+    /// or with `None` remove it. `--memory-max` on a run still wins.
+    pub fn set_memory_ceiling(&mut self, ceiling: Option<u64>) -> Result<()> {
+        self.config.set_memory_ceiling(ceiling);
+        self.config.save()?;
+        match ceiling {
+            Some(ceiling) => outln!("💾 Saved memory ceiling: {} MB", ceiling),
+            None => outln!("🗑️  Removed memory ceiling"),
+        }
+        Ok(())
     }
 
-    fn format_timestamp(ts: u64) -> String {
-        chrono::DateTime::from_timestamp(ts as i64, 0)
-            .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+    pub fn show_project(&self, json: bool) -> Result<()> {
+        let project_id = Project::get_id()?;
+        let project_name = Project::get_name(&project_id);
+        let key = Project::get_key(&project_id);
+        let saved = self.config.get_project(&project_id, key.as_deref());
+        let workspace_root = Project::workspace_root()?
+            .map(|root| root.to_string_lossy().to_string())
+            .filter(|root| *root != project_id);
+        let local = Storage::load_local(Path::new(&project_id))?;
+        let package = Storage::load_package(Path::new(&project_id))?;
+        let profiles = self.config.list_profiles(&project_id);
+
+        if json {
+            let report = ProjectReport {
+                name: project_name,
+                path: &project_id,
+                workspace_root,
+                local,
+                package,
+                config: saved,
+                profiles,
+                memory_ceiling_mb: self.config.memory_ceiling(),
+            };
+            println!("{}", serde_json::to_string_pretty(&report)?);
+            return Ok(());
+        }
+
+        outln!("📂 Project: {}", project_name);
+        outln!("   Path: {}", project_id);
+        if let Some(root) = &workspace_root {
+            outln!("   Workspace: {} (--workspace-root binds there)", root);
+        }
+        if let Some(local) = &local {
+            outln!(
+                "   {}: {} {} (overrides saved config)",
+                LOCAL_FILE_NAME,
+                local.runtime,
+                Self::written_memory(&local.memory)
+            );
+        }
+        if let Some(package) = &package {
+            let runtime = package.runtime.as_deref().unwrap_or("any runtime");
+            outln!(
+                "   package.json: {} {} (overrides saved config)",
+                runtime,
+                Self::written_memory(&package.memory)
+            );
+        }
+
+        if let Some(config) = saved {
+            let datetime = Self::format_timestamp(config.last_used);
+            outln!("\n⚙️  Saved Configuration:");
+            outln!("   Runtime: {}", config.runtime);
+            if let Some(note) = &config.note {
+                outln!("   Note: {}", note);
+            }
+            let share = match config.percent {
+                Some(percent) => format!(" ({}% of system memory)", percent),
+                None => String::new(),
+            };
+            outln!("   Memory: {} MB{}", config.memory, share);
+            outln!("   Last used: {}", datetime);
+            outln!("   Runs: {}", config.run_count);
+            if !config.created_by_version.is_empty() {
+                outln!("   Created by: frc {}", config.created_by_version);
+            }
+            if let Some(peak_mb) = config.last_peak_mb {
+                outln!("   Last peak: {} MB", peak_mb);
+            }
+            if !config.history.is_empty() {
+                outln!("   Recent runs:");
+                for record in config.history.iter().rev() {
+                    let datetime = Self::format_timestamp(record.timestamp);
+                    outln!("     {}  {} MB", datetime, record.memory);
+                }
+            }
+        } else {
+            outln!("\n❌ No saved configuration");
+            outln!("   Run with -m <memory> to save a config");
+        }
+
+        if !profiles.is_empty() {
+            outln!("\n🗂️  Profiles:");
+            for (name, profile) in &profiles {
+                outln!("   {}: {} {} MB", name, profile.runtime, profile.memory);
+            }
+        }
+
+        if let Some(ceiling) = self.config.memory_ceiling() {
+            outln!(
+                "\n⛔ Memory ceiling: {} MB (set-default --memory-max)",
+                ceiling
+            );
+        }
+
+        Ok(())
+    }
+
+    /// List saved projects, newest first. `pattern` is a glob over project
+    /// paths; a leading `~` stands for the home directory.
+    pub fn list_projects(
+        &self,
+        pattern: Option<&str>,
+        runtime: Option<&Runtime>,
+        limit: Option<usize>,
+        json: bool,
+    ) -> Result<()> {
+        let pattern = pattern
+            .map(|pattern| {
+                let expanded = Self::expand_home(pattern);
+                glob::Pattern::new(&expanded)
+                    .with_context(|| format!("Invalid path pattern '{}'", pattern))
+            })
+            .transpose()?;
+        let runtime = runtime.map(Runtime::name);
+        let projects = self
+            .config
+            .filter_projects(pattern.as_ref(), runtime, limit);
+
+        if !json && projects.is_empty() {
+            if pattern.is_some() || runtime.is_some() {
+                outln!("No saved projects match the filter");
+            } else {
+                outln!("No saved project configurations");
+            }
+            return Ok(());
+        }
+
+        Self::print_projects(&projects, json)
+    }
+
+    fn expand_home(path: &str) -> String {
+        let home = dirs::home_dir();
+        match (path.strip_prefix('~'), home) {
+            (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with(['/', '\\']) => {
+                format!("{}{}", home.display(), rest)
+            }
+            _ => path.to_string(),
+        }
+    }
+
+    /// List projects whose saved memory is above and/or below a threshold in MB
+    pub fn find_projects(&self, above: Option<u64>, below: Option<u64>, json: bool) -> Result<()> {
+        let projects = self.config.find_projects(above, below);
+
+        if !json && projects.is_empty() {
+            outln!("No saved projects match the memory threshold");
+            return Ok(());
+        }
+
+        Self::print_projects(&projects, json)
+    }
+
+    pub fn show_stats(&self, json: bool) -> Result<()> {
+        let stats = self.config.stats();
+        if json {
+            println!("{}", serde_json::to_string_pretty(&stats)?);
+            return Ok(());
+        }
+
+        let mb = |value: Option<u64>| value.map_or("-".to_string(), |mb| format!("{} MB", mb));
+        let runtimes: Vec<_> = stats
+            .by_runtime
+            .iter()
+            .map(|(runtime, count)| format!("{} {}", runtime, count))
+            .collect();
+
+        outln!("📊 Saved Project Stats:\n");
+        outln!("  Projects:        {}", stats.count);
+        if !runtimes.is_empty() {
+            outln!("  By runtime:      {}", runtimes.join(", "));
+        }
+        outln!("  Average memory:  {}", mb(stats.average_memory));
+        outln!("  Median memory:   {}", mb(stats.median_memory));
+        outln!("  Max memory:      {}", mb(stats.max_memory));
+        outln!("  Unused {}+ days: {}", STALE_DAYS, stats.stale);
+
+        Ok(())
+    }
+
+    fn print_projects(projects: &[(&String, &ProjectSettings)], json: bool) -> Result<()> {
+        if json {
+            let reports: Vec<_> = projects
+                .iter()
+                .map(|(path, config)| ProjectReport {
+                    name: Project::get_name(path),
+                    path,
+                    workspace_root: None,
+                    local: None,
+                    package: None,
+                    config: Some(config),
+                    profiles: Vec::new(),
+                    memory_ceiling_mb: None,
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&reports)?);
+            return Ok(());
+        }
+
+        outln!("📚 Saved Project Configurations:\n");
+
+        for (path, config) in projects {
+            let name = Project::get_name(path);
+            let datetime = Self::format_timestamp(config.last_used);
+
+            outln!("  📂 {}", name);
+            outln!("     Path: {}", path);
+            if let Some(note) = &config.note {
+                outln!("     Note: {}", note);
+            }
+            outln!(
+                "     Runtime: {} | Memory: {} MB | Last used: {}",
+                config.runtime,
+                config.memory,
+                datetime
+            );
+            outln!();
+        }
+
+        Ok(())
+    }
+
+    pub fn forget_project(&mut self, path: Option<String>, force: bool) -> Result<()> {
+        let project_id = Self::resolve_project_id(path, Project::get_id)?;
+        let project_name = Project::get_name(&project_id);
+
+        if self.config.get_project(&project_id, None).is_none() {
+            outln!("❌ No config found for '{}'", project_name);
+            return Ok(());
+        }
+        if !Self::confirm_removal(1, force)? {
+            return Ok(());
+        }
+
+        self.config.remove_project(&project_id);
+        self.config.save()?;
+        outln!("✅ Removed config for '{}'", project_name);
+        Ok(())
+    }
+
+    /// Ask before removing `count` configs, unless `force`. Without a
+    /// terminal to ask on, removing needs `force`.
+    fn confirm_removal(count: usize, force: bool) -> Result<bool> {
+        let interactive = std::io::stdin().is_terminal();
+        Self::confirm_removal_with(count, force, interactive, &mut std::io::stdin().lock())
+    }
+
+    fn confirm_removal_with(
+        count: usize,
+        force: bool,
+        interactive: bool,
+        input: &mut impl BufRead,
+    ) -> Result<bool> {
+        if force || count == 0 {
+            return Ok(true);
+        }
+        if !interactive {
+            anyhow::bail!(
+                "Refusing to remove {} config(s) without confirmation; pass --force",
+                count
+            );
+        }
+
+        print!("Remove {} config(s)? [y/N] ", count);
+        std::io::stdout().flush()?;
+        let mut answer = String::new();
+        input.read_line(&mut answer)?;
+        let confirmed = matches!(answer.trim().to_lowercase().as_str(), "y" | "yes");
+        if !confirmed {
+            outln!("Nothing removed");
+        }
+        Ok(confirmed)
+    }
+
+    /// Switch the current project's saved runtime to `runtime`
+    pub fn use_runtime(&mut self, runtime: &Runtime) -> Result<()> {
+        let project_id = Project::get_id()?;
+        let project_name = Project::get_name(&project_id);
+        if self.config.get_project(&project_id, None).is_none() {
+            anyhow::bail!(
+                "No config found for '{}', run with -m <memory> first",
+                project_name
+            );
+        }
+        let previous = self
+            .config
+            .set_project_runtime(&project_id, runtime.name())?;
+        self.config.save()?;
+
+        outln!(
+            "✅ '{}' now uses {} (was {})",
+            project_name,
+            runtime.name(),
+            previous
+        );
+        Ok(())
+    }
+
+    /// Attach a note to the current project's saved config; empty clears it
+    pub fn set_note(&mut self, note: &str) -> Result<()> {
+        let project_id = Project::get_id()?;
+        let project_name = Project::get_name(&project_id);
+        if self.config.get_project(&project_id, None).is_none() {
+            anyhow::bail!(
+                "No config found for '{}', run with -m <memory> first",
+                project_name
+            );
+        }
+        self.config.set_project_note(&project_id, note)?;
+        self.config.save()?;
+
+        if note.trim().is_empty() {
+            outln!("✅ Cleared the note for '{}'", project_name);
+        } else {
+            outln!("✅ Noted for '{}'", project_name);
+        }
+        Ok(())
+    }
+
+    pub fn rename_project(&mut self, from: &str, to: &str, force: bool) -> Result<()> {
+        self.config.rename_project(from, to, force)?;
+        self.config.save()?;
+        outln!(
+            "✅ Moved config for '{}' to {}",
+            Project::get_name(from),
+            to
+        );
+        Ok(())
+    }
+
+    /// Open the config file in $VISUAL or $EDITOR, then check it still parses
+    pub fn edit_config() -> Result<()> {
+        let path = Storage::config_path()?;
+        let editor = Self::editor();
+        Self::edit_file(&path, |path| {
+            let mut words = editor.split_whitespace();
+            let program = words.next().unwrap_or_default();
+            let status = Command::new(program)
+                .args(words)
+                .arg(path)
+                .status()
+                .with_context(|| format!("Failed to start editor '{}'", editor))?;
+            if !status.success() {
+                anyhow::bail!("Editor '{}' exited with {}", editor, status);
+            }
+            Ok(())
+        })?;
+        Ok(())
+    }
+
+    /// The user's editor command, possibly with arguments (`code --wait`)
+    fn editor() -> String {
+        ["VISUAL", "EDITOR"]
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|editor| !editor.trim().is_empty())
+            .unwrap_or_else(|| if cfg!(windows) { "notepad" } else { "vi" }.to_string())
+    }
+
+    /// Create `path` (and its directory) if missing, `launch` an editor on
+    /// it, then report whether it still parses. Returns whether it does.
+    fn edit_file(path: &Path, launch: impl FnOnce(&Path) -> Result<()>) -> Result<bool> {
+        if !path.exists() {
+            Storage::export(&StorageData::default(), path)?;
+        }
+        launch(path)?;
+
+        match Storage::import(path) {
+            Ok(_) => {
+                outln!("✅ {} is valid", path.display());
+                Ok(true)
+            }
+            Err(e) => {
+                errln!("⚠️  {:#}", e);
+                errln!("   Fix it, or frc will back it up and start over on its next run");
+                Ok(false)
+            }
+        }
+    }
+
+    /// Write every saved config to `path` for another machine to import
+    pub fn export_config(&self, path: &Path) -> Result<()> {
+        Storage::export(self.config.data(), path)?;
+        outln!(
+            "✅ Exported {} project config(s) to {}",
+            self.config.list_projects().len(),
+            path.display()
+        );
+        Ok(())
+    }
+
+    /// Replace the saved configs with those exported to `path`, or with
+    /// `merge` add them to the saved ones
+    pub fn import_config(&mut self, path: &Path, merge: bool) -> Result<()> {
+        let data = Storage::import(path)?;
+        let changed = self.config.import(data, merge);
+        self.config.save()?;
+        let verb = if merge { "Merged" } else { "Imported" };
+        outln!(
+            "✅ {} {} project config(s) from {}",
+            verb,
+            changed,
+            path.display()
+        );
+        Ok(())
+    }
+
+    /// `path` as given, else the project detected by `detect`
+    fn resolve_project_id(
+        path: Option<String>,
+        detect: impl FnOnce() -> Result<String>,
+    ) -> Result<String> {
+        match path {
+            Some(path) => Ok(path),
+            None => detect().context("Could not detect a project in the current directory"),
+        }
+    }
+
+    /// Remove configs older than `days`, only those for `runtime` if given
+    pub fn cleanup(&mut self, days: u64, runtime: Option<&Runtime>, force: bool) -> Result<()> {
+        let old = self
+            .config
+            .old_projects(days, runtime.map(Runtime::name))
+            .len();
+        if !Self::confirm_removal(old, force)? {
+            return Ok(());
+        }
+
+        let before = self.config.list_projects().len();
+        self.config
+            .cleanup_old_projects(days, runtime.map(Runtime::name));
+        let after = self.config.list_projects().len();
+
+        self.config.save()?;
+
+        let scope = match runtime {
+            Some(runtime) => format!(" {}", runtime.name()),
+            None => String::new(),
+        };
+        outln!(
+            "🧹 Cleaned up {}{} config(s) older than {} days",
+            before - after,
+            scope,
+            days
+        );
+        Ok(())
+    }
+
+    /// Remove configs whose project directory is gone, or with `dry_run`
+    /// only list them
+    pub fn prune(&mut self, dry_run: bool) -> Result<()> {
+        let missing = self.config.missing_projects();
+        if missing.is_empty() {
+            outln!("✨ Every saved project directory still exists");
+            return Ok(());
+        }
+
+        for path in &missing {
+            outln!("  🗑️  {}", path);
+        }
+        if dry_run {
+            outln!(
+                "\n🔍 Would remove {} config(s), run without --dry-run to remove them",
+                missing.len()
+            );
+            return Ok(());
+        }
+
+        for path in &missing {
+            self.config.remove_project(path);
+        }
+        self.config.save()?;
+        outln!(
+            "\n🧹 Removed {} config(s) for missing directories",
+            missing.len()
+        );
+        Ok(())
+    }
+
+    /// Run `args` with `runtime` in every saved project's directory, with
+    /// memory resolved as a run in that directory would, then print a
+    /// summary. Projects saved for another runtime are skipped. With
+    /// `keep_going`, failures don't stop the batch and are listed in the
+    /// summary. Returns the batch exit code.
+    pub fn run_all(
+        &mut self,
+        runtime: &Runtime,
+        args: &[String],
+        options: &RunOptions,
+        jobs: usize,
+        keep_going: bool,
+    ) -> Result<i32> {
+        let saved: Vec<(String, String)> = self
+            .config
+            .list_projects()
+            .into_iter()
+            .map(|(path, project)| (path.clone(), project.runtime.clone()))
+            .collect();
+        if saved.is_empty() {
+            outln!("No saved project configurations");
+            return Ok(0);
+        }
+
+        let system_gb = Self::system_memory_gb();
+        let mut projects = Vec::new();
+        // Projects whose memory couldn't be resolved, with `keep_going`
+        let mut unresolved = Vec::new();
+        for (path, saved_runtime) in saved {
+            if saved_runtime != runtime.name() {
+                errln!("⏭️  Skipping {}: saved for {}", path, saved_runtime);
+                continue;
+            }
+            let memory = match self.resolve_memory_for(Some(&path), runtime, options, system_gb) {
+                Ok(memory) => memory,
+                Err(e) if keep_going => {
+                    unresolved.push((path, e.to_string()));
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            // The saved entry is itself a source, so there is always a value
+            if let Some(memory) = memory {
+                projects.push((path, memory));
+            }
+        }
+        if projects.is_empty() && unresolved.is_empty() {
+            outln!("No saved projects use {}", runtime.name());
+            return Ok(0);
+        }
+
+        Self::warn_v8_flags(runtime, options);
+        // With several jobs, each project's output is held back and printed
+        // in one piece so projects don't interleave
+        let parallel = jobs > 1 && projects.len() > 1;
+        let printing = Mutex::new(());
+        let finished = Mutex::new(Vec::new());
+        let mut outcomes = batch::run(&projects, jobs, keep_going, |path, memory| {
+            let mut exec_options =
+                Self::exec_options(options, Some(memory.to_string()), PathBuf::from(path));
+            exec_options.capture_stdout = parallel;
+            if options.dry_run {
+                let _printing = printing.lock().unwrap_or_else(|e| e.into_inner());
+                outln!("\n📂 {}", path);
+                Self::print_dry_run(runtime, args, &exec_options);
+                return Ok(0);
+            }
+            if !parallel {
+                outln!("\n📂 {} ({} MB)", path, memory);
+            }
+
+            let child = if parallel {
+                // The header below already names the memory
+                output::silenced(|| runtime.execute(args, &exec_options))?
+            } else {
+                runtime.execute(args, &exec_options)?
+            };
+            let watchdog = options
+                .timeout
+                .map(|secs| signals::watchdog(&child, Duration::from_secs(secs)));
+            let output = child.wait_with_output()?;
+            let timed_out = watchdog.is_some_and(signals::Watchdog::finish);
+
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            {
+                let _printing = printing.lock().unwrap_or_else(|e| e.into_inner());
+                if parallel {
+                    outln!("\n📂 {} ({} MB)", path, memory);
+                    print!("{}", String::from_utf8_lossy(&output.stdout));
+                }
+                eprint!("{}", stderr);
+            }
+
+            let oom = if timed_out {
+                runtime.is_oom_error(&stderr)
+            } else {
+                runtime.check_oom_from_output(&stderr, output.status)
+            };
+            let success = output.status.success() && !oom && !timed_out;
+            let mut finished = finished.lock().unwrap_or_else(|e| e.into_inner());
+            finished.push((path.to_string(), memory.to_string(), success, oom));
+
+            match (oom, options.timeout) {
+                (true, _) => Err(anyhow::anyhow!("Out of Memory")),
+                (false, Some(secs)) if timed_out => {
+                    Err(anyhow::anyhow!("Timed out after {} seconds", secs))
+                }
+                _ => Ok(Self::exit_code(output.status)),
+            }
+        });
+
+        // Recorded once the batch is done, as each run in its own
+        // directory would have
+        for (path, memory, success, oom) in finished.into_inner().unwrap_or_else(|e| e.into_inner())
+        {
+            let recorded = self.record_run(runtime, &path, Some(&memory), success, None, options);
+            Self::unless_read_only(recorded)?;
+            if oom && !options.no_auto_increase {
+                let ceiling = self.memory_ceiling(options);
+                let profile = options.profile.as_deref();
+                let handled = self.handle_oom(runtime, &path, profile, ceiling);
+                Self::unless_read_only(handled)?;
+            }
+        }
+        for (path, error) in unresolved {
+            projects.push((path, String::new()));
+            outcomes.push(batch::Outcome::Failed(error));
+        }
+
+        outln!("\n📊 Summary:");
+        for line in batch::summary(&projects, &outcomes) {
+            outln!("  {}", line);
+        }
+        let failed = outcomes.iter().filter(|o| **o != batch::Outcome::Exited(0));
+        let failed = failed.count();
+        if failed > 0 {
+            errln!(
+                "❌ {} of {} project(s) did not succeed",
+                failed,
+                projects.len()
+            );
+        } else {
+            outln!("✅ All {} project(s) succeeded", projects.len());
+        }
+        Ok(batch::exit_code(&outcomes))
+    }
+
+    /// Print one line per environment check. Returns false if any failed.
+    pub fn doctor(&mut self) -> Result<bool> {
+        outln!("🩺 frc doctor\n");
+        let mut checks = Vec::new();
+
+        let runtimes = [Runtime::Node, Runtime::Deno, Runtime::Bun];
+        let installed: Vec<_> = runtimes
+            .iter()
+            .filter_map(|runtime| {
+                let version = runtime.detect_version();
+                checks.push(doctor::check_runtime(runtime, version));
+                version.map(|_| runtime)
+            })
+            .collect();
+        checks.extend(doctor::check_any_runtime(installed.len()));
+
+        checks.push(match Storage::config_path() {
+            Ok(path) => doctor::check_config_dir(path.parent().unwrap_or(Path::new("."))),
+            Err(e) => Check::new(Status::Fail, "config directory", e.to_string()),
+        });
+        checks.push(doctor::check_project(Project::get_id()));
+
+        if let Some(runtime) = installed.first() {
+            // The report replaces the messages a run prints while resolving
+            let options = RunOptions::default();
+            let system_gb = Self::system_memory_gb();
+            let memory = output::silenced(|| self.resolve_memory(runtime, &options, system_gb));
+            checks.push(doctor::check_memory(runtime, memory));
+        }
+        checks.extend(doctor::check_memory_ceiling(self.config.memory_ceiling()));
+
+        for check in &checks {
+            outln!("  {}", check);
+        }
+
+        let failed = checks.iter().any(|check| check.status == Status::Fail);
+        Ok(!failed)
+    }
+
+    pub fn check_overcommit(&self, runtime: Option<&Runtime>) -> Result<()> {
+        let projects = self.config.memory_by_project(runtime.map(|rt| rt.name()));
+
+        if projects.is_empty() {
+            outln!("No saved project configurations");
+            return Ok(());
+        }
+
+        let system_mb = Self::system_memory_gb() * 1024;
+        let total_mb: u64 = projects.iter().map(|(_, mb)| mb).sum();
+        let percentage = (total_mb as f64 / system_mb as f64) * 100.0;
+
+        outln!("\n📊 System: {} MB", system_mb);
+        outln!(
+            "   Configured: {} MB across {} project(s) ({}% of system)",
+            total_mb,
+            projects.len(),
+            percentage as u32
+        );
+
+        if total_mb > system_mb {
+            outln!(
+                "\n⚠️  Warning: running all of these at once would overcommit memory by {} MB",
+                total_mb - system_mb
+            );
+        } else {
+            outln!("\n✅ Running all of these at once fits in system memory");
+        }
+
+        outln!("\n📈 Biggest contributors:");
+        for (path, mb) in projects.iter().take(5) {
+            outln!("   {:>6} MB  {} ({})", mb, Project::get_name(path), path);
+        }
+
+        Ok(())
+    }
+
+    /// Explain the heap limit a run of `runtime` (else the project's saved
+    /// runtime, else Node) with `options` would get against what the
+    /// process actually uses. With `probe`, measure an idle Node process at
+    /// that limit; other runtimes aren't probed.
+    pub fn explain_memory(
+        &mut self,
+        runtime: Option<&Runtime>,
+        options: &RunOptions,
+        probe: bool,
+    ) -> Result<()> {
+        let project_id = Project::get_id()?;
+        let saved = || {
+            let key = Project::get_key(&project_id);
+            let config = self.config.get_project(&project_id, key.as_deref())?;
+            Runtime::from_command(&config.runtime).ok()
+        };
+        let runtime = &runtime.cloned().or_else(saved).unwrap_or(Runtime::Node);
+        // Resolved as a run would, without the messages a run prints
+        let system_gb = Self::system_memory_gb();
+        let memory = output::silenced(|| self.resolve_memory(runtime, options, system_gb))?;
+
+        outln!("📂 Project: {}", Project::get_name(&project_id));
+        match &memory {
+            Some(memory) => outln!(
+                "   Configured heap limit: {} MB ({})",
+                memory,
+                runtime.name()
+            ),
+            None => outln!(
+                "   No heap limit configured; {} derives one from system memory",
+                runtime.name()
+            ),
+        }
+
+        outln!("\n🧠 Where a V8 process's memory goes:");
+        outln!("   Old space   long-lived objects; the only part -m actually caps");
+        outln!("   New space   short-lived objects (semi-spaces), see --semi-space");
+        outln!("   Code space  compiled and JIT-optimized code");
+        outln!("   External    Buffers, ArrayBuffers and native addon allocations");
+        outln!("   Runtime     the binary itself, libuv, thread stacks");
+        outln!("\n   Total RSS is the sum of all of these and routinely exceeds the limit.");
+        if *runtime == Runtime::Bun {
+            outln!("   Bun runs JavaScriptCore rather than V8, but the same holds for it.");
+        }
+
+        if *runtime != Runtime::Node {
+            if probe {
+                outln!(
+                    "\n💡 --probe only measures Node; {} isn't probed",
+                    runtime.name()
+                );
+            }
+            return Ok(());
+        }
+        if !probe {
+            outln!("\n💡 Run with --probe to measure a Node process with this limit");
+            return Ok(());
+        }
+
+        let report = Self::probe_node_memory(memory.as_deref())?;
+        outln!("\n📊 Measured (idle node process):");
+        outln!("   V8 heap limit: {} MB", report.heap_limit_mb);
+        outln!("   Heap used:     {} MB", report.heap_used_mb);
+        outln!("   External:      {} MB", report.external_mb);
+        outln!("   Total RSS:     {} MB", report.rss_mb);
+        outln!(
+            "\n   Even idle, {} MB of RSS sits outside the JS heap.",
+            report.rss_mb.saturating_sub(report.heap_used_mb)
+        );
+
+        Ok(())
+    }
+
+    fn probe_node_memory(memory: Option<&str>) -> Result<MemoryProbe> {
+        let mut cmd = Command::new("node");
+        if let Some(mem) = memory {
+            cmd.arg(format!("--max-old-space-size={}", mem));
+        }
+        let output = cmd.args(["-e", PROBE_SCRIPT]).output()?;
+
+        if !output.status.success() {
+            return Err(anyhow::anyhow!("Memory probe failed: {}", output.status));
+        }
+        Self::parse_probe(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    fn parse_probe(stdout: &str) -> Result<MemoryProbe> {
+        Ok(serde_json::from_str(stdout.trim())?)
+    }
+
+    /// Time the work frc does before spawning a runtime (project detection,
+    /// config load, system memory probe) without spawning anything
+    pub fn benchmark_startup(iterations: u32) -> Result<()> {
+        let mut samples = Vec::new();
+
+        for _ in 0..iterations {
+            let start = Instant::now();
+            let project_id = Project::get_id()?;
+            Config::load_scoped(&project_id, Project::get_key(&project_id).as_deref())?;
+            Self::system_memory_gb();
+            samples.push(start.elapsed());
+        }
+
+        let Some((min, median, max)) = timings::summarize(&mut samples) else {
+            return Ok(());
+        };
+        let ms = |d: std::time::Duration| d.as_secs_f64() * 1000.0;
+        outln!("⏱️  Startup path over {} iteration(s):", iterations);
+        outln!(
+            "   min {:.2} ms | median {:.2} ms | max {:.2} ms",
+            ms(min),
+            ms(median),
+            ms(max)
+        );
+
+        Ok(())
+    }
+
+    /// Print shell lines that apply the project's memory outside frc.
+    /// Only the lines go to stdout so the output can be `eval`ed.
+    pub fn export(&self, runtime: &Runtime, shell: ExportShell) -> Result<()> {
+        let project_id = Project::get_id().ok();
+        let key = project_id.as_deref().and_then(Project::get_key);
+        let saved = project_id
+            .and_then(|id| self.config.get_project(&id, key.as_deref()))
+            .filter(|settings| settings.runtime == runtime.name());
+
+        let memory_mb = match saved {
+            Some(settings) => {
+                errln!("📌 Exporting saved config: {} MB", settings.memory);
+                settings
+                    .memory
+                    .parse::<u64>()
+                    .with_context(|| format!("Invalid saved memory '{}'", settings.memory))?
+            }
+            None => {
+                let recommended = self.recommended_memory(runtime, Self::system_memory_gb());
+                errln!(
+                    "💡 No saved config. Exporting recommended: {} MB",
+                    recommended
+                );
+                recommended
+            }
+        };
+
+        for (name, value) in runtime.export_vars(memory_mb) {
+            println!("{}", Runtime::format_export(name, &value, shell));
+        }
+        Ok(())
+    }
+
+    /// Print every supported runtime with its aliases
+    pub fn list_runtimes(json: bool) -> Result<()> {
+        if json {
+            let reports: Vec<_> = Runtime::ALL
+                .iter()
+                .map(|runtime| RuntimeReport {
+                    name: runtime.name(),
+                    supports_memory_config: runtime.supports_memory_config(),
+                    aliases: runtime.aliases(),
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&reports)?);
+            return Ok(());
+        }
+
+        outln!("🧩 Supported runtimes:\n");
+        for runtime in Runtime::ALL {
+            outln!("  {}", runtime.name());
+            let aliases = runtime.aliases();
+            if !aliases.is_empty() {
+                outln!("         aliases: {}", aliases.join(", "));
+            }
+        }
+        Ok(())
+    }
+
+    /// Print recommendations for each of `runtimes`. With several, the
+    /// JSON output is an array of what a single runtime prints.
+    pub fn show_recommendations(&self, runtimes: &[Runtime], json: bool) -> Result<()> {
+        let system_gb = Self::system_memory_gb();
+
+        if json {
+            let reports: Vec<_> = runtimes
+                .iter()
+                .map(|runtime| {
+                    let (low, high) = runtime.recommend_range(system_gb);
+                    RecommendationReport {
+                        system_gb,
+                        runtime: runtime.name(),
+                        supports_memory_config: runtime.supports_memory_config(),
+                        recommended_mb: self.recommended_memory(runtime, system_gb),
+                        range_mb: MemoryRange { low, high },
+                    }
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&reports)?);
+            return Ok(());
+        }
+
+        outln!("\n📊 System: {} GB", system_gb);
+        for runtime in runtimes {
+            outln!("\n💡 Recommendations for {}:", runtime.name());
+            outln!("   {}", runtime.recommend_memory(system_gb));
+
+            let recommended = self.recommended_memory(runtime, system_gb);
+            if self.config.get_runtime_default(runtime.name()).is_some() {
+                outln!("   Your default: {} MB (set-default -r)", recommended);
+            }
+            outln!("\n📝 Examples:");
+            outln!("   frc -m {} {} script.js", recommended, runtime.name());
+        }
+
+        Ok(())
+    }
+
+    /// Detected once per process, see `cached_memory_gb`
+    pub fn system_memory_gb() -> u64 {
+        Self::cached_memory_gb(&SYSTEM_MEMORY_GB, Self::probe_system_memory_gb)
+    }
+
+    fn cached_memory_gb(cache: &OnceLock<u64>, probe: impl FnOnce() -> u64) -> u64 {
+        *cache.get_or_init(|| timings::measure("system_memory", probe))
+    }
+
+    /// Memory available to frc in GB: physical memory, or a container's
+    /// cgroup limit when that is lower; 16 when the platform doesn't say
+    fn probe_system_memory_gb() -> u64 {
+        let mut system = System::new();
+        system.refresh_memory_specifics(MemoryRefreshKind::nothing().with_ram());
+        match Self::effective_memory_bytes(system.total_memory(), Self::cgroup_limit_bytes()) {
+            0 => 16,
+            bytes => Self::total_memory_gb(bytes).max(1),
+        }
+    }
+
+    /// `physical`, unless a cgroup `limit` is lower
+    fn effective_memory_bytes(physical: u64, limit: Option<u64>) -> u64 {
+        match limit {
+            Some(limit) if limit > 0 && (physical == 0 || limit < physical) => {
+                verbose!("system memory: cgroup limit {} MB", limit / (1024 * 1024));
+                limit
+            }
+            _ => physical,
+        }
+    }
+
+    /// Memory limit of frc's cgroup (v2 `memory.max`, else v1
+    /// `memory.limit_in_bytes`), if one is set
+    #[cfg(target_os = "linux")]
+    fn cgroup_limit_bytes() -> Option<u64> {
+        CGROUP_LIMIT_FILES
+            .iter()
+            .find_map(|path| std::fs::read_to_string(path).ok())
+            .and_then(|content| Self::parse_cgroup_limit(&content))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn cgroup_limit_bytes() -> Option<u64> {
+        None
+    }
+
+    /// A cgroup limit file's content in bytes; `max` means no limit
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    fn parse_cgroup_limit(content: &str) -> Option<u64> {
+        match content.trim() {
+            "max" => None,
+            value => value.parse().ok(),
+        }
+    }
+
+    /// Convert bytes to GB, rounding to the nearest GB: a 16 GB machine
+    /// often reports a little less, and truncating would drop it into the
+    /// lower recommendation tier.
+    fn total_memory_gb(bytes: u64) -> u64 {
+        const GIB: u64 = 1024 * 1024 * 1024;
+        (bytes + GIB / 2) / GIB
+    }
+
+    fn format_timestamp(ts: u64) -> String {
+        chrono::DateTime::from_timestamp(ts as i64, 0)
+            .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
             .unwrap_or_else(|| "unknown".to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolve(config: Config, memory: Option<&str>) -> Option<String> {
+        let mut manager = Manager { config };
+        let options = RunOptions {
+            memory: memory.map(str::to_string),
+            ..Default::default()
+        };
+        manager
+            .resolve_memory(&Runtime::Node, &options, 16)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_expand_home() {
+        let home = dirs::home_dir().unwrap();
+        let expanded = Manager::expand_home("~/work/*");
+        assert_eq!(expanded, format!("{}/work/*", home.display()));
+        assert_eq!(Manager::expand_home("~"), home.display().to_string());
+        assert_eq!(Manager::expand_home("~other/*"), "~other/*");
+        assert_eq!(Manager::expand_home("/srv/*"), "/srv/*");
+    }
+
+    #[test]
+    fn test_resolve_memory_non_interactive_never_prompts() {
+        let project = Project::get_id().unwrap();
+        let mut manager = Manager {
+            config: Config::default(),
+        };
+        let options = RunOptions::default();
+        assert!(!options.interactive);
+
+        let memory = manager.resolve_memory(&Runtime::Node, &options, 16);
+        assert_eq!(memory.unwrap(), None);
+        assert!(manager.config.get_project(&project, None).is_none());
+    }
+
+    #[test]
+    fn test_explicit_memory_prefers_flag_over_env() {
+        let forced = || Some("4096".to_string());
+        let flag = RunOptions {
+            memory: Some("2GB".to_string()),
+            ..Default::default()
+        };
+        let explicit = Manager::explicit_memory(&flag, forced());
+        assert_eq!(explicit, Some(("-m", "2GB".to_string())));
+
+        let none = RunOptions::default();
+        let explicit = Manager::explicit_memory(&none, forced());
+        assert_eq!(explicit, Some((MEMORY_ENV, "4096".to_string())));
+        assert_eq!(Manager::explicit_memory(&none, Some(" ".to_string())), None);
+        assert_eq!(Manager::explicit_memory(&none, None), None);
+    }
+
+    #[test]
+    fn test_resolve_memory_uses_profile() {
+        let project = Project::get_id().unwrap();
+        let mut config = Config::default();
+        config.save_profile(&project, "build", "node".to_string(), "3072".to_string());
+        let mut manager = Manager { config };
+        let mut resolve = |profile: Option<&str>| {
+            let options = RunOptions {
+                profile: profile.map(str::to_string),
+                ..Default::default()
+            };
+            manager
+                .resolve_memory(&Runtime::Node, &options, 16)
+                .unwrap()
+        };
+
+        assert_eq!(resolve(Some("build")).as_deref(), Some("3072"));
+        assert_eq!(resolve(Some("dev")), None);
+        assert_eq!(resolve(None), None);
+    }
+
+    #[test]
+    fn test_prompt_memory() {
+        let prompt = |input: &str| {
+            let mut input = std::io::Cursor::new(input.as_bytes());
+            Manager::prompt_memory(&Runtime::Node, 4096, 16, &mut input).unwrap()
+        };
+
+        assert_eq!(prompt("\n"), Some(4096));
+        assert_eq!(prompt("Y\n"), Some(4096));
+        assert_eq!(prompt("3072\n"), Some(3072));
+        assert_eq!(prompt("s\n"), None);
+        assert_eq!(prompt(""), None);
+        // Invalid and too large answers are asked again
+        assert_eq!(prompt("lots\n0\n99999\n2048\n"), Some(2048));
+        assert_eq!(prompt("lots\n"), None);
+    }
+
+    #[test]
+    fn test_resolve_memory_precedence() {
+        let project = Project::get_id().unwrap();
+        let node = |memory: &str| {
+            let mut config = Config::default();
+            config.save_project(project.clone(), "node".to_string(), memory.to_string());
+            config
+        };
+
+        // Recommendation only: nothing is applied
+        assert_eq!(resolve(Config::default(), None), None);
+
+        let mut config = Config::default();
+        config.set_global_default("3072".to_string());
+        assert_eq!(resolve(config, None).as_deref(), Some("3072"));
+
+        let mut config = node("6144");
+        config.set_global_default("3072".to_string());
+        assert_eq!(resolve(config, None).as_deref(), Some("6144"));
+
+        let mut config = node("6144");
+        config.set_global_default("3072".to_string());
+        assert_eq!(resolve(config, Some("8192")).as_deref(), Some("8192"));
+
+        // A project saved for another runtime doesn't apply
+        let mut config = Config::default();
+        config.save_project(project.clone(), "deno".to_string(), "6144".to_string());
+        config.set_global_default("3072".to_string());
+        assert_eq!(resolve(config, None).as_deref(), Some("3072"));
+    }
+
+    #[test]
+    fn test_resolve_memory_parses_local_file() {
+        let dir = std::env::temp_dir().join(format!("frc-local-units-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let project = dir.to_string_lossy().to_string();
+        let resolve = |memory: &str| {
+            let local = format!(r#"{{"runtime": "node", "memory": "{}"}}"#, memory);
+            std::fs::write(dir.join(LOCAL_FILE_NAME), local).unwrap();
+            let mut manager = Manager::with_config(Config::default());
+            let options = RunOptions::default();
+            manager.resolve_memory_for(Some(&project), &Runtime::Node, &options, 16)
+        };
+
+        assert_eq!(resolve("50%").unwrap().as_deref(), Some("8192"));
+        assert_eq!(resolve("4GB").unwrap().as_deref(), Some("4096"));
+        let err = resolve("lots").unwrap_err();
+        assert!(err.to_string().contains(LOCAL_FILE_NAME));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_resolve_memory_parses_package_json() {
+        let dir = std::env::temp_dir().join(format!("frc-package-units-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let project = dir.to_string_lossy().to_string();
+        let resolve = |memory: &str| {
+            let package = format!(r#"{{"name": "web", "frc": {{"memory": {}}}}}"#, memory);
+            std::fs::write(dir.join("package.json"), package).unwrap();
+            let mut manager = Manager::with_config(Config::default());
+            let options = RunOptions::default();
+            manager.resolve_memory_for(Some(&project), &Runtime::Node, &options, 16)
+        };
+
+        assert_eq!(resolve("4096").unwrap().as_deref(), Some("4096"));
+        assert_eq!(resolve(r#""4GB""#).unwrap().as_deref(), Some("4096"));
+        assert_eq!(resolve(r#""25%""#).unwrap().as_deref(), Some("4096"));
+        let err = resolve(r#""lots""#).unwrap_err();
+        assert!(err.to_string().contains("package.json"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_resolve_memory_validates_every_source() {
+        let dir = std::env::temp_dir().join(format!("frc-validate-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join(LOCAL_FILE_NAME),
+            r#"{"runtime": "node", "memory": "128"}"#,
+        )
+        .unwrap();
+        let project = dir.to_string_lossy().to_string();
+        let options = RunOptions::default();
+
+        let mut manager = Manager::with_config(Config::default());
+        let err = manager
+            .resolve_memory_for(Some(&project), &Runtime::Node, &options, 16)
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<FrcError>(),
+            Some(FrcError::MemoryBelowMinimum { memory_mb: 128, .. })
+        ));
+
+        let mut config = Config::default();
+        config.set_global_default("65536".to_string());
+        let mut manager = Manager::with_config(config);
+        let err = manager
+            .resolve_memory_for(None, &Runtime::Node, &options, 16)
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<FrcError>(),
+            Some(FrcError::MemoryExceedsSystem { .. })
+        ));
+
+        // Hand-edited values are read like `-m`, so unit forms are validated too
+        let mut config = Config::default();
+        config.set_global_default("64GB".to_string());
+        let mut manager = Manager::with_config(config);
+        let err = manager
+            .resolve_memory_for(None, &Runtime::Node, &options, 16)
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<FrcError>(),
+            Some(FrcError::MemoryExceedsSystem { .. })
+        ));
+
+        let mut config = Config::default();
+        config.set_global_default("25%".to_string());
+        let mut manager = Manager::with_config(config);
+        let memory = manager
+            .resolve_memory_for(None, &Runtime::Node, &options, 16)
+            .unwrap();
+        assert_eq!(memory.as_deref(), Some("4096"));
+
+        let mut config = Config::default();
+        config.set_global_default("lots".to_string());
+        let mut manager = Manager::with_config(config);
+        let err = manager
+            .resolve_memory_for(None, &Runtime::Node, &options, 16)
+            .unwrap_err();
+        assert!(err.to_string().contains("global default"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_resolve_memory_clamps_at_ceiling() {
+        let mut config = Config::default();
+        config.set_global_default("6144".to_string());
+        config.set_memory_ceiling(Some(4096));
+        let mut manager = Manager { config };
+        let mut resolve = |memory: Option<&str>, memory_max: Option<u64>, strict: bool| {
+            let options = RunOptions {
+                memory: memory.map(str::to_string),
+                memory_max,
+                strict,
+                ..Default::default()
+            };
+            manager.resolve_memory(&Runtime::Node, &options, 64)
+        };
+
+        // The saved ceiling applies to -m and saved values alike
+        assert_eq!(resolve(None, None, false).unwrap().as_deref(), Some("4096"));
+        assert_eq!(
+            resolve(Some("50%"), None, false).unwrap().as_deref(),
+            Some("4096")
+        );
+        assert_eq!(
+            resolve(Some("2048"), None, false).unwrap().as_deref(),
+            Some("2048")
+        );
+        // --memory-max overrides the saved ceiling
+        let memory = resolve(Some("8GB"), Some(5120), false).unwrap();
+        assert_eq!(memory.as_deref(), Some("5120"));
+
+        let err = resolve(Some("8GB"), None, true).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<FrcError>(),
+            Some(FrcError::MemoryExceedsCeiling {
+                memory_mb: 8192,
+                ceiling_mb: 4096
+            })
+        ));
+        assert!(resolve(Some("4096"), None, true).is_ok());
+    }
+
+    #[test]
+    fn test_runtime_default_overrides_recommendation() {
+        let mut config = Config::default();
+        config.set_runtime_default("node".to_string(), "6144".to_string());
+        let manager = Manager { config };
+
+        assert_eq!(manager.recommended_memory(&Runtime::Node, 16), 6144);
+        assert_eq!(
+            manager.recommended_memory(&Runtime::Deno, 16),
+            Runtime::default_memory(16)
+        );
+        // Overrides are recommendations, not applied memory
+        assert_eq!(resolve(manager.config, None), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_exit_code() {
+        use std::os::unix::process::ExitStatusExt;
+
+        assert_eq!(Manager::exit_code(ExitStatusExt::from_raw(0)), 0);
+        assert_eq!(Manager::exit_code(ExitStatusExt::from_raw(42 << 8)), 42);
+        // Killed by SIGKILL
+        assert_eq!(Manager::exit_code(ExitStatusExt::from_raw(9)), 137);
+    }
+
+    #[test]
+    fn test_resolve_project_id() {
+        let detected = || Ok("/detected".to_string());
+        let failing = || Err(anyhow::anyhow!("current directory is gone"));
+
+        let explicit = Manager::resolve_project_id(Some("/given".to_string()), failing);
+        assert_eq!(explicit.unwrap(), "/given");
+        assert_eq!(
+            Manager::resolve_project_id(None, detected).unwrap(),
+            "/detected"
+        );
+
+        let err = Manager::resolve_project_id(None, failing).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Could not detect a project in the current directory"
+        );
+        assert!(format!("{:#}", err).contains("current directory is gone"));
+    }
+
+    #[test]
+    fn test_confirm_removal() {
+        let confirm = |force: bool, interactive: bool, answer: &str| {
+            let mut input = std::io::Cursor::new(answer.as_bytes());
+            Manager::confirm_removal_with(3, force, interactive, &mut input)
+        };
+
+        // Scripts have to opt in
+        let err = confirm(false, false, "y\n").unwrap_err();
+        assert!(err.to_string().contains("--force"));
+        assert!(confirm(true, false, "").unwrap());
+
+        assert!(confirm(false, true, "y\n").unwrap());
+        assert!(confirm(false, true, "YES\n").unwrap());
+        assert!(!confirm(false, true, "\n").unwrap());
+        assert!(!confirm(false, true, "").unwrap());
+
+        // Nothing to remove, nothing to ask
+        let mut input = std::io::Cursor::new(&b""[..]);
+        assert!(Manager::confirm_removal_with(0, false, false, &mut input).unwrap());
+    }
+
+    #[test]
+    fn test_memory_messages() {
+        assert_eq!(
+            Manager::applying_message("4096", Some("project")),
+            "💾 Applying 4096 MB (saved to project)"
+        );
+        assert_eq!(
+            Manager::applying_message("4096", Some("profile 'build'")),
+            "💾 Applying 4096 MB (saved to profile 'build')"
+        );
+        assert!(Manager::applying_message("4096", None).contains("not saved"));
+        assert_eq!(
+            Manager::using_saved_message("4096"),
+            "📌 Using saved 4096 MB"
+        );
+    }
+
+    #[test]
+    fn test_edit_file_creates_and_validates() {
+        let dir = std::env::temp_dir().join(format!("frc-edit-{}", std::process::id()));
+        let path = dir.join("frc").join("config.json");
+
+        let valid = Manager::edit_file(&path, |path| {
+            assert!(path.exists());
+            assert!(std::fs::read_to_string(path).unwrap().contains("projects"));
+            Ok(())
+        });
+        assert!(valid.unwrap());
+
+        let valid = Manager::edit_file(&path, |path| Ok(std::fs::write(path, "{ oops")?));
+        assert!(!valid.unwrap());
+
+        let failed = Manager::edit_file(&path, |_| anyhow::bail!("editor crashed"));
+        assert!(failed.is_err());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_parse_cgroup_limit() {
+        assert_eq!(Manager::parse_cgroup_limit("max\n"), None);
+        assert_eq!(
+            Manager::parse_cgroup_limit("4294967296\n"),
+            Some(4294967296)
+        );
+        // v1 reports "no limit" as a huge page-aligned number
+        assert_eq!(
+            Manager::parse_cgroup_limit("9223372036854771712"),
+            Some(9223372036854771712)
+        );
+        assert_eq!(Manager::parse_cgroup_limit(""), None);
+        assert_eq!(Manager::parse_cgroup_limit("lots"), None);
+    }
+
+    #[test]
+    fn test_effective_memory_bytes() {
+        const GIB: u64 = 1024 * 1024 * 1024;
+        assert_eq!(
+            Manager::effective_memory_bytes(32 * GIB, Some(4 * GIB)),
+            4 * GIB
+        );
+        assert_eq!(
+            Manager::effective_memory_bytes(32 * GIB, Some(64 * GIB)),
+            32 * GIB
+        );
+        assert_eq!(Manager::effective_memory_bytes(32 * GIB, None), 32 * GIB);
+        assert_eq!(Manager::effective_memory_bytes(32 * GIB, Some(0)), 32 * GIB);
+        assert_eq!(Manager::effective_memory_bytes(0, Some(4 * GIB)), 4 * GIB);
+    }
+
+    #[test]
+    fn test_cached_memory_gb_probes_once() {
+        let cache = OnceLock::new();
+        let mut probes = 0;
+        for _ in 0..3 {
+            let gb = Manager::cached_memory_gb(&cache, || {
+                probes += 1;
+                32
+            });
+            assert_eq!(gb, 32);
+        }
+        assert_eq!(probes, 1);
+    }
+
+    #[test]
+    fn test_probe_system_memory_gb_is_plausible() {
+        let gb = Manager::probe_system_memory_gb();
+        assert!((1..=16 * 1024).contains(&gb), "{} GB", gb);
+    }
+
+    #[test]
+    fn test_total_memory_gb() {
+        const GIB: u64 = 1024 * 1024 * 1024;
+        const MIB: u64 = 1024 * 1024;
+
+        assert_eq!(Manager::total_memory_gb(16 * GIB), 16);
+        assert_eq!(Manager::total_memory_gb(31 * GIB / 2), 16);
+        assert_eq!(Manager::total_memory_gb(31 * GIB / 2 - 1), 15);
+        assert_eq!(Manager::total_memory_gb(159 * GIB / 10), 16);
+        // 16,320 MB lands in the 16 GB tier
+        assert_eq!(Manager::total_memory_gb(16_320 * MIB), 16);
+        assert_eq!(Manager::total_memory_gb(8 * GIB), 8);
+    }
+
+    #[test]
+    fn test_parse_probe() {
+        let stdout = r#"{"heap_limit_mb":4144,"rss_mb":42,"heap_used_mb":4,"external_mb":1}"#;
+        let report = Manager::parse_probe(&format!("{}\n", stdout)).unwrap();
+        assert_eq!(
+            report,
+            MemoryProbe {
+                heap_limit_mb: 4144,
+                rss_mb: 42,
+                heap_used_mb: 4,
+                external_mb: 1,
+            }
+        );
+
+        assert!(Manager::parse_probe("ReferenceError: v8 is not defined").is_err());
+    }
+}
@@ -0,0 +1,125 @@
+//! Memory values accepted by `-m`
+
+use anyhow::{Result, anyhow, bail};
+
+/// Smallest and largest share of system memory `-m <N>%` accepts
+const MIN_PERCENT: u64 = 1;
+const MAX_PERCENT: u64 = 95;
+
+/// A `-m` value: absolute MB, or a share of system memory
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MemoryValue {
+    Mb(u64),
+    Percent(u64),
+}
+
+impl MemoryValue {
+    /// Parse `4096` (MB), `512M`/`512MB`, `4G`/`4GB` or `40%`. Units are
+    /// case-insensitive; zero and fractional values are rejected.
+    pub fn parse(value: &str) -> Result<Self> {
+        let value = value.trim();
+        if let Some(percent) = value.strip_suffix('%') {
+            let percent: u64 = match percent.trim().parse() {
+                Ok(percent) => percent,
+                Err(_) => bail!("Invalid memory '{}': expected a percentage like 40%", value),
+            };
+            if !(MIN_PERCENT..=MAX_PERCENT).contains(&percent) {
+                bail!(
+                    "Invalid memory '{}': percentage must be {}-{}%",
+                    value,
+                    MIN_PERCENT,
+                    MAX_PERCENT
+                );
+            }
+            return Ok(MemoryValue::Percent(percent));
+        }
+
+        let invalid = || anyhow!("Invalid memory '{}': use MB or a unit like 4GB", value);
+        let lower = value.to_ascii_lowercase();
+        let digits_end = lower
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(lower.len());
+        let (amount, unit) = lower.split_at(digits_end);
+        let factor = match unit.trim() {
+            "" | "m" | "mb" => 1,
+            "g" | "gb" => 1024,
+            _ => return Err(invalid()),
+        };
+
+        match amount.parse::<u64>().map_err(|_| invalid())? {
+            0 => bail!("Invalid memory '{}': must be greater than zero", value),
+            amount => Ok(MemoryValue::Mb(
+                amount.checked_mul(factor).ok_or_else(invalid)?,
+            )),
+        }
+    }
+
+    /// Absolute MB on a machine with `system_gb` of memory
+    pub fn to_mb(self, system_gb: u64) -> u64 {
+        match self {
+            MemoryValue::Mb(mb) => mb,
+            MemoryValue::Percent(percent) => system_gb * 1024 * percent / 100,
+        }
+    }
+
+    pub fn percent(self) -> Option<u64> {
+        match self {
+            MemoryValue::Percent(percent) => Some(percent),
+            MemoryValue::Mb(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mb() {
+        assert_eq!(MemoryValue::parse("2048").unwrap(), MemoryValue::Mb(2048));
+        assert_eq!(MemoryValue::parse("512M").unwrap(), MemoryValue::Mb(512));
+        assert_eq!(MemoryValue::parse("512mb").unwrap(), MemoryValue::Mb(512));
+        assert_eq!(MemoryValue::parse("4GB").unwrap(), MemoryValue::Mb(4096));
+        assert_eq!(MemoryValue::parse("4g").unwrap(), MemoryValue::Mb(4096));
+        assert_eq!(MemoryValue::parse("8 GB").unwrap(), MemoryValue::Mb(8192));
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        let invalid = [
+            "",
+            "lots",
+            "0",
+            "0GB",
+            "4.5GB",
+            "4KB",
+            "GB",
+            "-1",
+            "18014398509481984G",
+        ];
+        for value in invalid {
+            let result = MemoryValue::parse(value);
+            assert!(result.is_err(), "{} should be rejected", value);
+        }
+    }
+
+    #[test]
+    fn test_parse_percent() {
+        assert_eq!(MemoryValue::parse("40%").unwrap(), MemoryValue::Percent(40));
+        assert_eq!(MemoryValue::parse("1%").unwrap(), MemoryValue::Percent(1));
+        assert_eq!(MemoryValue::parse("95%").unwrap(), MemoryValue::Percent(95));
+        assert!(MemoryValue::parse("0%").is_err());
+        assert!(MemoryValue::parse("96%").is_err());
+        assert!(MemoryValue::parse("%").is_err());
+        assert!(MemoryValue::parse("half%").is_err());
+    }
+
+    #[test]
+    fn test_percent_to_mb() {
+        let forty = MemoryValue::Percent(40);
+        assert_eq!(forty.to_mb(16), 6553);
+        assert_eq!(forty.to_mb(64), 26214);
+        assert_eq!(MemoryValue::Percent(50).to_mb(8), 4096);
+        assert_eq!(MemoryValue::Mb(3072).to_mb(8), 3072);
+    }
+}
@@ -0,0 +1,235 @@
+//! Terminal-aware rendering of frc's messages. On a terminal, lines that
+//! start with a status emoji are colored; elsewhere (pipes, files,
+//! `NO_COLOR`, `--no-color`) the emoji are replaced with plain ASCII.
+//! With `--quiet`, stdout only carries the runtime's own output.
+
+use std::cell::Cell;
+use std::fmt;
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static STDOUT_STYLED: AtomicBool = AtomicBool::new(false);
+static STDERR_STYLED: AtomicBool = AtomicBool::new(false);
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    /// Set inside `silenced`
+    static SILENCED: Cell<bool> = const { Cell::new(false) };
+}
+
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const YELLOW: &str = "\x1b[33m";
+const CYAN: &str = "\x1b[36m";
+const RESET: &str = "\x1b[0m";
+
+/// Symbols frc prints, their plain-text replacement and the color of a
+/// line they start. Decorative ones are dropped in plain output.
+const SYMBOLS: &[(&str, &str, Option<&str>)] = &[
+    ("✅", "[ok]", Some(GREEN)),
+    ("✓", "[ok]", Some(GREEN)),
+    ("❌", "[error]", Some(RED)),
+    ("⛔", "[error]", Some(RED)),
+    ("🔴", "[error]", Some(RED)),
+    ("⚠️", "[warn]", Some(YELLOW)),
+    ("💡", "[hint]", Some(CYAN)),
+    ("ℹ️", "[info]", Some(CYAN)),
+    ("→", "->", None),
+    ("⏱️", "", None),
+    ("⚙️", "", None),
+    ("🗑️", "", None),
+    ("🗂️", "", None),
+    ("⚡", "", None),
+    ("✨", "", None),
+    ("🌐", "", None),
+    ("🔍", "", None),
+    ("🔗", "", None),
+    ("🧠", "", None),
+    ("🧹", "", None),
+    ("🩺", "", None),
+    ("📂", "", None),
+    ("📈", "", None),
+    ("📉", "", None),
+    ("📌", "", None),
+    ("📊", "", None),
+    ("📐", "", None),
+    ("📚", "", None),
+    ("📝", "", None),
+    ("📦", "", None),
+    ("💾", "", None),
+];
+
+/// Decide once whether stdout and stderr get styled output
+pub fn init(no_color: bool) {
+    let allowed = !no_color && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty());
+    let stdout = allowed && std::io::stdout().is_terminal();
+    let stderr = allowed && std::io::stderr().is_terminal();
+    STDOUT_STYLED.store(stdout, Ordering::Relaxed);
+    STDERR_STYLED.store(stderr, Ordering::Relaxed);
+}
+
+/// Drop `outln!` messages, except warnings and errors, which move to stderr
+pub fn quiet() {
+    QUIET.store(true, Ordering::Relaxed);
+}
+
+/// Run `f` with every `outln!` and `errln!` message on this thread
+/// dropped, for reusing code that reports as it goes
+pub fn silenced<T>(f: impl FnOnce() -> T) -> T {
+    let previous = SILENCED.replace(true);
+    let result = f();
+    SILENCED.set(previous);
+    result
+}
+
+pub fn print(args: fmt::Arguments) {
+    if SILENCED.get() {
+        return;
+    }
+    if QUIET.load(Ordering::Relaxed) {
+        let text = args.to_string();
+        if is_alert(&text) {
+            eprint(format_args!("{}", text));
+        }
+        return;
+    }
+    let styled = STDOUT_STYLED.load(Ordering::Relaxed);
+    println!("{}", render(&args.to_string(), styled));
+}
+
+pub fn eprint(args: fmt::Arguments) {
+    if SILENCED.get() {
+        return;
+    }
+    let styled = STDERR_STYLED.load(Ordering::Relaxed);
+    eprintln!("{}", render(&args.to_string(), styled));
+}
+
+/// `text` colored by its leading symbol, or with every symbol replaced
+/// by plain text when not `styled`
+fn render(text: &str, styled: bool) -> String {
+    text.split('\n')
+        .map(|line| {
+            if styled {
+                color_line(line)
+            } else {
+                plain_line(line)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Whether `text` is a warning or an error, judged by its first line
+fn is_alert(text: &str) -> bool {
+    let text = text.trim_start();
+    SYMBOLS.iter().any(|(symbol, _, color)| {
+        matches!(*color, Some(RED) | Some(YELLOW)) && text.starts_with(symbol)
+    })
+}
+
+fn color_line(line: &str) -> String {
+    let content = line.trim_start();
+    let indent = &line[..line.len() - content.len()];
+    let color = SYMBOLS
+        .iter()
+        .find(|(symbol, _, _)| content.starts_with(symbol))
+        .and_then(|(_, _, color)| *color);
+    match color {
+        Some(color) => format!("{}{}{}{}", indent, color, content, RESET),
+        None => line.to_string(),
+    }
+}
+
+fn plain_line(line: &str) -> String {
+    let mut line = line.to_string();
+    for (symbol, plain, _) in SYMBOLS {
+        while let Some(start) = line.find(symbol) {
+            let mut end = start + symbol.len();
+            if plain.is_empty() {
+                // Drop the spacing that followed a removed symbol too
+                end += line[end..].len() - line[end..].trim_start_matches(' ').len();
+            }
+            line.replace_range(start..end, plain);
+        }
+    }
+    line
+}
+
+/// `println!` that goes through `render`
+macro_rules! outln {
+    () => {
+        $crate::output::print(format_args!(""))
+    };
+    ($($arg:tt)*) => {
+        $crate::output::print(format_args!($($arg)*))
+    };
+}
+pub(crate) use outln;
+
+/// `eprintln!` that goes through `render`
+macro_rules! errln {
+    () => {
+        $crate::output::eprint(format_args!(""))
+    };
+    ($($arg:tt)*) => {
+        $crate::output::eprint(format_args!($($arg)*))
+    };
+}
+pub(crate) use errln;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_styled() {
+        let saved = render("✅ Saved config for 'web': node 4096 MB", true);
+        assert_eq!(
+            saved,
+            "\x1b[32m✅ Saved config for 'web': node 4096 MB\x1b[0m"
+        );
+
+        let nested = render("\n   ⚠️  Warning: low memory", true);
+        assert_eq!(nested, "\n   \x1b[33m⚠️  Warning: low memory\x1b[0m");
+
+        // Decorative symbols keep the line as is
+        let pinned = "📌 Using saved config for 'web': 4096 MB";
+        assert_eq!(render(pinned, true), pinned);
+    }
+
+    #[test]
+    fn test_is_alert() {
+        assert!(is_alert("⚠️  Warning: 80% of system memory"));
+        assert!(is_alert(
+            "\n🔴 Out of Memory Detected!\n   Auto-increase disabled"
+        ));
+        assert!(is_alert("   ❌ Command failed"));
+        assert!(!is_alert("📌 Using saved config for 'web': 4096 MB"));
+        assert!(!is_alert("✅ Saved config for 'web': node 4096 MB"));
+        assert!(!is_alert("Running node with args: []"));
+        assert!(!is_alert(""));
+    }
+
+    #[test]
+    fn test_render_plain() {
+        let saved = render("✅ Saved config for 'web': node 4096 MB", false);
+        assert_eq!(saved, "[ok] Saved config for 'web': node 4096 MB");
+
+        let pinned = render("📌 Using saved config for 'web': 4096 MB", false);
+        assert_eq!(pinned, "Using saved config for 'web': 4096 MB");
+
+        let increase = render(
+            "\n🔴 Out of Memory!\n📈 Auto-increased: 2048 MB → 4096 MB",
+            false,
+        );
+        assert_eq!(
+            increase,
+            "\n[error] Out of Memory!\nAuto-increased: 2048 MB -> 4096 MB"
+        );
+
+        let indented = render("   ⚠️  Warning: low memory", false);
+        assert_eq!(indented, "   [warn]  Warning: low memory");
+        assert!(render(&indented, false).is_ascii());
+    }
+}
@@ -1,6 +1,16 @@
-use anyhow::Result;
+use crate::timings;
+use anyhow::{Context, Result, bail};
 use std::env;
+use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Effective working directory set via `--cwd`
+static WORKING_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Bind to the enclosing monorepo root instead of the nearest package
+static USE_WORKSPACE_ROOT: AtomicBool = AtomicBool::new(false);
 
 pub struct Project;
 
@@ -14,16 +24,86 @@ impl Project {
         "pnpm-workspace.yaml",
         "lerna.json",
         "nx.json",
+        "*.code-workspace",
     ];
 
+    /// Markers of a monorepo root that contains several packages
+    const WORKSPACE_MARKERS: &'static [&'static str] =
+        &["pnpm-workspace.yaml", "lerna.json", "nx.json"];
+
+    /// Operate as if frc was started in `path` (`--cwd`). Affects project
+    /// detection and the directory the runtime is spawned in.
+    pub fn set_working_dir(path: &Path) -> Result<()> {
+        let dir = path
+            .canonicalize()
+            .with_context(|| format!("Cannot access directory: {}", path.display()))?;
+        if !dir.is_dir() {
+            bail!("Not a directory: {}", path.display());
+        }
+        WORKING_DIR
+            .set(dir)
+            .map_err(|_| anyhow::anyhow!("Working directory already set"))
+    }
+
+    pub fn working_dir() -> Result<PathBuf> {
+        match WORKING_DIR.get() {
+            Some(dir) => Ok(dir.clone()),
+            None => Ok(env::current_dir()?),
+        }
+    }
+
+    /// Detect projects at the workspace root rather than the nearest
+    /// package (`--workspace-root`)
+    pub fn use_workspace_root() {
+        USE_WORKSPACE_ROOT.store(true, Ordering::Relaxed);
+    }
+
     pub fn detect_root() -> Result<PathBuf> {
-        let current_dir = env::current_dir()?;
-        let mut dir = current_dir.as_path();
+        Ok(Self::detect_root_from(&Self::working_dir()?))
+    }
+
+    /// The project containing `start`, as `detect_root` would find it if
+    /// frc was started there
+    pub fn detect_root_from(start: &Path) -> PathBuf {
+        timings::measure("detect_root", || Self::find_root(start))
+    }
+
+    /// The monorepo root enclosing the current project, if any
+    pub fn workspace_root() -> Result<Option<PathBuf>> {
+        Ok(Self::workspace_root_from(&Self::detect_root()?))
+    }
+
+    fn find_root(start: &Path) -> PathBuf {
+        let root = Self::root_from(start);
+        if USE_WORKSPACE_ROOT.load(Ordering::Relaxed)
+            && let Some(workspace) = Self::workspace_root_from(&root)
+        {
+            return workspace;
+        }
+        root
+    }
+
+    /// The nearest directory at or above `start` with a workspace marker
+    fn workspace_root_from(start: &Path) -> Option<PathBuf> {
+        start
+            .ancestors()
+            .find(|dir| {
+                Self::WORKSPACE_MARKERS
+                    .iter()
+                    .any(|marker| dir.join(marker).exists())
+            })
+            .map(Path::to_path_buf)
+    }
+
+    /// The nearest directory at or above `start` with any marker, so inside
+    /// a monorepo this is the package rather than the workspace root
+    fn root_from(start: &Path) -> PathBuf {
+        let mut dir = start;
 
         loop {
             for marker in Self::MARKERS {
-                if dir.join(marker).exists() {
-                    return Ok(dir.to_path_buf());
+                if Self::has_marker(dir, marker) {
+                    return dir.to_path_buf();
                 }
             }
 
@@ -33,12 +113,62 @@ impl Project {
             }
         }
 
-        Ok(current_dir)
+        start.to_path_buf()
+    }
+
+    /// Markers are exact file names or glob patterns matched against the
+    /// entries of `dir` (e.g. `*.code-workspace`).
+    fn has_marker(dir: &Path, marker: &str) -> bool {
+        if !marker.contains(['*', '?', '[']) {
+            return dir.join(marker).exists();
+        }
+
+        let Ok(pattern) = glob::Pattern::new(marker) else {
+            return false;
+        };
+        let Ok(entries) = fs::read_dir(dir) else {
+            return false;
+        };
+        entries
+            .flatten()
+            .any(|entry| pattern.matches(&entry.file_name().to_string_lossy()))
     }
 
     pub fn get_id() -> Result<String> {
-        let root = Self::detect_root()?;
-        Ok(root.to_string_lossy().to_string())
+        Ok(Self::get_id_from(&Self::working_dir()?))
+    }
+
+    /// `get_id` for the project containing `start`. Symlinks are resolved,
+    /// so the id is the same whichever way the project was reached.
+    pub fn get_id_from(start: &Path) -> String {
+        let root = Self::detect_root_from(start);
+        Self::canonical(&root).to_string_lossy().to_string()
+    }
+
+    /// `path` with symlinks resolved, or unchanged if it can't be accessed
+    pub fn canonical(path: &Path) -> PathBuf {
+        let Ok(real) = path.canonicalize() else {
+            return path.to_path_buf();
+        };
+
+        // Keep the usual `C:\...` form rather than the verbatim `\\?\C:\...`
+        #[cfg(windows)]
+        if let Some(plain) = real.to_str().and_then(|p| p.strip_prefix(r"\\?\"))
+            && !plain.starts_with("UNC\\")
+        {
+            return PathBuf::from(plain);
+        }
+
+        real
+    }
+
+    /// A path-independent identity for the project at `root`, taken from
+    /// the `name` in its package.json. Unnamed projects have none.
+    pub fn get_key(root: &str) -> Option<String> {
+        let content = fs::read_to_string(Path::new(root).join("package.json")).ok()?;
+        let manifest: serde_json::Value = serde_json::from_str(&content).ok()?;
+        let name = manifest.get("name")?.as_str()?.trim();
+        (!name.is_empty()).then(|| format!("npm:{}", name))
     }
 
     pub fn get_name(path: &str) -> String {
@@ -53,7 +183,6 @@ impl Project {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::fs;
 
     #[test]
     fn test_detect_root_with_cargo() {
@@ -81,7 +210,8 @@ mod tests {
     fn test_get_name_current_project() {
         let id = Project::get_id().unwrap();
         let name = Project::get_name(&id);
-        assert_eq!(name, "fe-run-container");
+        let expected = Project::get_name(env!("CARGO_MANIFEST_DIR"));
+        assert_eq!(name, expected);
     }
 
     #[test]
@@ -91,6 +221,75 @@ mod tests {
         assert!(Project::MARKERS.contains(&"Cargo.toml"));
         assert!(Project::MARKERS.contains(&".git"));
         assert!(Project::MARKERS.contains(&"deno.json"));
+        assert!(Project::MARKERS.contains(&"*.code-workspace"));
+    }
+
+    #[test]
+    fn test_has_marker_glob() {
+        let test_dir = std::env::temp_dir().join(format!("frc-glob-{}", std::process::id()));
+        fs::create_dir_all(&test_dir).unwrap();
+
+        assert!(!Project::has_marker(&test_dir, "*.code-workspace"));
+
+        fs::write(test_dir.join("app.code-workspace"), "{}").unwrap();
+        assert!(Project::has_marker(&test_dir, "*.code-workspace"));
+        assert!(Project::has_marker(&test_dir, "app.code-workspace"));
+        assert!(!Project::has_marker(&test_dir, "*.sln"));
+
+        fs::remove_dir_all(&test_dir).ok();
+    }
+
+    #[test]
+    fn test_monorepo_package_and_workspace_root() {
+        let root = std::env::temp_dir().join(format!("frc-monorepo-{}", std::process::id()));
+        let package = root.join("packages").join("web");
+        fs::create_dir_all(package.join("src")).unwrap();
+        fs::write(root.join("pnpm-workspace.yaml"), "packages: []\n").unwrap();
+        fs::write(root.join("package.json"), "{}").unwrap();
+        fs::write(package.join("package.json"), "{}").unwrap();
+
+        assert_eq!(Project::root_from(&package.join("src")), package);
+        assert_eq!(Project::root_from(&root.join("packages")), root);
+        assert_eq!(Project::workspace_root_from(&package), Some(root.clone()));
+        assert_eq!(Project::workspace_root_from(&root), Some(root.clone()));
+
+        fs::remove_file(root.join("pnpm-workspace.yaml")).unwrap();
+        assert_ne!(Project::workspace_root_from(&package), Some(root.clone()));
+
+        fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn test_get_key() {
+        let dir = std::env::temp_dir().join(format!("frc-key-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let root = dir.to_str().unwrap();
+
+        assert_eq!(Project::get_key(root), None);
+        fs::write(dir.join("package.json"), r#"{"version": "1.0.0"}"#).unwrap();
+        assert_eq!(Project::get_key(root), None);
+        fs::write(dir.join("package.json"), r#"{"name": "@acme/web"}"#).unwrap();
+        assert_eq!(Project::get_key(root).as_deref(), Some("npm:@acme/web"));
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_get_id_resolves_symlinks() {
+        let dir = std::env::temp_dir().join(format!("frc-symlink-{}", std::process::id()));
+        let real = dir.join("real");
+        let link = dir.join("link");
+        fs::create_dir_all(real.join("src")).unwrap();
+        fs::write(real.join("package.json"), "{}").unwrap();
+        std::os::unix::fs::symlink(&real, &link).unwrap();
+
+        let id = Project::get_id_from(&real.join("src"));
+        assert_eq!(id, real.canonicalize().unwrap().to_string_lossy());
+        assert_eq!(Project::get_id_from(&link), id);
+        assert_eq!(Project::get_id_from(&link.join("src")), id);
+
+        fs::remove_dir_all(&dir).ok();
     }
 
     #[test]
@@ -100,17 +299,27 @@ mod tests {
         let test_dir = temp_dir.join(format!("frc-test-{}", std::process::id()));
         fs::create_dir_all(&test_dir).unwrap();
 
-        let original_dir = std::env::current_dir().unwrap();
-        std::env::set_current_dir(&test_dir).unwrap();
-
-        let root = Project::detect_root().unwrap();
-        // Should return current dir when no markers found
-        // Use canonicalize to resolve symlinks (macOS /var -> /private/var)
-        let expected = test_dir.canonicalize().unwrap();
-        let actual = root.canonicalize().unwrap();
-        assert_eq!(actual, expected);
+        // Should return the start dir when no markers are found
+        assert_eq!(Project::detect_root_from(&test_dir), test_dir);
 
-        std::env::set_current_dir(original_dir).unwrap();
         fs::remove_dir_all(&test_dir).ok();
     }
+
+    #[test]
+    fn test_detect_root_from_synthetic_tree() {
+        let root = std::env::temp_dir().join(format!("frc-tree-{}", std::process::id()));
+        let app = root.join("apps").join("site");
+        let deep = app.join("src").join("pages");
+        fs::create_dir_all(&deep).unwrap();
+        fs::create_dir_all(root.join(".git")).unwrap();
+        fs::write(app.join("deno.json"), "{}").unwrap();
+
+        assert_eq!(Project::detect_root_from(&deep), app);
+        assert_eq!(Project::detect_root_from(&app), app);
+        assert_eq!(Project::detect_root_from(&root.join("apps")), root);
+        assert_eq!(Project::get_id_from(&deep), app.to_string_lossy());
+        assert_eq!(Project::get_name(&Project::get_id_from(&deep)), "site");
+
+        fs::remove_dir_all(&root).ok();
+    }
 }
@@ -1,5 +1,162 @@
-use anyhow::{anyhow, Result};
-use std::process::{Child, Command};
+use crate::error::FrcError;
+use crate::output::outln;
+use crate::project::Project;
+use crate::signals::{self, Signal};
+use crate::verbose::verbose;
+use anyhow::Result;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus};
+use std::sync::OnceLock;
+
+/// Set on the child to the memory frc resolved, so a nested frc can inherit it
+pub const RESOLVED_MEMORY_ENV: &str = "FRC_RESOLVED_MEMORY";
+
+/// JavaScriptCore option Bun reads to size its heap, in bytes
+const BUN_RAM_SIZE_ENV: &str = "BUN_JSC_forceRAMSize";
+
+/// Exit code a shell reports for a process killed by SIGKILL (128 + 9)
+const OOM_KILLED_EXIT_CODE: i32 = 137;
+
+/// OOM message fragments shared by every V8-based runtime, lowercase
+/// with single spaces (see `Runtime::normalize_output`)
+const COMMON_OOM_PATTERNS: &[&str] = &[
+    "javascript heap out of memory",
+    "reached heap limit",
+    "allocation failed",
+    "heap out of memory",
+];
+
+/// Newer V8 crash dumps in Node
+const NODE_OOM_PATTERNS: &[&str] = &[
+    "fatal javascript out of memory",
+    "ineffective mark-compacts near heap limit",
+    "last few gcs",
+];
+
+/// Deno prints V8's fatal error and GC trace without Node's wording
+const DENO_OOM_PATTERNS: &[&str] = &[
+    "fatal javascript out of memory",
+    "fatal process out of memory",
+    "markcompact",
+    "last few gcs",
+];
+
+/// JavaScriptCore reports allocation failures as plain "out of memory"
+const BUN_OOM_PATTERNS: &[&str] = &["out of memory", "outofmemory"];
+
+/// Below this budget Bun is also started with `--smol`
+const BUN_SMOL_THRESHOLD_MB: u64 = 1024;
+
+/// Hard floors for `-m`: below these the runtime runs out of memory
+/// almost as soon as it starts
+const NODE_MIN_MEMORY_MB: u64 = 256;
+const DENO_MIN_MEMORY_MB: u64 = 512;
+const BUN_MIN_MEMORY_MB: u64 = 256;
+
+/// Below this, Node's old-space limit is dwarfed by its other memory
+/// regions, so the process uses far more than the requested value
+const NODE_PRACTICAL_MIN_MB: u64 = 512;
+
+/// Oldest releases known to honor the memory settings frc applies:
+/// NODE_OPTIONS heap flags (Node), `--v8-flags` (Deno) and
+/// BUN_JSC_forceRAMSize with `--smol` (Bun)
+const NODE_MIN_VERSION: Version = Version(12, 0, 0);
+const DENO_MIN_VERSION: Version = Version(1, 0, 0);
+const BUN_MIN_VERSION: Version = Version(1, 0, 0);
+
+/// Commands that run on a runtime, matched by executable name. Tools
+/// installed from npm run on Node; `bunx` is Bun's package runner.
+const COMMAND_ALIASES: &[(&str, Runtime)] = &[
+    ("node", Runtime::Node),
+    ("npm", Runtime::Node),
+    ("npx", Runtime::Node),
+    ("pnpm", Runtime::Node),
+    ("yarn", Runtime::Node),
+    ("tsx", Runtime::Node),
+    ("ts-node", Runtime::Node),
+    ("vite", Runtime::Node),
+    ("webpack", Runtime::Node),
+    ("rollup", Runtime::Node),
+    ("esbuild", Runtime::Node),
+    ("next", Runtime::Node),
+    ("deno", Runtime::Deno),
+    ("bun", Runtime::Bun),
+    ("bunx", Runtime::Bun),
+];
+
+/// Installed runtime versions, looked up at most once per process
+static NODE_VERSION: OnceLock<Option<Version>> = OnceLock::new();
+static DENO_VERSION: OnceLock<Option<Version>> = OnceLock::new();
+static BUN_VERSION: OnceLock<Option<Version>> = OnceLock::new();
+
+/// A runtime's `major.minor.patch` version
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version(pub u64, pub u64, pub u64);
+
+impl Version {
+    /// Find the first `x.y.z` (optionally `v`-prefixed) in `--version`
+    /// output such as `v20.11.1`, `deno 1.40.0 (release, ...)` or `1.0.25`
+    pub fn parse(output: &str) -> Option<Self> {
+        output.split_whitespace().find_map(|word| {
+            let mut parts = word.trim_start_matches('v').splitn(3, '.');
+            let major = parts.next()?.parse().ok()?;
+            let minor = parts.next()?.parse().ok()?;
+            // Ignore pre-release and build suffixes (1.2.3-beta, 1.2.3+abc)
+            let patch: String = parts
+                .next()?
+                .chars()
+                .take_while(char::is_ascii_digit)
+                .collect();
+            Some(Version(major, minor, patch.parse().ok()?))
+        })
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.0, self.1, self.2)
+    }
+}
+
+/// How a runtime process is launched
+#[derive(Debug, Default)]
+pub struct ExecOptions {
+    /// Memory limit in MB
+    pub memory: Option<String>,
+    /// Also size Node's young generation from the memory limit
+    pub semi_space: bool,
+    /// Directory to spawn in; inherits frc's when unset
+    pub cwd: Option<PathBuf>,
+    /// Replace flags already set in the inherited NODE_OPTIONS instead of
+    /// deferring to them
+    pub override_env: bool,
+    /// Extra V8 flags (`--v8-flag`), passed to Deno with the heap size
+    pub v8_flags: Vec<String>,
+    /// Binary to run instead of the runtime found on PATH (`--runtime-path`)
+    pub program: Option<PathBuf>,
+    /// Extra environment for the child (`--env`). A NODE_OPTIONS here
+    /// takes the place of the inherited one and is merged the same way.
+    pub env: Vec<(String, String)>,
+    /// Pipe stdout instead of sharing frc's, so it can be printed in one
+    /// piece (`run-all --jobs`)
+    pub capture_stdout: bool,
+    /// Sent to the child instead of SIGINT when frc is interrupted
+    /// (`--on-interrupt`, Unix only)
+    pub on_interrupt: Option<Signal>,
+}
+
+/// Shell syntax for `frc export`
+#[derive(Debug, Clone, Copy, PartialEq, Default, clap::ValueEnum)]
+pub enum ExportShell {
+    /// `export NAME="value"` (bash, zsh, sh)
+    #[default]
+    Posix,
+    /// `set -x NAME "value"`
+    Fish,
+}
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Runtime {
@@ -9,14 +166,120 @@ pub enum Runtime {
 }
 
 impl Runtime {
-    pub fn from_command(cmd: &str) -> Result<Self> {
-        match cmd.to_lowercase().as_str() {
-            "node" => Ok(Runtime::Node),
-            "deno" => Ok(Runtime::Deno),
-            "bun" => Ok(Runtime::Bun),
-            "npm" | "npx" | "pnpm" | "yarn" => Ok(Runtime::Node),
-            _ => Err(anyhow!("Unknown runtime: {}", cmd)),
+    /// `--runtime` value that forces auto-detection from the command
+    pub const AUTO: &'static str = "auto";
+
+    /// Every supported runtime, for `--list-runtimes`
+    pub const ALL: &'static [Runtime] = &[Runtime::Node, Runtime::Deno, Runtime::Bun];
+
+    /// Resolve an explicit `--runtime` value; `auto` yields `None` so the
+    /// caller falls back to detecting the runtime from the command.
+    pub fn from_explicit(value: &str) -> Result<Option<Self>, FrcError> {
+        if value.eq_ignore_ascii_case(Self::AUTO) {
+            return Ok(None);
+        }
+        Self::from_command(value).map(Some)
+    }
+
+    /// Detect the runtime from a command name, or from the shebang line
+    /// when `cmd` is a path to a script
+    pub fn from_command(cmd: &str) -> Result<Self, FrcError> {
+        if let Some(runtime) = Self::from_name(cmd) {
+            return Ok(runtime);
+        }
+        match Self::read_shebang(cmd) {
+            Some((runtime, _)) => Ok(runtime),
+            None => Err(FrcError::UnknownRuntime(cmd.to_string())),
+        }
+    }
+
+    fn from_name(cmd: &str) -> Option<Self> {
+        let name = Self::command_name(cmd);
+        COMMAND_ALIASES
+            .iter()
+            .find(|(alias, _)| *alias == name)
+            .map(|(_, runtime)| runtime.clone())
+    }
+
+    /// The runtime `cmd` implies, when it isn't this one. Catches an
+    /// explicit runtime that doesn't fit the command (`-r deno npm install`).
+    pub fn conflicting_runtime(&self, cmd: &str) -> Option<Runtime> {
+        Self::from_command(cmd)
+            .ok()
+            .filter(|detected| detected != self)
+    }
+
+    /// Other commands `from_command` maps to this runtime
+    pub fn aliases(&self) -> Vec<&'static str> {
+        COMMAND_ALIASES
+            .iter()
+            .filter(|(alias, runtime)| runtime == self && *alias != self.name())
+            .map(|(alias, _)| *alias)
+            .collect()
+    }
+
+    /// Lowercased executable name of `cmd`, without its directory or a
+    /// Windows `.exe`/`.cmd` suffix (`/opt/volta/bin/Node.exe` -> `node`)
+    pub fn command_name(cmd: &str) -> String {
+        let name = Path::new(cmd)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        [".exe", ".cmd"]
+            .iter()
+            .find_map(|ext| name.strip_suffix(ext))
+            .map(str::to_string)
+            .unwrap_or(name)
+    }
+
+    /// Runtime arguments from the shebang of script `cmd` (e.g. `run -A`
+    /// for `#!/usr/bin/env -S deno run -A`), to pass before the script.
+    /// Empty for known commands and files without a shebang.
+    pub fn shebang_args(cmd: &str) -> Vec<String> {
+        if Self::from_name(cmd).is_some() {
+            return Vec::new();
         }
+        Self::read_shebang(cmd)
+            .map(|(_, args)| args)
+            .unwrap_or_default()
+    }
+
+    /// Read the shebang of `cmd`, relative to the working directory
+    fn read_shebang(cmd: &str) -> Option<(Self, Vec<String>)> {
+        let path = Project::working_dir().ok()?.join(cmd);
+        if !path.is_file() {
+            return None;
+        }
+        let mut line = String::new();
+        BufReader::new(File::open(path).ok()?)
+            .read_line(&mut line)
+            .ok()?;
+        Self::parse_shebang(&line)
+    }
+
+    /// Parse `#!/usr/bin/env node`, `#!/usr/bin/env -S deno run -A` or
+    /// `#!/usr/local/bin/bun` into the runtime and its extra arguments
+    fn parse_shebang(line: &str) -> Option<(Self, Vec<String>)> {
+        let mut words = line.strip_prefix("#!")?.split_whitespace();
+        let basename = |word: &str| {
+            Path::new(word)
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+        };
+
+        let mut interpreter = basename(words.next()?)?;
+        if interpreter == "env" {
+            // Skip env's own options such as -S
+            interpreter = basename(words.by_ref().find(|word| !word.starts_with('-'))?)?;
+        }
+
+        let runtime = match interpreter.as_str() {
+            "node" | "nodejs" => Runtime::Node,
+            "deno" => Runtime::Deno,
+            "bun" => Runtime::Bun,
+            _ => return None,
+        };
+        Some((runtime, words.map(str::to_string).collect()))
     }
 
     pub fn name(&self) -> &str {
@@ -31,100 +294,464 @@ impl Runtime {
         self.name()
     }
 
+    /// The installed version from `<runtime> --version`, or `None` if it
+    /// can't be run or parsed. Cached for the rest of the process.
+    pub fn detect_version(&self) -> Option<Version> {
+        let cache = match self {
+            Runtime::Node => &NODE_VERSION,
+            Runtime::Deno => &DENO_VERSION,
+            Runtime::Bun => &BUN_VERSION,
+        };
+        *cache.get_or_init(|| Self::version_of(self.as_str().as_ref()))
+    }
+
+    /// Where `program` runs from: itself if it is a path, else the first
+    /// match on PATH
+    pub fn locate(program: &Path) -> Option<PathBuf> {
+        if program.components().count() > 1 {
+            return Some(program.to_path_buf());
+        }
+        let names: Vec<PathBuf> = if cfg!(windows) && program.extension().is_none() {
+            ["exe", "cmd"]
+                .iter()
+                .map(|ext| program.with_extension(ext))
+                .collect()
+        } else {
+            vec![program.to_path_buf()]
+        };
+        let paths = std::env::var_os("PATH")?;
+        std::env::split_paths(&paths)
+            .flat_map(|dir| names.iter().map(move |name| dir.join(name)))
+            .find(|candidate| candidate.is_file())
+    }
+
+    /// Version reported by `program --version`, uncached
+    pub fn version_of(program: &std::ffi::OsStr) -> Option<Version> {
+        let output = Command::new(program).arg("--version").output().ok()?;
+        Version::parse(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    /// Warn when `version` is older than the floor for the memory settings
+    /// frc applies to this runtime
+    pub fn version_warning(&self, version: Version) -> Option<String> {
+        let floor = match self {
+            Runtime::Node => NODE_MIN_VERSION,
+            Runtime::Deno => DENO_MIN_VERSION,
+            Runtime::Bun => BUN_MIN_VERSION,
+        };
+        (version < floor).then(|| {
+            format!(
+                "⚠️  Warning: {} {} is older than {}; the memory limit may be ignored",
+                self.name(),
+                version,
+                floor
+            )
+        })
+    }
+
+    /// Every supported runtime takes a memory limit; kept for `--json`
+    /// reports and library users
     pub fn supports_memory_config(&self) -> bool {
-        matches!(self, Runtime::Node | Runtime::Deno)
+        matches!(self, Runtime::Node | Runtime::Deno | Runtime::Bun)
     }
 
-    pub fn execute(&self, args: &[String], memory: Option<&str>) -> Result<Child> {
-        if !self.supports_memory_config() && memory.is_some() {
-            println!("⚠️  WARNING: Bun does not support manual memory configuration!");
-            println!("   Bun uses JavaScriptCore and manages memory automatically.");
-            println!("   Memory flag will be ignored.\n");
+    /// Build the fully configured command without spawning it
+    pub fn build_command(&self, args: &[String], options: &ExecOptions) -> Command {
+        let mut cmd = match &options.program {
+            Some(program) => Command::new(program),
+            None => Command::new(self.name()),
+        };
+        cmd.envs(options.env.iter().cloned());
+        self.configure_memory(&mut cmd, options);
+        let flags = match self {
+            Runtime::Deno => Self::deno_v8_flags(options),
+            _ => Vec::new(),
+        };
+        if flags.is_empty() {
+            cmd.args(args);
+        } else {
+            cmd.args(Self::deno_args(args, &flags));
         }
+        if let Some(cwd) = &options.cwd {
+            cmd.current_dir(cwd);
+        }
+        cmd
+    }
 
-        let mut cmd = Command::new(self.name());
-        self.configure_memory(&mut cmd, memory);
-        cmd.args(args);
+    pub fn execute(&self, args: &[String], options: &ExecOptions) -> Result<Child> {
+        if let Some(mem) = &options.memory {
+            let name = match self {
+                Runtime::Node => "Node.js",
+                Runtime::Deno => "Deno",
+                Runtime::Bun => "Bun",
+            };
+            outln!("Setting memory limit to {} MB for {}", mem, name);
+            if *self == Runtime::Node
+                && options.semi_space
+                && let Ok(mb) = mem.parse::<u64>()
+            {
+                outln!("Setting semi-space size to {} MB", Self::semi_space_mb(mb));
+            }
+        }
+        let mut cmd = self.build_command(args, options);
+        let (line, env) = Self::describe_command(&cmd);
+        verbose!("command: {} (env: {})", line, env.join(" "));
         cmd.stderr(std::process::Stdio::piped());
-        cmd.stdout(std::process::Stdio::inherit());
+        cmd.stdout(if options.capture_stdout {
+            std::process::Stdio::piped()
+        } else {
+            std::process::Stdio::inherit()
+        });
+        signals::prepare(&mut cmd, options.on_interrupt);
 
-        let child = cmd.spawn()?;
-        Ok(child)
+        cmd.spawn().map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound if options.program.is_none() => self.not_found_error(),
+            _ => e.into(),
+        })
     }
 
-    pub fn check_oom_from_output(&self, stderr: &str) -> bool {
-        self.is_oom_error(stderr)
+    fn not_found_error(&self) -> anyhow::Error {
+        FrcError::RuntimeNotFound {
+            runtime: self.name().to_string(),
+            hint: self.install_hint().to_string(),
+        }
+        .into()
     }
 
-    fn configure_memory(&self, cmd: &mut Command, memory: Option<&str>) {
-        let Some(mem) = memory else { return };
+    fn install_hint(&self) -> &str {
+        match self {
+            Runtime::Node => "https://nodejs.org (or a version manager like nvm, fnm, volta)",
+            Runtime::Deno => "https://deno.com (curl -fsSL https://deno.land/install.sh | sh)",
+            Runtime::Bun => "https://bun.sh (curl -fsSL https://bun.sh/install | bash)",
+        }
+    }
+
+    /// Shell-style command line and the environment variables set on top of
+    /// frc's own environment, one `KEY=VALUE` per entry
+    pub fn describe_command(cmd: &Command) -> (String, Vec<String>) {
+        let line = std::iter::once(cmd.get_program())
+            .chain(cmd.get_args())
+            .map(|part| Self::shell_quote(&part.to_string_lossy()))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let env = cmd
+            .get_envs()
+            .filter_map(|(key, value)| {
+                let value = Self::shell_quote(&value?.to_string_lossy());
+                Some(format!("{}={}", key.to_string_lossy(), value))
+            })
+            .collect();
+
+        (line, env)
+    }
+
+    fn shell_quote(value: &str) -> String {
+        let safe = |c: char| c.is_ascii_alphanumeric() || "-_=./:,@%+".contains(c);
+        if !value.is_empty() && value.chars().all(safe) {
+            value.to_string()
+        } else {
+            format!("'{}'", value.replace('\'', "'\\''"))
+        }
+    }
+
+    /// Whether `args` run an inline snippet (`node -e`, `bun --eval`,
+    /// `deno eval`) rather than a script. Only runtime options ahead of
+    /// the first positional argument are considered.
+    pub fn is_eval(&self, args: &[String]) -> bool {
+        if *self == Runtime::Deno {
+            return args.first().is_some_and(|arg| arg == "eval");
+        }
+
+        args.iter()
+            .take_while(|arg| arg.starts_with('-'))
+            .any(|arg| arg == "-e" || arg == "--eval" || arg.starts_with("--eval="))
+    }
+
+    /// Whether the run ran out of memory: the runtime said so on stderr,
+    /// or the process was killed the way the OS OOM killer does it
+    pub fn check_oom_from_output(&self, stderr: &str, status: ExitStatus) -> bool {
+        self.is_oom_error(stderr) || Self::killed_by_oom(status)
+    }
+
+    /// SIGKILL, or exit code 137 from a shell or wrapper reporting one
+    fn killed_by_oom(status: ExitStatus) -> bool {
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::ExitStatusExt;
+            if status.signal() == Some(libc::SIGKILL) {
+                return true;
+            }
+        }
+        status.code() == Some(OOM_KILLED_EXIT_CODE)
+    }
+
+    fn configure_memory(&self, cmd: &mut Command, options: &ExecOptions) {
+        let Some(mem) = options.memory.as_deref() else {
+            return;
+        };
+        cmd.env(RESOLVED_MEMORY_ENV, mem);
 
         match self {
             Runtime::Node => {
-                println!("Setting memory limit to {} MB for Node.js", mem);
-                let flag = format!("--max-old-space-size={}", mem);
-                let current = std::env::var("NODE_OPTIONS").unwrap_or_default();
-                let value = if current.is_empty() {
-                    flag
-                } else {
-                    format!("{} {}", current, flag)
+                let mut flags = vec![format!("--max-old-space-size={}", mem)];
+                if options.semi_space
+                    && let Ok(mb) = mem.parse::<u64>()
+                {
+                    flags.push(format!("--max-semi-space-size={}", Self::semi_space_mb(mb)));
+                }
+                let mut env = options.env.iter().rev();
+                let current = match env.find(|(key, _)| key == "NODE_OPTIONS") {
+                    Some((_, value)) => value.clone(),
+                    None => std::env::var("NODE_OPTIONS").unwrap_or_default(),
                 };
-                cmd.env("NODE_OPTIONS", value);
+                for theirs in Self::conflicting_node_options(&current, &flags) {
+                    if options.override_env {
+                        outln!("   Replacing {} from NODE_OPTIONS", theirs);
+                    } else {
+                        outln!(
+                            "⚠️  NODE_OPTIONS already sets {}; keeping it (--override-env to replace)",
+                            theirs
+                        );
+                    }
+                }
+                let merged = Self::merge_node_options(&current, &flags, options.override_env);
+                cmd.env("NODE_OPTIONS", merged);
             }
-            Runtime::Deno => {
-                println!("Setting memory limit to {} MB for Deno", mem);
-                let flag = format!("--max-old-space-size={}", mem);
-                cmd.arg("--v8-flags").arg(&flag);
+            // The flag itself goes into the arguments, see `deno_args`
+            Runtime::Deno => {}
+            Runtime::Bun => {
+                let Ok(mb) = mem.parse::<u64>() else { return };
+                cmd.env(BUN_RAM_SIZE_ENV, Self::bun_ram_size_bytes(mb).to_string());
+                if Self::bun_use_smol(mb) {
+                    outln!("   Using --smol (below {} MB)", BUN_SMOL_THRESHOLD_MB);
+                    cmd.arg("--smol");
+                }
             }
-            Runtime::Bun => {}
         }
     }
 
-    fn is_oom_error(&self, stderr: &str) -> bool {
-        let patterns = [
-            "JavaScript heap out of memory",
-            "FATAL ERROR: Reached heap limit",
-            "Allocation failed",
-            "heap out of memory",
-        ];
+    /// Environment variables that apply `memory_mb` when the runtime is
+    /// started without frc. Deno takes its V8 flags from DENO_V8_FLAGS,
+    /// which can also be spliced into `--v8-flags=$DENO_V8_FLAGS`.
+    pub fn export_vars(&self, memory_mb: u64) -> Vec<(&'static str, String)> {
+        match self {
+            Runtime::Node => vec![(
+                "NODE_OPTIONS",
+                format!("--max-old-space-size={}", memory_mb),
+            )],
+            Runtime::Deno => vec![(
+                "DENO_V8_FLAGS",
+                format!("--max-old-space-size={}", memory_mb),
+            )],
+            Runtime::Bun => vec![(
+                BUN_RAM_SIZE_ENV,
+                Self::bun_ram_size_bytes(memory_mb).to_string(),
+            )],
+        }
+    }
 
-        let stderr_lower = stderr.to_lowercase();
-        patterns
+    /// One shell line that sets `name` to `value` for child processes
+    pub fn format_export(name: &str, value: &str, shell: ExportShell) -> String {
+        match shell {
+            ExportShell::Posix => format!("export {}=\"{}\"", name, value),
+            ExportShell::Fish => format!("set -x {} \"{}\"", name, value),
+        }
+    }
+
+    /// The heap size from `options.memory` followed by `options.v8_flags`;
+    /// an extra flag that sets the heap size itself replaces ours
+    fn deno_v8_flags(options: &ExecOptions) -> Vec<String> {
+        let heap = options
+            .memory
+            .as_deref()
+            .map(|mem| format!("--max-old-space-size={}", mem))
+            .filter(|heap| !Self::sets_same_option(heap, &options.v8_flags));
+        heap.into_iter()
+            .chain(options.v8_flags.iter().cloned())
+            .collect()
+    }
+
+    /// Deno arguments carrying V8 `flags` in a single `--v8-flags`. A
+    /// `--v8-flags=` the user already passes gets them appended to its
+    /// list, since V8 may not apply a second, separate `--v8-flags`; an
+    /// option already in that list is left as is. Arguments after `--`
+    /// belong to the script.
+    fn deno_args(args: &[String], flags: &[String]) -> Vec<String> {
+        let mut merged = args.to_vec();
+        let existing = args
             .iter()
-            .any(|p| stderr_lower.contains(&p.to_lowercase()))
+            .take_while(|arg| *arg != "--")
+            .position(|arg| arg.starts_with("--v8-flags="));
+
+        let Some(index) = existing else {
+            merged.splice(0..0, ["--v8-flags".to_string(), flags.join(",")]);
+            return merged;
+        };
+
+        let list = &args[index]["--v8-flags=".len()..];
+        let theirs: Vec<_> = list.split(',').filter(|item| !item.is_empty()).collect();
+        let added: Vec<_> = flags
+            .iter()
+            .map(String::as_str)
+            .filter(|flag| !Self::sets_same_option(flag, &theirs))
+            .collect();
+        if !added.is_empty() {
+            let items: Vec<_> = theirs.iter().chain(&added).copied().collect();
+            merged[index] = format!("--v8-flags={}", items.join(","));
+        }
+        merged
     }
 
-    pub fn recommend_memory(&self, system_gb: u64) -> String {
-        if !self.supports_memory_config() {
-            return "Bun manages memory automatically (GC at ~80% system memory)".to_string();
+    /// Young-generation size derived from the old-space limit: 1/32 of it,
+    /// clamped to 16-128 MB
+    fn semi_space_mb(old_space_mb: u64) -> u64 {
+        (old_space_mb / 32).clamp(16, 128)
+    }
+
+    /// Append `flags` to an inherited NODE_OPTIONS, keeping unrelated
+    /// entries. An option set on both sides keeps the inherited value,
+    /// unless `override_env` replaces it with ours.
+    fn merge_node_options(current: &str, flags: &[String], override_env: bool) -> String {
+        let inherited: Vec<_> = current.split_whitespace().collect();
+        let kept = inherited
+            .iter()
+            .copied()
+            .filter(|option| !override_env || !Self::sets_same_option(option, flags));
+        let added = flags
+            .iter()
+            .map(String::as_str)
+            .filter(|flag| override_env || !Self::sets_same_option(flag, &inherited));
+        kept.chain(added).collect::<Vec<_>>().join(" ")
+    }
+
+    /// Entries of an inherited NODE_OPTIONS that set one of `flags`
+    fn conflicting_node_options<'a>(current: &'a str, flags: &[String]) -> Vec<&'a str> {
+        current
+            .split_whitespace()
+            .filter(|option| Self::sets_same_option(option, flags))
+            .collect()
+    }
+
+    /// Whether `option` sets the same V8 flag as one of `others`; V8
+    /// accepts `_` and `-` interchangeably
+    fn sets_same_option<S: AsRef<str>>(option: &str, others: &[S]) -> bool {
+        let name = |option: &str| {
+            let name = option.split('=').next().unwrap_or_default();
+            name.replace('_', "-")
+        };
+        let own = name(option);
+        others.iter().any(|other| name(other.as_ref()) == own)
+    }
+
+    /// JavaScriptCore sizes its heap from the RAM size it believes it has
+    fn bun_ram_size_bytes(memory_mb: u64) -> u64 {
+        memory_mb * 1024 * 1024
+    }
+
+    /// Small budgets also get Bun's `--smol` mode, which trades speed for footprint
+    fn bun_use_smol(memory_mb: u64) -> bool {
+        memory_mb < BUN_SMOL_THRESHOLD_MB
+    }
+
+    /// Lowercase, single-spaced fragments of this runtime's OOM messages
+    fn oom_patterns(&self) -> &'static [&'static str] {
+        match self {
+            Runtime::Node => NODE_OOM_PATTERNS,
+            Runtime::Deno => DENO_OOM_PATTERNS,
+            Runtime::Bun => BUN_OOM_PATTERNS,
         }
+    }
 
+    /// Whether stderr contains one of the runtime's OOM messages
+    pub fn is_oom_error(&self, stderr: &str) -> bool {
+        let normalized = Self::normalize_output(stderr);
+        COMMON_OOM_PATTERNS
+            .iter()
+            .chain(self.oom_patterns())
+            .any(|pattern| normalized.contains(pattern))
+    }
+
+    /// Lowercase `text` with every run of whitespace collapsed to one
+    /// space, so wrapped or indented messages still match
+    fn normalize_output(text: &str) -> String {
+        let mut normalized = String::with_capacity(text.len());
+        for word in text.split_whitespace() {
+            if !normalized.is_empty() {
+                normalized.push(' ');
+            }
+            normalized.extend(word.chars().flat_map(char::to_lowercase));
+        }
+        normalized
+    }
+
+    pub fn recommend_memory(&self, system_gb: u64) -> String {
+        let (low, high) = self.recommend_range(system_gb);
         let recommendation = match system_gb {
-            gb if gb >= 64 => "For 64GB+: 16384-24576 MB for large projects",
-            gb if gb >= 32 => "For 32GB: 8192-12288 MB for large projects",
-            gb if gb >= 16 => "For 16GB: 4096-6144 MB for large projects",
-            _ => "For <16GB: 2048-4096 MB",
+            gb if gb >= 64 => format!("For 64GB+: {}-{} MB for large projects", low, high),
+            gb if gb >= 32 => format!("For 32GB: {}-{} MB for large projects", low, high),
+            gb if gb >= 16 => format!("For 16GB: {}-{} MB for large projects", low, high),
+            _ => format!("For <16GB: {}-{} MB", low, high),
         };
 
-        format!(
+        let mut text = format!(
             "{}\nRule: Allocate 20-40% of system memory for development",
             recommendation
-        )
+        );
+        if *self == Runtime::Bun {
+            text.push_str(&format!(
+                "\nBun: -m sets {}; the GC still runs automatically, and values below {} MB add --smol",
+                BUN_RAM_SIZE_ENV, BUN_SMOL_THRESHOLD_MB
+            ));
+        }
+        text
+    }
+
+    /// Recommended heap range in MB for large projects on this system
+    pub fn recommend_range(&self, system_gb: u64) -> (u64, u64) {
+        match system_gb {
+            gb if gb >= 64 => (16384, 24576),
+            gb if gb >= 32 => (8192, 12288),
+            gb if gb >= 16 => (4096, 6144),
+            _ => (2048, 4096),
+        }
     }
 
-    pub fn validate_memory(&self, memory_mb: u64, system_gb: u64) -> Result<String> {
-        if !self.supports_memory_config() {
-            return Ok(String::new());
+    /// Smallest memory limit `validate_memory` accepts
+    pub fn min_memory_mb(&self) -> u64 {
+        match self {
+            Runtime::Node => NODE_MIN_MEMORY_MB,
+            Runtime::Deno => DENO_MIN_MEMORY_MB,
+            Runtime::Bun => BUN_MIN_MEMORY_MB,
         }
+    }
 
+    pub fn validate_memory(&self, memory_mb: u64, system_gb: u64) -> Result<String, FrcError> {
         let system_mb = system_gb * 1024;
         let percentage = (memory_mb as f64 / system_mb as f64) * 100.0;
 
         if memory_mb > system_mb {
-            return Err(anyhow!(
-                "Memory limit ({} MB) exceeds system memory ({} GB)",
+            return Err(FrcError::MemoryExceedsSystem {
                 memory_mb,
-                system_gb
+                system_gb,
+            });
+        }
+
+        let floor = self.min_memory_mb();
+        if memory_mb < floor {
+            return Err(FrcError::MemoryBelowMinimum {
+                memory_mb,
+                runtime: self.name().to_string(),
+                minimum_mb: floor,
+            });
+        }
+
+        if *self == Runtime::Node && memory_mb < NODE_PRACTICAL_MIN_MB {
+            return Ok(format!(
+                "⚠️  Warning: {} MB is below Node's practical minimum ({} MB); \
+                 total RSS will still exceed it because code space, new space and \
+                 external memory are not part of --max-old-space-size",
+                memory_mb, NODE_PRACTICAL_MIN_MB
             ));
         }
 
@@ -168,7 +795,207 @@ mod tests {
         assert_eq!(Runtime::from_command("deno").unwrap(), Runtime::Deno);
         assert_eq!(Runtime::from_command("DENO").unwrap(), Runtime::Deno);
         assert_eq!(Runtime::from_command("bun").unwrap(), Runtime::Bun);
-        assert!(Runtime::from_command("unknown").is_err());
+        assert!(matches!(
+            Runtime::from_command("unknown"),
+            Err(FrcError::UnknownRuntime(cmd)) if cmd == "unknown"
+        ));
+    }
+
+    #[test]
+    fn test_runtime_from_tool_alias() {
+        let tools = [
+            "tsx", "ts-node", "vite", "webpack", "rollup", "esbuild", "next",
+        ];
+        for tool in tools {
+            let runtime = Runtime::from_command(tool).unwrap();
+            assert_eq!(runtime, Runtime::Node, "{}", tool);
+        }
+        assert_eq!(Runtime::from_command("bunx").unwrap(), Runtime::Bun);
+        let bunx = Runtime::from_command("/usr/local/bin/bunx").unwrap();
+        assert_eq!(bunx, Runtime::Bun);
+        assert!(Runtime::from_command("vitest-ish").is_err());
+    }
+
+    #[test]
+    fn test_runtime_from_command_path() {
+        let node = Runtime::from_command("/Users/me/.volta/bin/node").unwrap();
+        assert_eq!(node, Runtime::Node);
+        let bun = Runtime::from_command("/usr/local/bin/bun").unwrap();
+        assert_eq!(bun, Runtime::Bun);
+        let pnpm = Runtime::from_command("/home/me/.nvm/versions/node/v20/bin/pnpm").unwrap();
+        assert_eq!(pnpm, Runtime::Node);
+        assert!(Runtime::from_command("/usr/local/bin/unknown").is_err());
+    }
+
+    #[test]
+    fn test_runtime_from_command_exe() {
+        assert_eq!(Runtime::from_command("node.exe").unwrap(), Runtime::Node);
+        assert_eq!(Runtime::from_command("DENO.EXE").unwrap(), Runtime::Deno);
+        assert_eq!(Runtime::from_command("npm.cmd").unwrap(), Runtime::Node);
+        assert_eq!(Runtime::command_name("node.exe"), "node");
+        assert_eq!(Runtime::command_name("/usr/bin/node"), "node");
+        assert_eq!(Runtime::command_name("vite"), "vite");
+    }
+
+    #[test]
+    fn test_runtime_from_explicit() {
+        assert_eq!(Runtime::from_explicit("auto").unwrap(), None);
+        assert_eq!(Runtime::from_explicit("AUTO").unwrap(), None);
+        assert_eq!(Runtime::from_explicit("deno").unwrap(), Some(Runtime::Deno));
+        assert!(Runtime::from_explicit("unknown").is_err());
+    }
+
+    #[test]
+    fn test_export_node() {
+        let lines: Vec<_> = Runtime::Node
+            .export_vars(4096)
+            .iter()
+            .map(|(name, value)| Runtime::format_export(name, value, ExportShell::Posix))
+            .collect();
+        assert_eq!(lines, ["export NODE_OPTIONS=\"--max-old-space-size=4096\""]);
+
+        let (name, value) = &Runtime::Node.export_vars(2048)[0];
+        assert_eq!(
+            Runtime::format_export(name, value, ExportShell::Fish),
+            "set -x NODE_OPTIONS \"--max-old-space-size=2048\""
+        );
+    }
+
+    #[test]
+    fn test_export_deno() {
+        let (name, value) = &Runtime::Deno.export_vars(4096)[0];
+        assert_eq!(
+            Runtime::format_export(name, value, ExportShell::Posix),
+            "export DENO_V8_FLAGS=\"--max-old-space-size=4096\""
+        );
+        assert_eq!(
+            Runtime::format_export(name, value, ExportShell::Fish),
+            "set -x DENO_V8_FLAGS \"--max-old-space-size=4096\""
+        );
+    }
+
+    #[test]
+    fn test_version_parse() {
+        assert_eq!(Version::parse("v20.11.1\n"), Some(Version(20, 11, 1)));
+        assert_eq!(
+            Version::parse("deno 1.40.0 (release, x86_64-unknown-linux-gnu)\nv8 12.1.285.6\n"),
+            Some(Version(1, 40, 0))
+        );
+        assert_eq!(Version::parse("1.0.25\n"), Some(Version(1, 0, 25)));
+        assert_eq!(Version::parse("v22.0.0-nightly"), Some(Version(22, 0, 0)));
+        assert_eq!(Version::parse("bun 1.1.3+2615dc7"), Some(Version(1, 1, 3)));
+        assert_eq!(Version::parse("command not found"), None);
+        assert_eq!(Version::parse(""), None);
+    }
+
+    #[test]
+    fn test_version_warning() {
+        assert!(Runtime::Node.version_warning(Version(10, 24, 1)).is_some());
+        assert!(Runtime::Node.version_warning(Version(12, 0, 0)).is_none());
+        assert!(Runtime::Node.version_warning(Version(20, 11, 1)).is_none());
+        assert!(Runtime::Deno.version_warning(Version(0, 42, 0)).is_some());
+        assert!(Runtime::Bun.version_warning(Version(1, 1, 3)).is_none());
+        assert!(Version(1, 10, 0) > Version(1, 9, 9));
+    }
+
+    #[test]
+    fn test_conflicting_runtime() {
+        assert_eq!(
+            Runtime::Deno.conflicting_runtime("npm"),
+            Some(Runtime::Node)
+        );
+        assert_eq!(
+            Runtime::Node.conflicting_runtime("bunx"),
+            Some(Runtime::Bun)
+        );
+        assert_eq!(
+            Runtime::Bun.conflicting_runtime("/usr/local/bin/deno"),
+            Some(Runtime::Deno)
+        );
+
+        assert_eq!(Runtime::Node.conflicting_runtime("npm"), None);
+        assert_eq!(Runtime::Deno.conflicting_runtime("deno"), None);
+        // Commands frc doesn't know can run under anything
+        assert_eq!(Runtime::Node.conflicting_runtime("index.js"), None);
+        assert_eq!(Runtime::Bun.conflicting_runtime("my-tool"), None);
+    }
+
+    #[test]
+    fn test_aliases() {
+        for runtime in Runtime::ALL {
+            assert_eq!(Runtime::from_command(runtime.name()).unwrap(), *runtime);
+            for alias in runtime.aliases() {
+                assert_eq!(Runtime::from_command(alias).unwrap(), *runtime);
+            }
+        }
+        assert!(Runtime::Node.aliases().contains(&"npm"));
+        assert!(!Runtime::Node.aliases().contains(&"node"));
+        assert_eq!(Runtime::Deno.aliases(), Vec::<&str>::new());
+        assert_eq!(Runtime::Bun.aliases(), ["bunx"]);
+    }
+
+    #[test]
+    fn test_parse_shebang() {
+        let parse = |line: &str| Runtime::parse_shebang(line);
+
+        assert_eq!(
+            parse("#!/usr/bin/env node\n"),
+            Some((Runtime::Node, vec![]))
+        );
+        assert_eq!(parse("#!/usr/local/bin/bun"), Some((Runtime::Bun, vec![])));
+        assert_eq!(
+            parse("#!/usr/bin/env -S deno run -A\n"),
+            Some((Runtime::Deno, vec!["run".to_string(), "-A".to_string()]))
+        );
+        assert_eq!(
+            parse("#!/usr/bin/deno run"),
+            Some((Runtime::Deno, vec!["run".to_string()]))
+        );
+        assert_eq!(parse("#!/bin/sh"), None);
+        assert_eq!(parse("#!/usr/bin/env python3"), None);
+        assert_eq!(parse("console.log(1)"), None);
+    }
+
+    #[test]
+    fn test_from_command_shebang_script() {
+        let dir = std::env::temp_dir().join(format!("frc-shebang-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let script = |name: &str, content: &str| {
+            let path = dir.join(name);
+            std::fs::write(&path, content).unwrap();
+            path.to_string_lossy().to_string()
+        };
+
+        let node = script("build", "#!/usr/bin/env node\nconsole.log(1)\n");
+        assert_eq!(Runtime::from_command(&node).unwrap(), Runtime::Node);
+        assert!(Runtime::shebang_args(&node).is_empty());
+
+        let deno = script("serve", "#!/usr/bin/env -S deno run --allow-net\n");
+        assert_eq!(Runtime::from_command(&deno).unwrap(), Runtime::Deno);
+        assert_eq!(Runtime::shebang_args(&deno), ["run", "--allow-net"]);
+
+        let plain = script("notes.txt", "no shebang here\n");
+        assert!(Runtime::from_command(&plain).is_err());
+        assert!(Runtime::from_command(&dir.join("missing").to_string_lossy()).is_err());
+        assert!(Runtime::shebang_args("node").is_empty());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_is_eval() {
+        let args = |list: &[&str]| list.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+
+        assert!(Runtime::Node.is_eval(&args(&["-e", "1 + 1"])));
+        assert!(Runtime::Node.is_eval(&args(&["--trace-gc", "--eval", "1"])));
+        assert!(Runtime::Node.is_eval(&args(&["--eval=1"])));
+        assert!(Runtime::Bun.is_eval(&args(&["-e", "1"])));
+        assert!(Runtime::Deno.is_eval(&args(&["eval", "1"])));
+
+        // Flags after the script belong to the script
+        assert!(!Runtime::Node.is_eval(&args(&["index.js", "-e"])));
+        assert!(!Runtime::Node.is_eval(&args(&["npm", "run", "build"])));
+        assert!(!Runtime::Deno.is_eval(&args(&["run", "main.ts"])));
     }
 
     #[test]
@@ -189,7 +1016,7 @@ mod tests {
     fn test_supports_memory_config() {
         assert!(Runtime::Node.supports_memory_config());
         assert!(Runtime::Deno.supports_memory_config());
-        assert!(!Runtime::Bun.supports_memory_config());
+        assert!(Runtime::Bun.supports_memory_config());
     }
 
     #[test]
@@ -201,7 +1028,13 @@ mod tests {
         assert!(runtime.validate_memory(8192, 32).is_ok());
 
         // Exceeds system memory
-        assert!(runtime.validate_memory(20480, 16).is_err());
+        assert!(matches!(
+            runtime.validate_memory(20480, 16),
+            Err(FrcError::MemoryExceedsSystem {
+                memory_mb: 20480,
+                system_gb: 16
+            })
+        ));
 
         // Warning: too high percentage
         let result = runtime.validate_memory(14336, 16).unwrap();
@@ -211,8 +1044,39 @@ mod tests {
         let result = runtime.validate_memory(512, 16).unwrap();
         assert!(result.contains("Info"));
 
-        // Bun doesn't validate
+        // Warning: below Node's practical minimum
+        let result = runtime.validate_memory(300, 16).unwrap();
+        assert!(result.contains("practical minimum"));
+        assert!(result.contains("RSS"));
+        let result = Runtime::Deno.validate_memory(600, 16).unwrap();
+        assert!(!result.contains("practical minimum"));
+
+        // Bun is validated like the V8 runtimes
         assert!(Runtime::Bun.validate_memory(4096, 16).unwrap().is_empty());
+        assert!(Runtime::Bun.validate_memory(20480, 16).is_err());
+    }
+
+    #[test]
+    fn test_validate_memory_floor() {
+        for runtime in [Runtime::Node, Runtime::Deno, Runtime::Bun] {
+            let floor = runtime.min_memory_mb();
+            let err = runtime.validate_memory(floor - 1, 16).unwrap_err();
+            assert!(err.to_string().contains("below the minimum"));
+            assert!(matches!(
+                err,
+                FrcError::MemoryBelowMinimum { minimum_mb, .. } if minimum_mb == floor
+            ));
+            assert!(runtime.validate_memory(floor, 16).is_ok());
+            assert!(runtime.validate_memory(floor + 1, 16).is_ok());
+        }
+
+        assert_eq!(Runtime::Node.min_memory_mb(), 256);
+        assert_eq!(Runtime::Deno.min_memory_mb(), 512);
+        assert!(Runtime::Node.validate_memory(1, 16).is_err());
+
+        // Low but valid values keep their warning
+        let result = Runtime::Node.validate_memory(256, 16).unwrap();
+        assert!(result.contains("practical minimum"));
     }
 
     #[test]
@@ -223,7 +1087,278 @@ mod tests {
         assert!(node.recommend_memory(64).contains("For 64GB+"));
 
         let bun = Runtime::Bun;
+        assert!(bun.recommend_memory(16).contains("For 16GB"));
         assert!(bun.recommend_memory(16).contains("automatically"));
+        assert!(!node.recommend_memory(16).contains("--smol"));
+        assert!(node.recommend_memory(16).contains("4096-6144 MB"));
+    }
+
+    #[test]
+    fn test_recommend_range() {
+        let node = Runtime::Node;
+        assert_eq!(node.recommend_range(8), (2048, 4096));
+        assert_eq!(node.recommend_range(15), (2048, 4096));
+        assert_eq!(node.recommend_range(16), (4096, 6144));
+        assert_eq!(node.recommend_range(32), (8192, 12288));
+        assert_eq!(node.recommend_range(64), (16384, 24576));
+        assert_eq!(node.recommend_range(256), (16384, 24576));
+
+        // Every runtime takes a memory limit, so every runtime gets a range
+        assert_eq!(Runtime::Deno.recommend_range(32), (8192, 12288));
+        assert_eq!(Runtime::Bun.recommend_range(16), (4096, 6144));
+    }
+
+    fn memory_options(memory: &str) -> ExecOptions {
+        ExecOptions {
+            memory: Some(memory.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_describe_command_node() {
+        let args = ["index.js", "--port", "3000"].map(String::from);
+        let cmd = Runtime::Node.build_command(&args, &memory_options("4096"));
+        let (line, env) = Runtime::describe_command(&cmd);
+
+        assert_eq!(line, "node index.js --port 3000");
+        assert!(
+            env.iter()
+                .any(|e| e.starts_with("NODE_OPTIONS=") && e.contains("--max-old-space-size=4096"))
+        );
+        assert!(env.contains(&"FRC_RESOLVED_MEMORY=4096".to_string()));
+    }
+
+    #[test]
+    fn test_build_command_with_program() {
+        let options = ExecOptions {
+            program: Some(PathBuf::from("/opt/node-18/bin/node")),
+            ..memory_options("4096")
+        };
+        let cmd = Runtime::Node.build_command(&["index.js".to_string()], &options);
+        let (line, env) = Runtime::describe_command(&cmd);
+
+        assert_eq!(cmd.get_program(), "/opt/node-18/bin/node");
+        assert_eq!(line, "/opt/node-18/bin/node index.js");
+        assert!(
+            env.iter()
+                .any(|e| e.starts_with("NODE_OPTIONS=") && e.contains("--max-old-space-size=4096"))
+        );
+    }
+
+    #[test]
+    fn test_build_command_with_env() {
+        let options = ExecOptions {
+            env: vec![
+                ("NODE_ENV".to_string(), "production".to_string()),
+                ("API_URL".to_string(), "http://localhost".to_string()),
+                ("NODE_OPTIONS".to_string(), "--no-warnings".to_string()),
+            ],
+            ..memory_options("4096")
+        };
+        let cmd = Runtime::Node.build_command(&["index.js".to_string()], &options);
+        let env: Vec<_> = cmd
+            .get_envs()
+            .map(|(key, value)| (key.to_str().unwrap(), value.unwrap().to_str().unwrap()))
+            .collect();
+
+        assert!(env.contains(&("NODE_ENV", "production")));
+        assert!(env.contains(&("API_URL", "http://localhost")));
+        // The given NODE_OPTIONS keeps its flags next to the heap size
+        let node_options = "--no-warnings --max-old-space-size=4096";
+        assert!(env.contains(&("NODE_OPTIONS", node_options)));
+
+        // Without a memory limit it passes through untouched
+        let options = ExecOptions {
+            env: vec![("NODE_OPTIONS".to_string(), "--inspect".to_string())],
+            ..Default::default()
+        };
+        let cmd = Runtime::Node.build_command(&[], &options);
+        let (_, env) = Runtime::describe_command(&cmd);
+        assert_eq!(env, ["NODE_OPTIONS=--inspect"]);
+    }
+
+    #[test]
+    fn test_describe_command_deno() {
+        let args = ["run", "main.ts"].map(String::from);
+        let cmd = Runtime::Deno.build_command(&args, &memory_options("8192"));
+        let (line, env) = Runtime::describe_command(&cmd);
+
+        assert_eq!(
+            line,
+            "deno --v8-flags --max-old-space-size=8192 run main.ts"
+        );
+        assert_eq!(env, ["FRC_RESOLVED_MEMORY=8192"]);
+    }
+
+    #[test]
+    fn test_deno_args_without_v8_flags() {
+        let heap = ["--max-old-space-size=4096".to_string()];
+        let args = ["run", "main.ts"].map(String::from);
+        assert_eq!(
+            Runtime::deno_args(&args, &heap),
+            ["--v8-flags", "--max-old-space-size=4096", "run", "main.ts"]
+        );
+
+        // A flag after `--` is a script argument
+        let args = ["run", "main.ts", "--", "--v8-flags=--x"].map(String::from);
+        let merged = Runtime::deno_args(&args, &heap);
+        assert_eq!(&merged[..2], ["--v8-flags", "--max-old-space-size=4096"]);
+        assert_eq!(&merged[2..], args);
+    }
+
+    #[test]
+    fn test_deno_extra_v8_flags_share_one_argument() {
+        let args = ["run", "main.ts"].map(String::from);
+        let mut options = memory_options("4096");
+        options.v8_flags = vec!["--stack-size=2000".into(), "--max-heap-size=8192".into()];
+        let cmd = Runtime::Deno.build_command(&args, &options);
+        let (line, _) = Runtime::describe_command(&cmd);
+        assert_eq!(
+            line,
+            "deno --v8-flags --max-old-space-size=4096,--stack-size=2000,--max-heap-size=8192 run main.ts"
+        );
+
+        // Merged into the user's own --v8-flags too
+        let args = ["run", "--v8-flags=--expose-gc", "main.ts"].map(String::from);
+        let cmd = Runtime::Deno.build_command(&args, &options);
+        let (line, _) = Runtime::describe_command(&cmd);
+        assert_eq!(
+            line,
+            "deno run --v8-flags=--expose-gc,--max-old-space-size=4096,--stack-size=2000,--max-heap-size=8192 main.ts"
+        );
+
+        // An explicit heap size replaces the one from -m; no -m still passes flags
+        let args = ["run", "main.ts"].map(String::from);
+        options.v8_flags = vec!["--max-old-space-size=1024".into()];
+        let flags = Runtime::deno_v8_flags(&options);
+        assert_eq!(flags, ["--max-old-space-size=1024"]);
+        options.memory = None;
+        options.v8_flags = vec!["--stack-size=2000".into()];
+        let cmd = Runtime::Deno.build_command(&args, &options);
+        let (line, _) = Runtime::describe_command(&cmd);
+        assert_eq!(line, "deno --v8-flags --stack-size=2000 run main.ts");
+
+        // Other runtimes don't take them
+        let cmd = Runtime::Node.build_command(&args, &options);
+        assert_eq!(Runtime::describe_command(&cmd).0, "node run main.ts");
+    }
+
+    #[test]
+    fn test_deno_args_merges_existing_v8_flags() {
+        let flag = ["--max-old-space-size=4096".to_string()];
+
+        let args = ["run", "--v8-flags=--expose-gc", "main.ts"].map(String::from);
+        assert_eq!(
+            Runtime::deno_args(&args, &flag),
+            [
+                "run",
+                "--v8-flags=--expose-gc,--max-old-space-size=4096",
+                "main.ts"
+            ]
+        );
+
+        let args = ["run", "--v8-flags=", "main.ts"].map(String::from);
+        assert_eq!(
+            Runtime::deno_args(&args, &flag),
+            ["run", "--v8-flags=--max-old-space-size=4096", "main.ts"]
+        );
+
+        // The user's own heap size is preserved
+        let args = [
+            "run",
+            "--v8-flags=--max-old-space-size=512,--expose-gc",
+            "main.ts",
+        ]
+        .map(String::from);
+        assert_eq!(Runtime::deno_args(&args, &flag), args);
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(Runtime::shell_quote("index.js"), "index.js");
+        assert_eq!(Runtime::shell_quote("a b"), "'a b'");
+        assert_eq!(Runtime::shell_quote("it's"), "'it'\\''s'");
+        assert_eq!(Runtime::shell_quote(""), "''");
+    }
+
+    #[test]
+    fn test_semi_space_mb() {
+        assert_eq!(Runtime::semi_space_mb(256), 16);
+        assert_eq!(Runtime::semi_space_mb(1024), 32);
+        assert_eq!(Runtime::semi_space_mb(2048), 64);
+        assert_eq!(Runtime::semi_space_mb(4096), 128);
+        assert_eq!(Runtime::semi_space_mb(16384), 128);
+    }
+
+    #[test]
+    fn test_merge_node_options() {
+        let flags = [
+            "--max-old-space-size=4096".to_string(),
+            "--max-semi-space-size=128".to_string(),
+        ];
+
+        assert_eq!(
+            Runtime::merge_node_options("", &flags, false),
+            "--max-old-space-size=4096 --max-semi-space-size=128"
+        );
+        let current = "--enable-source-maps  --trace-warnings";
+        assert_eq!(
+            Runtime::merge_node_options(current, &flags[..1], false),
+            "--enable-source-maps --trace-warnings --max-old-space-size=4096"
+        );
+    }
+
+    #[test]
+    fn test_merge_node_options_defers_to_inherited() {
+        let flags = [
+            "--max-old-space-size=4096".to_string(),
+            "--max-semi-space-size=128".to_string(),
+        ];
+        let current = "--enable-source-maps --max-old-space-size=8192";
+
+        assert_eq!(
+            Runtime::merge_node_options(current, &flags, false),
+            "--enable-source-maps --max-old-space-size=8192 --max-semi-space-size=128"
+        );
+        assert_eq!(
+            Runtime::conflicting_node_options(current, &flags),
+            ["--max-old-space-size=8192"]
+        );
+        // V8 treats `_` and `-` alike
+        assert_eq!(
+            Runtime::merge_node_options("--max_old_space_size=8192", &flags[..1], false),
+            "--max_old_space_size=8192"
+        );
+    }
+
+    #[test]
+    fn test_merge_node_options_replaces_inherited() {
+        let flags = ["--max-old-space-size=4096".to_string()];
+        let current = "--max-old-space-size=8192 --enable-source-maps";
+
+        assert_eq!(
+            Runtime::merge_node_options(current, &flags, true),
+            "--enable-source-maps --max-old-space-size=4096"
+        );
+        assert_eq!(
+            Runtime::merge_node_options("--max_old_space_size=8192", &flags, true),
+            "--max-old-space-size=4096"
+        );
+    }
+
+    #[test]
+    fn test_bun_ram_size_bytes() {
+        assert_eq!(Runtime::bun_ram_size_bytes(512), 536_870_912);
+        assert_eq!(Runtime::bun_ram_size_bytes(4096), 4_294_967_296);
+    }
+
+    #[test]
+    fn test_bun_smol_threshold() {
+        assert!(Runtime::bun_use_smol(512));
+        assert!(Runtime::bun_use_smol(1023));
+        assert!(!Runtime::bun_use_smol(1024));
+        assert!(!Runtime::bun_use_smol(4096));
     }
 
     #[test]
@@ -245,4 +1380,55 @@ mod tests {
         assert!(!runtime.is_oom_error("Some other error"));
         assert!(!runtime.is_oom_error("Success"));
     }
+
+    #[test]
+    fn test_is_oom_error_modern_node_trace() {
+        let trace = "<--- Last few GCs --->\n\n\
+            [4821:0x6a1c000]    10512 ms: Mark-Compact 2046.7 (2082.3) -> 2045.9 (2083.3) MB\n\n\
+            <--- JS stacktrace --->\n\n\
+            FATAL ERROR: Ineffective mark-compacts   near heap limit\n";
+        assert!(Runtime::Node.is_oom_error(trace));
+
+        let v8 = "#\n# Fatal JavaScript out of memory: Reached heap limit\n#";
+        assert!(Runtime::Node.is_oom_error(v8));
+    }
+
+    #[test]
+    fn test_is_oom_error_deno_trace() {
+        let trace = "\n#\n# Fatal JavaScript out of memory: MarkCompactCollector: \
+            young object promotion failed\n#\n==== C stack trace ===\n";
+        assert!(Runtime::Deno.is_oom_error(trace));
+
+        let process = "#\n# Fatal process out of memory: Zone\n#";
+        assert!(Runtime::Deno.is_oom_error(process));
+        assert!(!Runtime::Deno.is_oom_error("error: Uncaught TypeError: x is not a function"));
+    }
+
+    #[test]
+    fn test_is_oom_error_is_whitespace_insensitive() {
+        let wrapped = "FATAL ERROR: Reached\n   heap\tlimit Allocation\r\nfailed - JavaScript\n heap out of memory";
+        assert!(Runtime::Node.is_oom_error(wrapped));
+        assert!(Runtime::Bun.is_oom_error("RangeError: Out   of memory"));
+        // Runtime-specific patterns don't leak into other runtimes
+        assert!(!Runtime::Node.is_oom_error("Out of memory"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_oom_from_exit_status() {
+        use std::os::unix::process::ExitStatusExt;
+        let runtime = Runtime::Node;
+
+        let killed = ExitStatus::from_raw(libc::SIGKILL);
+        assert!(runtime.check_oom_from_output("", killed));
+        let exited_137 = ExitStatus::from_raw(137 << 8);
+        assert!(runtime.check_oom_from_output("", exited_137));
+
+        let terminated = ExitStatus::from_raw(libc::SIGTERM);
+        assert!(!runtime.check_oom_from_output("", terminated));
+        let failed = ExitStatus::from_raw(1 << 8);
+        assert!(!runtime.check_oom_from_output("Some other error", failed));
+        assert!(runtime.check_oom_from_output("JavaScript heap out of memory", failed));
+        assert!(!runtime.check_oom_from_output("", ExitStatus::from_raw(0)));
+    }
 }
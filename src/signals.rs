@@ -0,0 +1,354 @@
+//! Relays termination signals from frc to the spawned runtime so it can
+//! shut down cleanly instead of being orphaned when frc is interrupted.
+
+use std::process::{Child, Command};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// How long a timed-out child gets after SIGTERM before it is killed
+const KILL_GRACE: Duration = Duration::from_secs(5);
+
+/// What the child is sent when frc gets SIGINT (`--on-interrupt`)
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum Signal {
+    #[value(name = "SIGINT", alias = "INT")]
+    Int,
+    #[value(name = "SIGTERM", alias = "TERM")]
+    Term,
+    #[value(name = "SIGHUP", alias = "HUP")]
+    Hup,
+    #[value(name = "SIGQUIT", alias = "QUIT")]
+    Quit,
+    #[value(name = "SIGUSR1", alias = "USR1")]
+    Usr1,
+    #[value(name = "SIGUSR2", alias = "USR2")]
+    Usr2,
+    #[value(name = "SIGKILL", alias = "KILL")]
+    Kill,
+}
+
+#[cfg(unix)]
+impl Signal {
+    fn number(self) -> libc::c_int {
+        match self {
+            Signal::Int => libc::SIGINT,
+            Signal::Term => libc::SIGTERM,
+            Signal::Hup => libc::SIGHUP,
+            Signal::Quit => libc::SIGQUIT,
+            Signal::Usr1 => libc::SIGUSR1,
+            Signal::Usr2 => libc::SIGUSR2,
+            Signal::Kill => libc::SIGKILL,
+        }
+    }
+}
+
+/// Restores frc's own signal handling when dropped
+pub struct Forwarding {
+    #[cfg(unix)]
+    previous: Vec<(libc::c_int, libc::sighandler_t)>,
+    #[cfg(unix)]
+    owns_terminal: bool,
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+
+    /// Signals relayed to the child's process group
+    pub const FORWARDED: [libc::c_int; 3] = [libc::SIGINT, libc::SIGTERM, libc::SIGHUP];
+
+    /// Process group of the running child, 0 when none
+    pub static CHILD_PGID: AtomicI32 = AtomicI32::new(0);
+
+    /// Sent to the child in place of SIGINT
+    pub static ON_INTERRUPT: AtomicI32 = AtomicI32::new(libc::SIGINT);
+
+    /// Whether the terminal was handed to the child and must be taken back
+    pub static HANDED_TERMINAL: AtomicBool = AtomicBool::new(false);
+
+    pub extern "C" fn relay(signal: libc::c_int) {
+        let pgid = CHILD_PGID.load(Ordering::SeqCst);
+        let signal = match signal {
+            libc::SIGINT => ON_INTERRUPT.load(Ordering::SeqCst),
+            signal => signal,
+        };
+        if pgid > 0 {
+            // kill(2) is async-signal-safe
+            unsafe {
+                libc::kill(-pgid, signal);
+            }
+        }
+    }
+
+    /// Whether frc's stdin is a terminal whose foreground group is frc's
+    pub fn in_foreground() -> bool {
+        unsafe {
+            libc::isatty(libc::STDIN_FILENO) == 1
+                && libc::tcgetpgrp(libc::STDIN_FILENO) == libc::getpgrp()
+        }
+    }
+
+    /// Make `pgid` the terminal's foreground group. SIGTTOU is ignored for
+    /// the call so it also works from a background group.
+    pub unsafe fn set_foreground(pgid: libc::pid_t) {
+        unsafe {
+            let previous = libc::signal(libc::SIGTTOU, libc::SIG_IGN);
+            libc::tcsetpgrp(libc::STDIN_FILENO, pgid);
+            libc::signal(libc::SIGTTOU, previous);
+        }
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    use windows_sys::Win32::Foundation::{BOOL, TRUE};
+
+    /// Console control handler that keeps frc alive while the child exits
+    pub unsafe extern "system" fn ignore(_ctrl_type: u32) -> BOOL {
+        TRUE
+    }
+}
+
+/// Put the child in its own process group so signals can be relayed to it
+/// (and anything it spawns). When frc owns the terminal, the child's group
+/// is made the foreground group so it keeps reading input and receives
+/// Ctrl-C directly, unless `on_interrupt` asks for another signal than
+/// SIGINT: then Ctrl-C has to reach frc to be translated, and the child
+/// can't read from the terminal. On Windows the child gets Ctrl-C itself
+/// and `on_interrupt` has no effect.
+pub fn prepare(cmd: &mut Command, on_interrupt: Option<Signal>) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        use std::sync::atomic::Ordering;
+
+        cmd.process_group(0);
+        let on_interrupt = on_interrupt.map_or(libc::SIGINT, Signal::number);
+        unix::ON_INTERRUPT.store(on_interrupt, Ordering::SeqCst);
+        let hand_over = unix::in_foreground() && on_interrupt == libc::SIGINT;
+        unix::HANDED_TERMINAL.store(hand_over, Ordering::SeqCst);
+        if hand_over {
+            // SAFETY: only async-signal-safe calls between fork and exec
+            unsafe {
+                cmd.pre_exec(|| {
+                    unix::set_foreground(libc::getpid());
+                    Ok(())
+                });
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    let _ = (cmd, on_interrupt);
+}
+
+/// Relay SIGINT (as the `on_interrupt` signal given to `prepare`), SIGTERM and SIGHUP
+/// received by frc to `child` until the returned guard is dropped. On Windows the child shares frc's console and
+/// gets CTRL_C_EVENT itself, so frc only ignores it and waits for the child.
+pub fn forward_to(child: &Child) -> Forwarding {
+    #[cfg(unix)]
+    {
+        use std::sync::atomic::Ordering;
+
+        let pgid = child.id() as libc::pid_t;
+        unix::CHILD_PGID.store(pgid, Ordering::SeqCst);
+
+        let handler = unix::relay as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        let previous = unix::FORWARDED
+            .iter()
+            .map(|&signal| (signal, unsafe { libc::signal(signal, handler) }))
+            .collect();
+
+        let owns_terminal = unix::HANDED_TERMINAL.load(Ordering::SeqCst);
+        if owns_terminal {
+            // Also done in the child before exec; whichever runs first wins
+            unsafe { unix::set_foreground(pgid) };
+        }
+
+        Forwarding {
+            previous,
+            owns_terminal,
+        }
+    }
+
+    #[cfg(windows)]
+    {
+        use windows_sys::Win32::Foundation::TRUE;
+        use windows_sys::Win32::System::Console::SetConsoleCtrlHandler;
+
+        let _ = child;
+        unsafe {
+            SetConsoleCtrlHandler(Some(windows::ignore), TRUE);
+        }
+        Forwarding {}
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    {
+        let _ = child;
+        Forwarding {}
+    }
+}
+
+impl Drop for Forwarding {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        {
+            use std::sync::atomic::Ordering;
+
+            unix::CHILD_PGID.store(0, Ordering::SeqCst);
+            for &(signal, handler) in &self.previous {
+                unsafe {
+                    libc::signal(signal, handler);
+                }
+            }
+            if self.owns_terminal {
+                unsafe { unix::set_foreground(libc::getpgrp()) };
+            }
+        }
+
+        #[cfg(windows)]
+        {
+            use windows_sys::Win32::Foundation::FALSE;
+            use windows_sys::Win32::System::Console::SetConsoleCtrlHandler;
+
+            unsafe {
+                SetConsoleCtrlHandler(Some(windows::ignore), FALSE);
+            }
+        }
+    }
+}
+
+/// Stops the child once it outlives a timeout; see `watchdog`
+pub struct Watchdog {
+    cancel: mpsc::Sender<()>,
+    fired: Arc<AtomicBool>,
+    thread: JoinHandle<()>,
+}
+
+impl Watchdog {
+    /// Stop watching. Returns whether the timeout had already fired.
+    pub fn finish(self) -> bool {
+        drop(self.cancel);
+        let _ = self.thread.join();
+        self.fired.load(Ordering::SeqCst)
+    }
+}
+
+/// Terminate `child` (its process group on Unix) if it is still running
+/// after `timeout`: SIGTERM first, SIGKILL after `KILL_GRACE`. On Windows
+/// the process is terminated right away.
+pub fn watchdog(child: &Child, timeout: Duration) -> Watchdog {
+    let pid = child.id();
+    let (cancel, cancelled) = mpsc::channel::<()>();
+    let fired = Arc::new(AtomicBool::new(false));
+
+    let thread = {
+        let fired = Arc::clone(&fired);
+        thread::spawn(move || {
+            if cancelled.recv_timeout(timeout) != Err(RecvTimeoutError::Timeout) {
+                return;
+            }
+            fired.store(true, Ordering::SeqCst);
+            terminate(pid, false);
+            if cancelled.recv_timeout(KILL_GRACE) == Err(RecvTimeoutError::Timeout) {
+                terminate(pid, true);
+            }
+        })
+    };
+
+    Watchdog {
+        cancel,
+        fired,
+        thread,
+    }
+}
+
+/// Lets another thread stop the running child early, e.g. `watch`
+/// restarting it after a file change
+#[derive(Clone, Default)]
+pub struct Stop {
+    state: Arc<Mutex<StopState>>,
+}
+
+#[derive(Default)]
+struct StopState {
+    pid: Option<u32>,
+    stopped: bool,
+}
+
+impl Stop {
+    pub fn attach(&self, child: &Child) {
+        *self.lock() = StopState {
+            pid: Some(child.id()),
+            stopped: false,
+        };
+    }
+
+    /// Forget the child once it has exited. Returns whether `trigger`
+    /// stopped it.
+    pub fn detach(&self) -> bool {
+        let mut state = self.lock();
+        state.pid = None;
+        state.stopped
+    }
+
+    /// Terminate the attached child, if any, the way `watchdog` does
+    pub fn trigger(&self) {
+        let pid = {
+            let mut state = self.lock();
+            let Some(pid) = state.pid else {
+                return;
+            };
+            state.stopped = true;
+            pid
+        };
+        terminate(pid, false);
+
+        let stop = self.clone();
+        thread::spawn(move || {
+            thread::sleep(KILL_GRACE);
+            if stop.lock().pid == Some(pid) {
+                terminate(pid, true);
+            }
+        });
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, StopState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn terminate(pid: u32, force: bool) {
+    #[cfg(unix)]
+    {
+        let signal = if force { libc::SIGKILL } else { libc::SIGTERM };
+        // The child leads its own process group (see `prepare`)
+        unsafe {
+            libc::kill(-(pid as libc::pid_t), signal);
+        }
+    }
+
+    #[cfg(windows)]
+    {
+        use windows_sys::Win32::Foundation::CloseHandle;
+        use windows_sys::Win32::System::Threading::{
+            OpenProcess, PROCESS_TERMINATE, TerminateProcess,
+        };
+
+        let _ = force;
+        unsafe {
+            let handle = OpenProcess(PROCESS_TERMINATE, 0, pid);
+            if !handle.is_null() {
+                TerminateProcess(handle, 1);
+                CloseHandle(handle);
+            }
+        }
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    let _ = (pid, force);
+}
@@ -1,58 +1,1311 @@
+use crate::error::FrcError;
+use crate::output::errln;
+use crate::project::Project;
 use anyhow::Result;
+use serde::de::{self, DeserializeSeed, Deserializer, IgnoredAny, MapAccess, Visitor};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
 use std::fs;
-use std::path::PathBuf;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::thread;
+use std::time::{Duration, Instant};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Subdirectory of the user config dir holding frc's files
+const DEFAULT_DIR_NAME: &str = "frc";
+
+/// Overrides `DEFAULT_DIR_NAME`, e.g. when frc is embedded under another name
+const DIR_NAME_ENV: &str = "FRC_DIR_NAME";
+
+/// Directory to keep the config in instead of `<config dir>/frc`, e.g. a
+/// throwaway one on CI or a separate profile
+pub const CONFIG_HOME_ENV: &str = "FRC_CONFIG_HOME";
+
+/// Config format to use when `--config-format` isn't given
+const FORMAT_ENV: &str = "FRC_CONFIG_FORMAT";
+
+/// Per-project file, meant to be committed, that overrides the user config
+pub const LOCAL_FILE_NAME: &str = ".frc.json";
+
+/// Schema version written to new config files. Version 2 keys projects by
+/// their path with symlinks resolved.
+pub const CURRENT_VERSION: u32 = 2;
+
+/// Version assumed for files without a `version` field, which predate it
+const UNVERSIONED: u32 = 1;
+
+/// Format selected with `--config-format`; otherwise taken from
+/// `FRC_CONFIG_FORMAT` or the extension of the config file that exists
+static FORMAT: OnceLock<ConfigFormat> = OnceLock::new();
+
+/// On-disk format of the config file
+#[derive(Debug, Clone, Copy, PartialEq, Default, clap::ValueEnum)]
+pub enum ConfigFormat {
+    #[default]
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl ConfigFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ConfigFormat::Json => "json",
+            ConfigFormat::Yaml => "yaml",
+            ConfigFormat::Toml => "toml",
+        }
+    }
+
+    /// Format implied by a file's extension, if it is one frc understands
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "json" => Some(ConfigFormat::Json),
+            "yaml" | "yml" => Some(ConfigFormat::Yaml),
+            "toml" => Some(ConfigFormat::Toml),
+            _ => None,
+        }
+    }
+
+    fn parse(self, content: &str) -> Result<StorageData> {
+        Ok(match self {
+            ConfigFormat::Json => serde_json::from_str(content)?,
+            ConfigFormat::Yaml => serde_yaml::from_str(content)?,
+            ConfigFormat::Toml => toml::from_str(content)?,
+        })
+    }
+
+    /// Parse into an untyped value, for migrating shapes `parse` rejects
+    fn parse_value(self, content: &str) -> Result<serde_json::Value> {
+        Ok(match self {
+            ConfigFormat::Json => serde_json::from_str(content)?,
+            ConfigFormat::Yaml => serde_yaml::from_str(content)?,
+            ConfigFormat::Toml => toml::from_str(content)?,
+        })
+    }
+
+    /// Parse a `FRC_CONFIG_FORMAT` value; `yml` is accepted for YAML
+    fn from_env_value(value: &str) -> Result<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "json" => Ok(ConfigFormat::Json),
+            "yaml" | "yml" => Ok(ConfigFormat::Yaml),
+            "toml" => Ok(ConfigFormat::Toml),
+            other => anyhow::bail!("{} must be json, yaml or toml, got '{}'", FORMAT_ENV, other),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
 pub struct ProjectSettings {
     pub runtime: String,
     pub memory: String,
     pub last_used: u64,
+    /// Path-independent identity (see `Project::get_key`), used to find
+    /// the config again after the project directory moves
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    /// Share of system memory `memory` was derived from (`-m 40%`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub percent: Option<u64>,
+    /// Runs through frc with this config
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub run_count: u64,
+    /// Most recent runs, oldest first, at most `HISTORY_LIMIT`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub history: Vec<RunRecord>,
+    /// Peak resident memory of the last run, where the platform reports it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_peak_mb: Option<u64>,
+    /// frc version that created the entry; empty for entries older than
+    /// this field
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub created_by_version: String,
+    /// Free-form reminder set with `frc note`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+fn is_zero(value: &u64) -> bool {
+    *value == 0
+}
+
+/// Entries kept in `ProjectSettings::history`
+pub const HISTORY_LIMIT: usize = 10;
+
+/// One run of a project: when it started, the memory it got and whether
+/// it exited cleanly
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RunRecord {
+    pub timestamp: u64,
+    pub memory: String,
+    #[serde(default)]
+    pub success: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize, Default)]
+/// Contents of a project's `.frc.json`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LocalSettings {
+    pub runtime: String,
+    pub memory: String,
+}
+
+/// The `frc` key in a project's package.json. Without `runtime` it
+/// applies to every runtime.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PackageSettings {
+    /// As written: `4096` and `"4GB"` are both accepted
+    #[serde(deserialize_with = "number_or_string")]
+    pub memory: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub runtime: Option<String>,
+}
+
+/// Memory used when no project config applies
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DefaultSettings {
+    pub memory: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct StorageData {
-    pub projects: HashMap<String, ProjectSettings>,
+    #[serde(default = "unversioned")]
+    pub version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub global_default: Option<DefaultSettings>,
+    /// Recommended memory per runtime name, replacing the built-in ladder
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub defaults: BTreeMap<String, String>,
+    /// Most memory in MB frc will ever give a run, whatever the system has;
+    /// `--memory-max` takes precedence
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_ceiling: Option<u64>,
+    /// Keyed by project path. A `BTreeMap` keeps the serialized key order
+    /// stable so re-saving a config produces minimal diffs.
+    pub projects: BTreeMap<String, ProjectSettings>,
+    /// Named profiles (`--profile`) by project path, then profile name.
+    /// The entry in `projects` is the project's default profile.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, BTreeMap<String, ProjectSettings>>,
+    /// Installed runtime versions by binary path, so a run doesn't have to
+    /// spawn `<runtime> --version` to check it
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub runtime_versions: BTreeMap<String, RuntimeVersion>,
+}
+
+/// The version a runtime binary reported, valid while its modification
+/// time (seconds since the epoch) is unchanged
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RuntimeVersion {
+    pub modified: u64,
+    pub version: String,
+}
+
+impl Default for StorageData {
+    fn default() -> Self {
+        StorageData {
+            version: CURRENT_VERSION,
+            global_default: None,
+            defaults: BTreeMap::new(),
+            memory_ceiling: None,
+            projects: BTreeMap::new(),
+            profiles: BTreeMap::new(),
+            runtime_versions: BTreeMap::new(),
+        }
+    }
+}
+
+fn unversioned() -> u32 {
+    UNVERSIONED
+}
+
+/// A memory value hand-written in a JSON file: a number of MB or a string
+/// such as `"4GB"`
+fn number_or_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::String(memory) => Ok(memory),
+        serde_json::Value::Number(memory) => memory
+            .as_u64()
+            .map(|memory| memory.to_string())
+            .ok_or_else(|| de::Error::custom(format!("invalid memory {}", memory))),
+        other => Err(de::Error::custom(format!(
+            "invalid memory {}: expected a number or a string",
+            other
+        ))),
+    }
+}
+
+/// A project entry as written by earlier releases: `last_used` may be
+/// missing and `memory` may be a number
+#[derive(Deserialize)]
+struct LegacySettings {
+    runtime: String,
+    memory: serde_json::Value,
+    #[serde(default)]
+    last_used: Option<u64>,
+}
+
+impl LegacySettings {
+    fn upgrade(self, now: u64) -> Option<ProjectSettings> {
+        let memory = match self.memory {
+            serde_json::Value::String(memory) => memory,
+            serde_json::Value::Number(memory) => memory.as_u64()?.to_string(),
+            _ => return None,
+        };
+        Some(ProjectSettings {
+            runtime: self.runtime,
+            memory,
+            last_used: self.last_used.unwrap_or(now),
+            ..Default::default()
+        })
+    }
 }
 
 pub struct Storage;
 
+/// How long `Storage::lock` waits for another frc process to finish
+/// writing the config
+const LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+const LOCK_RETRY_DELAY: Duration = Duration::from_millis(5);
+const LOCK_RETRY_MAX_DELAY: Duration = Duration::from_millis(200);
+
+/// Held while the config file is read and written back; see `Storage::lock`
+pub struct ConfigLock {
+    _file: fs::File,
+}
+
+/// Turn an IO failure on config file `path` into `FrcError::ConfigIo`
+fn io_error(action: &'static str, path: &Path) -> impl FnOnce(std::io::Error) -> FrcError {
+    let path = path.to_path_buf();
+    move |source| FrcError::ConfigIo {
+        action,
+        path,
+        source,
+    }
+}
+
 impl Storage {
-    fn config_path() -> Result<PathBuf> {
+    /// Use `format` for the config file instead of detecting it.
+    /// Only the first call has an effect.
+    pub fn set_format(format: ConfigFormat) {
+        let _ = FORMAT.set(format);
+    }
+
+    pub fn config_path() -> Result<PathBuf> {
+        let dir = Self::config_dir(env::var(CONFIG_HOME_ENV).ok())?;
+        Ok(Self::config_file(&dir, Self::explicit_format()?))
+    }
+
+    /// `home` (from FRC_CONFIG_HOME) if set, else frc's directory under
+    /// the user config dir
+    fn config_dir(home: Option<String>) -> Result<PathBuf> {
+        if let Some(home) = home.filter(|home| !home.trim().is_empty()) {
+            return Ok(PathBuf::from(home));
+        }
         let config_dir =
             dirs::config_dir().ok_or_else(|| anyhow::anyhow!("Cannot find config directory"))?;
-        Ok(config_dir.join("frc").join("config.json"))
+        let dir_name = Self::dir_name(env::var(DIR_NAME_ENV).ok())?;
+        Ok(config_dir.join(dir_name))
+    }
+
+    /// Format from `--config-format`, falling back to `FRC_CONFIG_FORMAT`
+    fn explicit_format() -> Result<Option<ConfigFormat>> {
+        if let Some(format) = FORMAT.get() {
+            return Ok(Some(*format));
+        }
+        env::var(FORMAT_ENV)
+            .ok()
+            .filter(|value| !value.trim().is_empty())
+            .map(|value| ConfigFormat::from_env_value(&value))
+            .transpose()
+    }
+
+    /// The config file in `dir`. Without an explicit format, JSON is used
+    /// unless only a YAML or TOML file exists.
+    fn config_file(dir: &Path, format: Option<ConfigFormat>) -> PathBuf {
+        if let Some(format) = format {
+            return dir.join(format!("config.{}", format.extension()));
+        }
+
+        let json = dir.join("config.json");
+        if json.exists() {
+            return json;
+        }
+        ["config.yaml", "config.yml", "config.toml"]
+            .iter()
+            .map(|name| dir.join(name))
+            .find(|path| path.exists())
+            .unwrap_or(json)
+    }
+
+    fn format_of(path: &Path) -> ConfigFormat {
+        ConfigFormat::from_path(path).unwrap_or_default()
+    }
+
+    fn dir_name(value: Option<String>) -> Result<String> {
+        let Some(name) = value.filter(|name| !name.trim().is_empty()) else {
+            return Ok(DEFAULT_DIR_NAME.to_string());
+        };
+
+        if name == "." || name == ".." || name.contains(['/', '\\']) {
+            anyhow::bail!("{} must be a plain directory name: {}", DIR_NAME_ENV, name);
+        }
+        Ok(name)
     }
 
     pub fn load() -> Result<StorageData> {
+        Self::load_from(&Self::config_path()?)
+    }
+
+    /// `load` for the config file at `path`
+    pub fn load_from(path: &Path) -> Result<StorageData> {
+        if !path.exists() {
+            return Ok(StorageData::default());
+        }
+
+        let content = fs::read_to_string(path).map_err(io_error("read", path))?;
+        let format = Self::format_of(path);
+
+        if let Ok(mut data) = format.parse(&content) {
+            if data.version < CURRENT_VERSION {
+                Self::upgrade(&mut data, path)?;
+            }
+            return Ok(data);
+        }
+
+        // Keep the original before rewriting or removing it
+        let backup = Self::backup_path(path);
+        fs::copy(path, &backup).map_err(io_error("back up", path))?;
+
+        if let Some(mut data) =
+            Self::migrate(format, &content, chrono::Utc::now().timestamp() as u64)
+        {
+            errln!(
+                "⚠️  Old config format detected, migrated {} project(s) (backup: {})",
+                data.projects.len(),
+                backup.display()
+            );
+            Self::upgrade(&mut data, path)?;
+            return Ok(data);
+        }
+
+        errln!(
+            "⚠️  Unreadable config, recreating (backup: {})",
+            backup.display()
+        );
+        fs::remove_file(path).map_err(io_error("remove", path))?;
+        Ok(StorageData::default())
+    }
+
+    /// Read the `.frc.json` in project `root`, if there is one. Unlike the
+    /// user config, an unreadable file is an error: it belongs to the repo.
+    pub fn load_local(root: &Path) -> Result<Option<LocalSettings>> {
+        let path = root.join(LOCAL_FILE_NAME);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let content = fs::read_to_string(&path).map_err(io_error("read", &path))?;
+        let settings = serde_json::from_str(&content).map_err(|e| FrcError::ConfigParse {
+            reason: e.to_string(),
+            path,
+        })?;
+        Ok(Some(settings))
+    }
+
+    /// Read the `frc` key of the package.json in project `root`, if both
+    /// exist. Everything else in the file is left unparsed.
+    pub fn load_package(root: &Path) -> Result<Option<PackageSettings>> {
+        let path = root.join("package.json");
+        let Ok(content) = fs::read_to_string(&path) else {
+            return Ok(None);
+        };
+
+        #[derive(Deserialize)]
+        struct Package {
+            frc: Option<PackageSettings>,
+        }
+        let package: Package =
+            serde_json::from_str(&content).map_err(|e| FrcError::ConfigParse {
+                reason: format!("\"frc\" key: {}", e),
+                path,
+            })?;
+        Ok(package.frc)
+    }
+
+    /// Write `.frc.json` in project `root`, returning its path
+    /// Whether files can be created in `dir`. Found out by creating one,
+    /// since permission bits don't show read-only mounts.
+    pub fn is_writable(dir: &Path) -> bool {
+        let probe = dir.join(format!(".frc-probe-{}", std::process::id()));
+        match fs::File::create(&probe) {
+            Ok(_) => fs::remove_file(&probe).is_ok(),
+            Err(_) => false,
+        }
+    }
+
+    pub fn save_local(root: &Path, settings: &LocalSettings) -> Result<PathBuf> {
+        let path = root.join(LOCAL_FILE_NAME);
+        let mut content = serde_json::to_string_pretty(settings)?;
+        content.push('\n');
+        Self::write_atomic(&path, &content)?;
+        Ok(path)
+    }
+
+    /// `config.json` -> `config.json.bak`
+    fn backup_path(path: &Path) -> PathBuf {
+        let mut name = path.file_name().unwrap_or_default().to_os_string();
+        name.push(".bak");
+        path.with_file_name(name)
+    }
+
+    /// Upgrade content written by earlier releases: a `projects` map, or a
+    /// flat map of projects, whose entries may lack `last_used` (set to
+    /// `now`) or store `memory` as a number. Returns `None` if any entry
+    /// can't be understood.
+    fn migrate(format: ConfigFormat, content: &str, now: u64) -> Option<StorageData> {
+        let value = format.parse_value(content).ok()?;
+        let root = value.as_object()?;
+        let projects = match root.get("projects") {
+            Some(projects) => projects.as_object()?,
+            None => root,
+        };
+
+        let mut data = StorageData::default();
+        for (path, entry) in projects {
+            let legacy: LegacySettings = serde_json::from_value(entry.clone()).ok()?;
+            data.projects.insert(path.clone(), legacy.upgrade(now)?);
+        }
+        Some(data)
+    }
+
+    /// Bring a config from an earlier schema version up to date and save
+    /// it, so this only happens once
+    fn upgrade(data: &mut StorageData, path: &Path) -> Result<()> {
+        let merged = Self::merge_aliases(data, |path| {
+            Project::canonical(Path::new(path))
+                .to_string_lossy()
+                .to_string()
+        });
+        if merged > 0 {
+            errln!(
+                "⚠️  Merged {} duplicate config(s) saved under symlinked paths",
+                merged
+            );
+        }
+        data.version = CURRENT_VERSION;
+
+        match Self::save_to(data, path) {
+            Err(e) if !Self::is_read_only(&e) => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    /// Re-key projects and profiles by `canonical(path)`. Where several
+    /// paths lead to the same project, the most recently used entry wins.
+    /// Returns how many project entries were dropped as duplicates.
+    fn merge_aliases(data: &mut StorageData, canonical: impl Fn(&str) -> String) -> usize {
+        let mut dropped = 0;
+        for (path, project) in std::mem::take(&mut data.projects) {
+            let path = canonical(&path);
+            if let Some(existing) = data.projects.get(&path) {
+                dropped += 1;
+                if existing.last_used >= project.last_used {
+                    continue;
+                }
+            }
+            data.projects.insert(path, project);
+        }
+
+        for (path, profiles) in std::mem::take(&mut data.profiles) {
+            let merged = data.profiles.entry(canonical(&path)).or_default();
+            for (name, profile) in profiles {
+                if merged
+                    .get(&name)
+                    .is_none_or(|existing| existing.last_used < profile.last_used)
+                {
+                    merged.insert(name, profile);
+                }
+            }
+        }
+        dropped
+    }
+
+    /// Load the config keeping only the project entry for `key`. Other
+    /// entries are skipped while parsing rather than deserialized, which
+    /// keeps lookups fast when thousands of projects are saved.
+    pub fn load_scoped(key: &str) -> Result<StorageData> {
         let path = Self::config_path()?;
 
         if !path.exists() {
             return Ok(StorageData::default());
         }
 
-        let content = fs::read_to_string(&path)?;
+        let content = fs::read_to_string(&path).map_err(io_error("read", &path))?;
 
-        match serde_json::from_str::<StorageData>(&content) {
+        match Self::parse_scoped(&content, key, Self::format_of(&path)) {
+            // Older files are upgraded as a whole first
+            Ok(data) if data.version < CURRENT_VERSION => {
+                let mut data = Self::load()?;
+                data.projects.retain(|path, _| path == key);
+                data.profiles.retain(|path, _| path == key);
+                Ok(data)
+            }
             Ok(data) => Ok(data),
-            Err(_) => {
-                eprintln!("⚠️  Old config format detected, recreating...");
-                fs::remove_file(&path)?;
-                Ok(StorageData::default())
+            // Let the full loader deal with unreadable files
+            Err(_) => Self::load(),
+        }
+    }
+
+    fn parse_scoped(content: &str, key: &str, format: ConfigFormat) -> Result<StorageData> {
+        let visitor = ScopedVisitor { key };
+        match format {
+            ConfigFormat::Json => {
+                let mut deserializer = serde_json::Deserializer::from_str(content);
+                let data = deserializer.deserialize_map(visitor)?;
+                deserializer.end()?;
+                Ok(data)
+            }
+            ConfigFormat::Yaml => {
+                Ok(serde_yaml::Deserializer::from_str(content).deserialize_map(visitor)?)
+            }
+            ConfigFormat::Toml => {
+                let mut data = format.parse(content)?;
+                data.projects.retain(|path, _| path == key);
+                data.profiles.retain(|path, _| path == key);
+                Ok(data)
             }
         }
     }
 
     pub fn save(data: &StorageData) -> Result<()> {
-        let path = Self::config_path()?;
+        Self::save_to(data, &Self::config_path()?)
+    }
+
+    /// `save` to the config file at `path`
+    pub fn save_to(data: &StorageData, path: &Path) -> Result<()> {
+        Self::write_atomic(path, &Self::serialize(data, Self::format_of(path))?)
+    }
+
+    /// Take the advisory lock guarding the config file at `path` against
+    /// other frc processes, waiting with backoff while one holds it. The
+    /// lock is released when the returned guard is dropped.
+    pub fn lock(path: &Path) -> Result<ConfigLock> {
+        let mut name = path.file_name().unwrap_or_default().to_os_string();
+        name.push(".lock");
+        let lock_path = path.with_file_name(name);
+        if let Some(parent) = lock_path.parent() {
+            fs::create_dir_all(parent).map_err(io_error("lock", path))?;
+        }
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&lock_path)
+            .map_err(io_error("lock", path))?;
+
+        let start = Instant::now();
+        let mut delay = LOCK_RETRY_DELAY;
+        loop {
+            match file.try_lock() {
+                Ok(()) => return Ok(ConfigLock { _file: file }),
+                Err(fs::TryLockError::WouldBlock) if start.elapsed() < LOCK_TIMEOUT => {
+                    thread::sleep(delay);
+                    delay = (delay * 2).min(LOCK_RETRY_MAX_DELAY);
+                }
+                Err(fs::TryLockError::WouldBlock) => {
+                    anyhow::bail!(
+                        "Config {} is locked by another frc process; try again",
+                        path.display()
+                    )
+                }
+                Err(fs::TryLockError::Error(e)) => return Err(io_error("lock", path)(e).into()),
+            }
+        }
+    }
 
+    /// Whether `error` came from a location frc may not write to, like a
+    /// read-only config directory in a locked-down environment
+    pub fn is_read_only(error: &anyhow::Error) -> bool {
+        error
+            .chain()
+            .filter_map(|cause| cause.downcast_ref::<std::io::Error>())
+            .any(|cause| {
+                matches!(
+                    cause.kind(),
+                    std::io::ErrorKind::PermissionDenied | std::io::ErrorKind::ReadOnlyFilesystem
+                )
+            })
+    }
+
+    /// Write `data` to `path` for `frc export-config`, in the format its
+    /// extension implies (JSON otherwise)
+    pub fn export(data: &StorageData, path: &Path) -> Result<()> {
+        let format = ConfigFormat::from_path(path).unwrap_or_default();
+        Self::write_atomic(path, &Self::serialize(data, format)?)
+    }
+
+    /// Read a file written by `export`
+    pub fn import(path: &Path) -> Result<StorageData> {
+        let content = fs::read_to_string(path).map_err(io_error("read", path))?;
+        let format = ConfigFormat::from_path(path).unwrap_or_default();
+        let data = format.parse(&content).map_err(|e| FrcError::ConfigParse {
+            path: path.to_path_buf(),
+            reason: format!("not an frc config ({})", e),
+        })?;
+        Ok(data)
+    }
+
+    /// Write `content` to a temporary file next to `path` and rename it into
+    /// place, so a crash or full disk never leaves a truncated config behind.
+    fn write_atomic(path: &Path, content: &str) -> Result<()> {
         if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent)?;
+            fs::create_dir_all(parent).map_err(io_error("write", path))?;
+        }
+
+        let mut name = path.file_name().unwrap_or_default().to_os_string();
+        name.push(format!(".tmp-{}", std::process::id()));
+        let temp = path.with_file_name(name);
+
+        let result = fs::write(&temp, content).and_then(|()| fs::rename(&temp, path));
+        if result.is_err() {
+            let _ = fs::remove_file(&temp);
+        }
+        Ok(result.map_err(io_error("write", path))?)
+    }
+
+    /// Serializes with a stable layout: struct fields keep their declaration
+    /// order, project keys are sorted, and the file ends with a newline.
+    fn serialize(data: &StorageData, format: ConfigFormat) -> Result<String> {
+        let mut content = match format {
+            ConfigFormat::Json => serde_json::to_string_pretty(data)?,
+            ConfigFormat::Yaml => serde_yaml::to_string(data)?,
+            ConfigFormat::Toml => toml::to_string_pretty(data)?,
+        };
+        if !content.ends_with('\n') {
+            content.push('\n');
+        }
+        Ok(content)
+    }
+}
+
+/// Visits the top-level object, filtering `projects` and `profiles` down
+/// to one key
+struct ScopedVisitor<'k> {
+    key: &'k str,
+}
+
+impl<'de> Visitor<'de> for ScopedVisitor<'_> {
+    type Value = StorageData;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an frc config object")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<StorageData, A::Error> {
+        let mut rest = serde_json::Map::new();
+        let mut projects = BTreeMap::new();
+        let mut profiles = BTreeMap::new();
+
+        while let Some(field) = map.next_key::<String>()? {
+            if field == "projects" {
+                projects = map.next_value_seed(ProjectSeed::new(self.key))?;
+            } else if field == "profiles" {
+                profiles = map.next_value_seed(ProjectSeed::new(self.key))?;
+            } else {
+                rest.insert(field, map.next_value()?);
+            }
+        }
+
+        // Remaining top-level fields are small; deserialize them as usual
+        rest.insert(
+            "projects".to_string(),
+            serde_json::Value::Object(Default::default()),
+        );
+        let mut data: StorageData =
+            serde_json::from_value(serde_json::Value::Object(rest)).map_err(de::Error::custom)?;
+        data.projects = projects;
+        data.profiles = profiles;
+        Ok(data)
+    }
+}
+
+/// Deserializes a map keyed by project path, keeping only the entry for
+/// `key`
+struct ProjectSeed<'k, T> {
+    key: &'k str,
+    value: PhantomData<T>,
+}
+
+impl<'k, T> ProjectSeed<'k, T> {
+    fn new(key: &'k str) -> Self {
+        Self {
+            key,
+            value: PhantomData,
+        }
+    }
+}
+
+impl<'de, T: Deserialize<'de>> DeserializeSeed<'de> for ProjectSeed<'_, T> {
+    type Value = BTreeMap<String, T>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for ProjectSeed<'_, T> {
+    type Value = BTreeMap<String, T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a map keyed by project path")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut projects = BTreeMap::new();
+
+        while let Some(path) = map.next_key::<String>()? {
+            if path == self.key {
+                projects.insert(path, map.next_value()?);
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+
+        Ok(projects)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(runtime: &str, memory: &str, last_used: u64) -> ProjectSettings {
+        ProjectSettings {
+            runtime: runtime.to_string(),
+            memory: memory.to_string(),
+            last_used,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_is_read_only() {
+        let denied = std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+        let error = anyhow::Error::from(denied).context("Failed to write config.json");
+        assert!(Storage::is_read_only(&error));
+
+        let missing = std::io::Error::from(std::io::ErrorKind::NotFound);
+        assert!(!Storage::is_read_only(&missing.into()));
+        assert!(!Storage::is_read_only(&anyhow::anyhow!("bad json")));
+    }
+
+    #[test]
+    fn test_serialize_round_trip_is_stable() {
+        let mut data = StorageData::default();
+        data.projects
+            .insert("/b".to_string(), settings("deno", "8192", 2));
+        data.projects
+            .insert("/a".to_string(), settings("node", "4096", 1));
+
+        let first = Storage::serialize(&data, ConfigFormat::Json).unwrap();
+        let reloaded: StorageData = serde_json::from_str(&first).unwrap();
+        let second = Storage::serialize(&reloaded, ConfigFormat::Json).unwrap();
+
+        assert_eq!(reloaded, data);
+        assert_eq!(first, second);
+        assert!(first.ends_with('\n'));
+    }
+
+    #[test]
+    fn test_serialize_is_independent_of_insertion_order() {
+        let mut forward = StorageData::default();
+        let mut reverse = StorageData::default();
+        let entries = [("/a", "node"), ("/b", "deno"), ("/c", "bun")];
+
+        for (path, runtime) in entries {
+            forward
+                .projects
+                .insert(path.to_string(), settings(runtime, "4096", 1));
+        }
+        for (path, runtime) in entries.iter().rev() {
+            reverse
+                .projects
+                .insert(path.to_string(), settings(runtime, "4096", 1));
+        }
+
+        let content = Storage::serialize(&forward, ConfigFormat::Json).unwrap();
+        let reversed = Storage::serialize(&reverse, ConfigFormat::Json).unwrap();
+        assert_eq!(content, reversed);
+
+        // Keys are sorted and fields keep their declaration order
+        let a = content.find("\"/a\"").unwrap();
+        let c = content.find("\"/c\"").unwrap();
+        assert!(a < c);
+        let runtime = content.find("\"runtime\"").unwrap();
+        let last_used = content.find("\"last_used\"").unwrap();
+        assert!(runtime < last_used);
+    }
+
+    #[test]
+    fn test_defaults_round_trip() {
+        let mut data = StorageData {
+            global_default: Some(DefaultSettings {
+                memory: "4096".to_string(),
+            }),
+            ..Default::default()
+        };
+        data.defaults.insert("deno".to_string(), "6144".to_string());
+        data.projects
+            .insert("/a".to_string(), settings("node", "2048", 1));
+
+        for format in [ConfigFormat::Json, ConfigFormat::Yaml, ConfigFormat::Toml] {
+            let content = Storage::serialize(&data, format).unwrap();
+            assert_eq!(format.parse(&content).unwrap(), data);
+
+            let scoped = Storage::parse_scoped(&content, "/b", format).unwrap();
+            assert_eq!(scoped.global_default, data.global_default);
+            assert_eq!(scoped.defaults, data.defaults);
+        }
+
+        // Omitted when unset, so existing files don't change
+        let empty = Storage::serialize(&StorageData::default(), ConfigFormat::Json).unwrap();
+        assert!(!empty.contains("global_default"));
+        assert!(!empty.contains("defaults"));
+    }
+
+    #[test]
+    fn test_yaml_round_trip_matches_json() {
+        let mut data = StorageData::default();
+        data.projects
+            .insert("/a".to_string(), settings("node", "4096", 1));
+        data.projects
+            .insert("/b: colon".to_string(), settings("deno", "8192", 2));
+
+        let yaml = Storage::serialize(&data, ConfigFormat::Yaml).unwrap();
+        let json = Storage::serialize(&data, ConfigFormat::Json).unwrap();
+        let from_yaml = ConfigFormat::Yaml.parse(&yaml).unwrap();
+        let from_json = ConfigFormat::Json.parse(&json).unwrap();
+
+        assert_eq!(from_yaml, data);
+        assert_eq!(from_yaml, from_json);
+        let reserialized = Storage::serialize(&from_yaml, ConfigFormat::Yaml).unwrap();
+        assert_eq!(yaml, reserialized);
+        assert!(yaml.ends_with('\n'));
+
+        let scoped = Storage::parse_scoped(&yaml, "/a", ConfigFormat::Yaml).unwrap();
+        assert_eq!(scoped.projects.len(), 1);
+        assert_eq!(scoped.projects["/a"], data.projects["/a"]);
+    }
+
+    #[test]
+    fn test_toml_round_trip_matches_json() {
+        let mut data = StorageData::default();
+        let history = vec![RunRecord {
+            timestamp: 1,
+            memory: "4096".to_string(),
+            success: true,
+        }];
+        let run = ProjectSettings {
+            run_count: 1,
+            history,
+            ..settings("node", "4096", 1)
+        };
+        data.projects.insert("/a".to_string(), run);
+        data.projects
+            .insert("C:\\work\\b".to_string(), settings("bun", "2048", 2));
+
+        let toml = Storage::serialize(&data, ConfigFormat::Toml).unwrap();
+        let json = Storage::serialize(&data, ConfigFormat::Json).unwrap();
+        let from_toml = ConfigFormat::Toml.parse(&toml).unwrap();
+
+        assert_eq!(from_toml, data);
+        assert_eq!(from_toml, ConfigFormat::Json.parse(&json).unwrap());
+        let reserialized = Storage::serialize(&from_toml, ConfigFormat::Toml).unwrap();
+        assert_eq!(toml, reserialized);
+        assert!(toml.ends_with('\n'));
+
+        let empty = Storage::serialize(&StorageData::default(), ConfigFormat::Toml).unwrap();
+        let parsed = ConfigFormat::Toml.parse(&empty).unwrap();
+        assert!(parsed.projects.is_empty());
+
+        let scoped = Storage::parse_scoped(&toml, "/a", ConfigFormat::Toml).unwrap();
+        assert_eq!(scoped.projects.len(), 1);
+        assert!(Storage::parse_scoped("not = [toml", "/a", ConfigFormat::Toml).is_err());
+    }
+
+    #[test]
+    fn test_format_from_env_value() {
+        assert_eq!(
+            ConfigFormat::from_env_value("TOML").unwrap(),
+            ConfigFormat::Toml
+        );
+        assert_eq!(
+            ConfigFormat::from_env_value("yml").unwrap(),
+            ConfigFormat::Yaml
+        );
+        assert!(ConfigFormat::from_env_value("ini").is_err());
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("config.json")),
+            Some(ConfigFormat::Json)
+        );
+        assert_eq!(
+            ConfigFormat::from_path(Path::new(".frc.yml")),
+            Some(ConfigFormat::Yaml)
+        );
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("config.toml")),
+            Some(ConfigFormat::Toml)
+        );
+        assert_eq!(ConfigFormat::from_path(Path::new("config")), None);
+    }
+
+    #[test]
+    fn test_config_file_detects_yaml() {
+        let dir = std::env::temp_dir().join(format!("frc-format-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        assert_eq!(Storage::config_file(&dir, None), dir.join("config.json"));
+        fs::write(dir.join("config.yaml"), "projects: {}\n").unwrap();
+        assert_eq!(Storage::config_file(&dir, None), dir.join("config.yaml"));
+        assert_eq!(
+            Storage::config_file(&dir, Some(ConfigFormat::Json)),
+            dir.join("config.json")
+        );
+        fs::write(dir.join("config.json"), "{}").unwrap();
+        assert_eq!(Storage::config_file(&dir, None), dir.join("config.json"));
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_merge_symlinked_duplicates() {
+        let dir = std::env::temp_dir().join(format!("frc-alias-{}", std::process::id()));
+        let real = dir.join("real");
+        let link = dir.join("link");
+        fs::create_dir_all(&real).unwrap();
+        std::os::unix::fs::symlink(&real, &link).unwrap();
+
+        let entry = |memory: &str, last_used| ProjectSettings {
+            runtime: "node".to_string(),
+            memory: memory.to_string(),
+            last_used,
+            ..Default::default()
+        };
+        let key = |path: &Path| path.to_string_lossy().to_string();
+        let mut data = StorageData::default();
+        data.projects.insert(key(&real), entry("2048", 10));
+        data.projects.insert(key(&link), entry("4096", 20));
+        data.projects.insert("/gone".to_string(), entry("1024", 5));
+        data.profiles
+            .entry(key(&link))
+            .or_default()
+            .insert("build".to_string(), entry("3072", 1));
+
+        let canonical = |path: &str| key(&Project::canonical(Path::new(path)));
+        assert_eq!(Storage::merge_aliases(&mut data, canonical), 1);
+
+        // One entry under the real path, the newest one; missing paths stay
+        let real = key(&real.canonicalize().unwrap());
+        let keys: Vec<_> = data.projects.keys().cloned().collect();
+        assert_eq!(keys, ["/gone".to_string(), real.clone()]);
+        assert_eq!(data.projects[&real].memory, "4096");
+        assert_eq!(data.profiles[&real]["build"].memory, "3072");
+        assert_eq!(data.profiles.len(), 1);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_load_upgrades_unversioned_config() {
+        let dir = std::env::temp_dir().join(format!("frc-unversioned-{}", std::process::id()));
+        let real = dir.join("real");
+        let link = dir.join("link");
+        fs::create_dir_all(&real).unwrap();
+        std::os::unix::fs::symlink(&real, &link).unwrap();
+
+        let file = dir.join("config.json");
+        let content = serde_json::json!({
+            "projects": {
+                real.to_string_lossy(): {"runtime": "node", "memory": "2048", "last_used": 10},
+                link.to_string_lossy(): {"runtime": "node", "memory": "4096", "last_used": 20},
+            }
+        });
+        fs::write(&file, content.to_string()).unwrap();
+
+        let data = Storage::load_from(&file).unwrap();
+        let real = real.canonicalize().unwrap().to_string_lossy().to_string();
+        assert_eq!(data.version, CURRENT_VERSION);
+        assert_eq!(data.projects.len(), 1);
+        assert_eq!(data.projects[&real].memory, "4096");
+
+        // The upgrade is saved, so it only happens once
+        let saved = ConfigFormat::Json
+            .parse(&fs::read_to_string(&file).unwrap())
+            .unwrap();
+        assert_eq!(saved, data);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_migrate_entries_without_last_used() {
+        let old = r#"{"projects": {"/a": {"runtime": "node", "memory": "4096"}}}"#;
+        assert!(ConfigFormat::Json.parse(old).is_err());
+
+        let data = Storage::migrate(ConfigFormat::Json, old, 42).unwrap();
+        assert_eq!(data.version, CURRENT_VERSION);
+        assert_eq!(data.projects["/a"], settings("node", "4096", 42));
+    }
+
+    #[test]
+    fn test_migrate_flat_map() {
+        let old = r#"{
+            "/a": {"runtime": "node", "memory": 4096},
+            "/b": {"runtime": "deno", "memory": "8192", "last_used": 7}
+        }"#;
+
+        let data = Storage::migrate(ConfigFormat::Json, old, 42).unwrap();
+        assert_eq!(data.projects.len(), 2);
+        assert_eq!(data.projects["/a"], settings("node", "4096", 42));
+        assert_eq!(data.projects["/b"], settings("deno", "8192", 7));
+
+        let yaml = "/a:\n  runtime: bun\n  memory: 2048\n";
+        let data = Storage::migrate(ConfigFormat::Yaml, yaml, 42).unwrap();
+        assert_eq!(data.projects["/a"], settings("bun", "2048", 42));
+    }
+
+    #[test]
+    fn test_migrate_rejects_unknown_shapes() {
+        assert!(Storage::migrate(ConfigFormat::Json, "not json", 0).is_none());
+        assert!(Storage::migrate(ConfigFormat::Json, "[1, 2]", 0).is_none());
+        let bad = r#"{"projects": {"/a": {"memory": "4096"}}}"#;
+        assert!(Storage::migrate(ConfigFormat::Json, bad, 0).is_none());
+    }
+
+    #[test]
+    fn test_unversioned_config_parses() {
+        let content =
+            r#"{"projects": {"/a": {"runtime": "node", "memory": "4096", "last_used": 1}}}"#;
+        let data = ConfigFormat::Json.parse(content).unwrap();
+        assert_eq!(data.version, UNVERSIONED);
+        assert_eq!(data.projects.len(), 1);
+        assert_eq!(data.projects["/a"].run_count, 0);
+        assert!(data.projects["/a"].history.is_empty());
+    }
+
+    #[test]
+    fn test_write_atomic() {
+        let dir = std::env::temp_dir().join(format!("frc-atomic-{}", std::process::id()));
+        let path = dir.join("nested").join("config.json");
+
+        let mut data = StorageData::default();
+        data.projects
+            .insert("/a".to_string(), settings("node", "4096", 1));
+        let content = Storage::serialize(&data, ConfigFormat::Json).unwrap();
+
+        fs::create_dir_all(&path).unwrap();
+        assert!(Storage::write_atomic(&path, &content).is_err());
+        fs::remove_dir(&path).unwrap();
+
+        Storage::write_atomic(&path, &content).unwrap();
+        Storage::write_atomic(&path, &content).unwrap();
+
+        let entries: Vec<_> = fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(entries, ["config.json"]);
+        let written = fs::read_to_string(&path).unwrap();
+        assert_eq!(ConfigFormat::Json.parse(&written).unwrap(), data);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_export_import_round_trip() {
+        let dir = std::env::temp_dir().join(format!("frc-export-{}", std::process::id()));
+        let mut data = StorageData::default();
+        data.projects
+            .insert("/a".to_string(), settings("node", "4096", 1));
+
+        for name in ["shared.json", "shared.yaml", "shared.toml"] {
+            let path = dir.join(name);
+            Storage::export(&data, &path).unwrap();
+            assert_eq!(Storage::import(&path).unwrap(), data);
+        }
+
+        let bogus = dir.join("bogus.json");
+        fs::write(&bogus, "[1, 2]").unwrap();
+        let err = Storage::import(&bogus).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<FrcError>(),
+            Some(FrcError::ConfigParse { path, .. }) if *path == bogus
+        ));
+        let err = Storage::import(&dir.join("missing.json")).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<FrcError>(),
+            Some(FrcError::ConfigIo { action: "read", .. })
+        ));
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_local_settings() {
+        let dir = std::env::temp_dir().join(format!("frc-local-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        assert_eq!(Storage::load_local(&dir).unwrap(), None);
+
+        let local = LocalSettings {
+            runtime: "node".to_string(),
+            memory: "3072".to_string(),
+        };
+        let path = Storage::save_local(&dir, &local).unwrap();
+        assert_eq!(path, dir.join(".frc.json"));
+        assert_eq!(Storage::load_local(&dir).unwrap(), Some(local));
+
+        fs::write(&path, "{\"memory\": 3072}").unwrap();
+        let err = Storage::load_local(&dir).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<FrcError>(),
+            Some(FrcError::ConfigParse { .. })
+        ));
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_package_settings() {
+        let dir = std::env::temp_dir().join(format!("frc-package-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        assert_eq!(Storage::load_package(&dir).unwrap(), None);
+
+        let path = dir.join("package.json");
+        fs::write(&path, r#"{"name": "web", "scripts": {"build": "vite"}}"#).unwrap();
+        assert_eq!(Storage::load_package(&dir).unwrap(), None);
+
+        let with_key = r#"{
+            "name": "web",
+            "dependencies": {"vite": "^5.0.0"},
+            "frc": {"memory": "4096", "runtime": "node"}
+        }"#;
+        fs::write(&path, with_key).unwrap();
+        let settings = Storage::load_package(&dir).unwrap().unwrap();
+        assert_eq!(settings.memory, "4096");
+        assert_eq!(settings.runtime.as_deref(), Some("node"));
+
+        fs::write(&path, r#"{"frc": {"memory": "2048"}}"#).unwrap();
+        let settings = Storage::load_package(&dir).unwrap().unwrap();
+        assert_eq!(settings.runtime, None);
+
+        fs::write(&path, r#"{"frc": {"memory": 4096}}"#).unwrap();
+        let settings = Storage::load_package(&dir).unwrap().unwrap();
+        assert_eq!(settings.memory, "4096");
+
+        fs::write(&path, r#"{"frc": {"memory": "4GB"}}"#).unwrap();
+        let settings = Storage::load_package(&dir).unwrap().unwrap();
+        assert_eq!(settings.memory, "4GB");
+
+        fs::write(&path, r#"{"frc": {"memory": 4.5}}"#).unwrap();
+        assert!(Storage::load_package(&dir).is_err());
+
+        fs::write(&path, r#"{"frc": {"runtime": "node"}}"#).unwrap();
+        assert!(Storage::load_package(&dir).is_err());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_backup_path() {
+        assert_eq!(
+            Storage::backup_path(Path::new("/cfg/frc/config.json")),
+            Path::new("/cfg/frc/config.json.bak")
+        );
+    }
+
+    #[test]
+    fn test_dir_name() {
+        assert_eq!(Storage::dir_name(None).unwrap(), "frc");
+        assert_eq!(Storage::dir_name(Some(String::new())).unwrap(), "frc");
+        assert_eq!(
+            Storage::dir_name(Some("acme-frc".to_string())).unwrap(),
+            "acme-frc"
+        );
+        assert!(Storage::dir_name(Some("../elsewhere".to_string())).is_err());
+        assert!(Storage::dir_name(Some("..".to_string())).is_err());
+    }
+
+    #[test]
+    fn test_config_dir_home_override() {
+        let home = Storage::config_dir(Some("/tmp/frc-profile".to_string())).unwrap();
+        assert_eq!(home, PathBuf::from("/tmp/frc-profile"));
+
+        let default = Storage::config_dir(None).unwrap();
+        assert_eq!(Storage::config_dir(Some(" ".to_string())).unwrap(), default);
+        assert!(default.starts_with(dirs::config_dir().unwrap()));
+    }
+
+    #[test]
+    fn test_parse_scoped_keeps_only_requested_project() {
+        let mut data = StorageData::default();
+        data.projects
+            .insert("/a".to_string(), settings("node", "4096", 1));
+        data.projects
+            .insert("/b".to_string(), settings("deno", "8192", 2));
+        let content = Storage::serialize(&data, ConfigFormat::Json).unwrap();
+
+        let scoped = Storage::parse_scoped(&content, "/b", ConfigFormat::Json).unwrap();
+        assert_eq!(scoped.projects.len(), 1);
+        assert_eq!(scoped.projects["/b"], data.projects["/b"]);
+
+        let missing = Storage::parse_scoped(&content, "/c", ConfigFormat::Json).unwrap();
+        assert!(missing.projects.is_empty());
+
+        assert!(Storage::parse_scoped("not json", "/a", ConfigFormat::Json).is_err());
+    }
+
+    #[test]
+    fn test_parse_scoped_keeps_only_requested_profiles() {
+        let mut data = StorageData::default();
+        data.projects
+            .insert("/a".to_string(), settings("node", "4096", 1));
+        for path in ["/a", "/b"] {
+            let build = BTreeMap::from([("build".to_string(), settings("node", "8192", 1))]);
+            data.profiles.insert(path.to_string(), build);
+        }
+
+        for format in [ConfigFormat::Json, ConfigFormat::Yaml, ConfigFormat::Toml] {
+            let content = Storage::serialize(&data, format).unwrap();
+            assert_eq!(format.parse(&content).unwrap(), data);
+
+            let scoped = Storage::parse_scoped(&content, "/a", format).unwrap();
+            assert_eq!(scoped.profiles.len(), 1);
+            assert_eq!(scoped.profiles["/a"]["build"].memory, "8192");
+        }
+    }
+
+    #[test]
+    fn test_parse_scoped_large_config() {
+        let mut data = StorageData::default();
+        for i in 0..20_000 {
+            data.projects.insert(
+                format!("/work/project-{:05}", i),
+                settings("node", &(2048 + i).to_string(), i as u64),
+            );
         }
+        let content = Storage::serialize(&data, ConfigFormat::Json).unwrap();
 
-        let content = serde_json::to_string_pretty(data)?;
-        fs::write(&path, content)?;
-        Ok(())
+        let scoped =
+            Storage::parse_scoped(&content, "/work/project-12345", ConfigFormat::Json).unwrap();
+        assert_eq!(scoped.projects.len(), 1);
+        assert_eq!(scoped.projects["/work/project-12345"].memory, "14393");
     }
 }
@@ -0,0 +1,110 @@
+//! Opt-in wall-clock breakdown of frc's own overhead (`--timings`)
+
+use crate::output::errln;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// Label for time spent waiting on the child; excluded from frc's overhead
+pub const CHILD: &str = "child";
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static ENTRIES: Mutex<Vec<(&'static str, Duration)>> = Mutex::new(Vec::new());
+
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Run `f`, adding its wall-clock time to `label` when timings are enabled
+pub fn measure<T>(label: &'static str, f: impl FnOnce() -> T) -> T {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return f();
+    }
+
+    let start = Instant::now();
+    let result = f();
+    let elapsed = start.elapsed();
+
+    let mut entries = ENTRIES.lock().unwrap_or_else(|e| e.into_inner());
+    match entries.iter_mut().find(|(l, _)| *l == label) {
+        Some((_, total)) => *total += elapsed,
+        None => entries.push((label, elapsed)),
+    }
+    result
+}
+
+/// Print the breakdown to stderr. `total` is frc's whole lifetime.
+pub fn report(total: Duration) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+
+    let entries = ENTRIES.lock().unwrap_or_else(|e| e.into_inner());
+    errln!("\n⏱️  frc timings:");
+    for line in format_report(&entries, total) {
+        errln!("   {}", line);
+    }
+}
+
+/// Min, median and max of a set of samples
+pub fn summarize(samples: &mut [Duration]) -> Option<(Duration, Duration, Duration)> {
+    samples.sort();
+    let min = *samples.first()?;
+    let max = *samples.last()?;
+    Some((min, samples[samples.len() / 2], max))
+}
+
+fn format_report(entries: &[(&'static str, Duration)], total: Duration) -> Vec<String> {
+    let child = entries
+        .iter()
+        .find(|(label, _)| *label == CHILD)
+        .map(|(_, d)| *d)
+        .unwrap_or_default();
+
+    let mut lines: Vec<_> = entries
+        .iter()
+        .filter(|(label, _)| *label != CHILD)
+        .map(|(label, d)| format!("{:<16} {:>9.2} ms", label, d.as_secs_f64() * 1000.0))
+        .collect();
+
+    let overhead = total.saturating_sub(child);
+    lines.push(format!(
+        "{:<16} {:>9.2} ms",
+        "total overhead",
+        overhead.as_secs_f64() * 1000.0
+    ));
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summarize() {
+        let mut samples = [5, 1, 9, 3, 7].map(Duration::from_millis);
+        let (min, median, max) = summarize(&mut samples).unwrap();
+        assert_eq!(min, Duration::from_millis(1));
+        assert_eq!(median, Duration::from_millis(5));
+        assert_eq!(max, Duration::from_millis(9));
+
+        assert!(summarize(&mut []).is_none());
+    }
+
+    #[test]
+    fn test_format_report_excludes_child() {
+        let entries = [
+            ("detect_root", Duration::from_millis(2)),
+            (CHILD, Duration::from_millis(500)),
+            ("config_load", Duration::from_millis(3)),
+        ];
+
+        let lines = format_report(&entries, Duration::from_millis(510));
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("detect_root"));
+        assert!(lines[0].contains("2.00 ms"));
+        assert!(lines[1].starts_with("config_load"));
+        assert!(lines[2].starts_with("total overhead"));
+        assert!(lines[2].contains("10.00 ms"));
+    }
+}
@@ -0,0 +1,61 @@
+//! Peak memory of the runtime process, read back after it exits
+
+/// Bytes per unit of `ru_maxrss`: kilobytes on Linux and the BSDs, bytes
+/// on macOS
+#[cfg(target_os = "macos")]
+const MAXRSS_UNIT: u64 = 1;
+#[cfg(all(unix, not(target_os = "macos")))]
+const MAXRSS_UNIT: u64 = 1024;
+
+/// Peak resident set size, in MB, of the largest child frc has waited
+/// for. `None` where the platform doesn't report it.
+#[cfg(unix)]
+pub fn children_peak_mb() -> Option<u64> {
+    let mut usage = std::mem::MaybeUninit::<libc::rusage>::uninit();
+    let usage = unsafe {
+        if libc::getrusage(libc::RUSAGE_CHILDREN, usage.as_mut_ptr()) != 0 {
+            return None;
+        }
+        usage.assume_init()
+    };
+    let peak = u64::try_from(usage.ru_maxrss).ok()?;
+    (peak > 0).then(|| maxrss_to_mb(peak, MAXRSS_UNIT))
+}
+
+#[cfg(not(unix))]
+pub fn children_peak_mb() -> Option<u64> {
+    None
+}
+
+/// `maxrss` in units of `unit` bytes, rounded to the nearest MB
+#[cfg_attr(not(unix), allow(dead_code))]
+fn maxrss_to_mb(maxrss: u64, unit: u64) -> u64 {
+    let bytes = maxrss * unit;
+    (bytes + 512 * 1024) / (1024 * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_maxrss_to_mb() {
+        // Linux reports kilobytes
+        assert_eq!(maxrss_to_mb(2_097_152, 1024), 2048);
+        assert_eq!(maxrss_to_mb(1_536, 1024), 2);
+        assert_eq!(maxrss_to_mb(100, 1024), 0);
+        // macOS reports bytes
+        assert_eq!(maxrss_to_mb(2_147_483_648, 1), 2048);
+        assert_eq!(maxrss_to_mb(1_572_864, 1), 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_children_peak_mb() {
+        std::process::Command::new("sh")
+            .args(["-c", "true"])
+            .status()
+            .unwrap();
+        assert!(children_peak_mb().is_some());
+    }
+}
@@ -0,0 +1,25 @@
+//! Opt-in trace of how frc picks the memory value (`--verbose`)
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Print `args` to stderr when verbose output is enabled
+pub fn log(args: fmt::Arguments) {
+    if ENABLED.load(Ordering::Relaxed) {
+        eprintln!("[frc] {}", args);
+    }
+}
+
+/// `eprintln!`-style logging, only shown with `--verbose`
+macro_rules! verbose {
+    ($($arg:tt)*) => {
+        $crate::verbose::log(format_args!($($arg)*))
+    };
+}
+pub(crate) use verbose;
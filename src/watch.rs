@@ -0,0 +1,201 @@
+//! File watching for `frc watch`: turns bursts of file events into single
+//! restart requests.
+
+use crate::signals::Stop;
+use anyhow::{Context, Result};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::{Component, Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Directories whose changes never trigger a restart
+const IGNORED_DIRS: [&str; 2] = [".git", "node_modules"];
+
+/// Waits for a quiet period after the last event before firing, so a save
+/// touching many files restarts the runtime once
+#[derive(Debug)]
+pub struct Debouncer {
+    delay: Duration,
+    last_event: Option<Instant>,
+}
+
+impl Debouncer {
+    pub fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            last_event: None,
+        }
+    }
+
+    pub fn event(&mut self, at: Instant) {
+        self.last_event = Some(at);
+    }
+
+    /// How long to wait for further events; `None` when nothing is pending
+    pub fn wait_time(&self, now: Instant) -> Option<Duration> {
+        self.last_event
+            .map(|last| (last + self.delay).saturating_duration_since(now))
+    }
+
+    /// Whether the pending events have settled. Firing clears them.
+    pub fn fire(&mut self, now: Instant) -> bool {
+        match self.last_event {
+            Some(last) if now >= last + self.delay => {
+                self.last_event = None;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Watches `paths` recursively. After each settled burst of changes the
+/// running child is stopped through `stop` and a message is sent on the
+/// returned channel. Watching ends when the watcher is dropped.
+pub fn on_change(
+    paths: &[PathBuf],
+    delay: Duration,
+    stop: Stop,
+) -> Result<(RecommendedWatcher, Receiver<()>)> {
+    let (events_tx, events) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+        if event.is_ok_and(|event| is_relevant(&event)) {
+            let _ = events_tx.send(());
+        }
+    })?;
+    for path in paths {
+        watcher
+            .watch(path, RecursiveMode::Recursive)
+            .with_context(|| format!("Failed to watch {}", path.display()))?;
+    }
+
+    let (changes_tx, changes) = mpsc::channel();
+    thread::spawn(move || {
+        debounce(&events, Debouncer::new(delay), || {
+            stop.trigger();
+            changes_tx.send(()).is_ok()
+        })
+    });
+    Ok((watcher, changes))
+}
+
+/// Feed `events` through `debouncer`, calling `fire` for each settled
+/// burst until it returns false or the events end
+fn debounce(events: &Receiver<()>, mut debouncer: Debouncer, mut fire: impl FnMut() -> bool) {
+    loop {
+        let received = match debouncer.wait_time(Instant::now()) {
+            Some(wait) => events.recv_timeout(wait),
+            None => events.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        match received {
+            Ok(()) => debouncer.event(Instant::now()),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+        if debouncer.fire(Instant::now()) && !fire() {
+            return;
+        }
+    }
+}
+
+/// Content changes outside `IGNORED_DIRS`; reads don't count
+fn is_relevant(event: &Event) -> bool {
+    !matches!(event.kind, EventKind::Access(_)) && !event.paths.iter().all(|p| is_ignored(p))
+}
+
+fn is_ignored(path: &Path) -> bool {
+    path.components().any(|component| match component {
+        Component::Normal(name) => IGNORED_DIRS.iter().any(|dir| name == *dir),
+        _ => false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{AccessKind, ModifyKind};
+
+    const DELAY: Duration = Duration::from_millis(200);
+
+    #[test]
+    fn test_debouncer_waits_for_quiet_period() {
+        let start = Instant::now();
+        let mut debouncer = Debouncer::new(DELAY);
+        assert_eq!(debouncer.wait_time(start), None);
+        assert!(!debouncer.fire(start));
+
+        debouncer.event(start);
+        assert_eq!(debouncer.wait_time(start), Some(DELAY));
+        assert!(!debouncer.fire(start + Duration::from_millis(100)));
+
+        // A later event pushes the deadline back
+        debouncer.event(start + Duration::from_millis(150));
+        assert!(!debouncer.fire(start + DELAY));
+        assert_eq!(
+            debouncer.wait_time(start + DELAY),
+            Some(Duration::from_millis(150))
+        );
+
+        assert!(debouncer.fire(start + Duration::from_millis(350)));
+        // Fired once; nothing pending afterwards
+        assert!(!debouncer.fire(start + Duration::from_secs(1)));
+        assert_eq!(debouncer.wait_time(start + Duration::from_secs(1)), None);
+    }
+
+    #[test]
+    fn test_debounce_collapses_bursts() {
+        let (tx, rx) = mpsc::channel();
+        let sender = thread::spawn(move || {
+            for burst in 0..2 {
+                if burst > 0 {
+                    thread::sleep(DELAY * 3);
+                }
+                for _ in 0..5 {
+                    tx.send(()).unwrap();
+                    thread::sleep(Duration::from_millis(10));
+                }
+            }
+            thread::sleep(DELAY * 3);
+        });
+
+        let mut fired = 0;
+        debounce(&rx, Debouncer::new(DELAY), || {
+            fired += 1;
+            true
+        });
+        sender.join().unwrap();
+        assert_eq!(fired, 2);
+    }
+
+    #[test]
+    fn test_debounce_stops_when_fire_declines() {
+        let (tx, rx) = mpsc::channel();
+        tx.send(()).unwrap();
+        let mut fired = 0;
+        // Returns even though the sender is still alive
+        debounce(&rx, Debouncer::new(Duration::from_millis(10)), || {
+            fired += 1;
+            false
+        });
+        assert_eq!(fired, 1);
+        drop(tx);
+    }
+
+    #[test]
+    fn test_is_relevant() {
+        let modify = |path: &str| {
+            Event::new(EventKind::Modify(ModifyKind::Any)).add_path(PathBuf::from(path))
+        };
+        assert!(is_relevant(&modify("/app/src/index.js")));
+        assert!(!is_relevant(&modify("/app/node_modules/x/index.js")));
+        assert!(!is_relevant(&modify("/app/.git/index")));
+        assert!(is_relevant(
+            &modify("/app/.git/index").add_path(PathBuf::from("/app/src/a.js"))
+        ));
+
+        let read = Event::new(EventKind::Access(AccessKind::Any))
+            .add_path(PathBuf::from("/app/src/index.js"));
+        assert!(!is_relevant(&read));
+    }
+}
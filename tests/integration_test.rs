@@ -1,5 +1,18 @@
+use fe_runtime_container::{Config, Manager, Project, RunOptions, Runtime};
+use std::fs;
+use std::path::PathBuf;
 use std::process::Command;
 
+/// Allocates until the heap limit is hit
+const OOM_SCRIPT: &str = "const a = []; while (true) a.push(new Array(1e6).fill(1));";
+
+fn temp_project(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("frc-it-{}-{}", name, std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("package.json"), "{}").unwrap();
+    dir
+}
+
 #[test]
 fn test_help_command() {
     let output = Command::new("cargo")
@@ -23,7 +36,7 @@ fn test_version_command() {
     assert!(output.status.success());
     let stdout = String::from_utf8_lossy(&output.stdout);
     assert!(stdout.contains("frc"));
-    assert!(stdout.contains("0.1.0"));
+    assert!(stdout.contains(env!("CARGO_PKG_VERSION")));
 }
 
 #[test]
@@ -52,6 +65,38 @@ fn test_info_deno_command() {
     assert!(stdout.contains("Recommendations for deno"));
 }
 
+#[test]
+fn test_info_multiple_runtimes() {
+    let output = Command::new(env!("CARGO_BIN_EXE_frc"))
+        .args(["info", "node", "deno", "bun"])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.matches("System:").count(), 1);
+    for runtime in ["node", "deno", "bun"] {
+        assert!(stdout.contains(&format!("Recommendations for {}", runtime)));
+    }
+
+    let output = Command::new(env!("CARGO_BIN_EXE_frc"))
+        .args(["info", "--all", "--json"])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    let info: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let reports = info.as_array().unwrap();
+    let runtimes: Vec<_> = reports.iter().map(|r| &r["runtime"]).collect();
+    assert_eq!(runtimes, ["node", "deno", "bun"]);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_frc"))
+        .arg("info")
+        .output()
+        .expect("Failed to execute command");
+    assert!(!output.status.success());
+}
+
 #[test]
 fn test_info_bun_command() {
     let output = Command::new("cargo")
@@ -75,7 +120,7 @@ fn test_project_command() {
     assert!(output.status.success());
     let stdout = String::from_utf8_lossy(&output.stdout);
     assert!(stdout.contains("Project:"));
-    assert!(stdout.contains("fe-run-container"));
+    assert!(stdout.contains(env!("CARGO_MANIFEST_DIR")));
 }
 
 #[test]
@@ -121,3 +166,1536 @@ fn test_node_with_invalid_memory() {
     // Should either succeed with warning or fail with error about memory
     assert!(combined.contains("GB") || combined.contains("memory"));
 }
+
+#[test]
+fn test_nested_run_inherits_parent_memory() {
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "--inherit-parent-memory",
+            "node",
+            "-e",
+            "console.log(process.env.NODE_OPTIONS, process.env.FRC_RESOLVED_MEMORY)",
+        ])
+        .env("FRC_RESOLVED_MEMORY", "1234")
+        .env_remove("NODE_OPTIONS")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("inherited from parent frc: 1234 MB"));
+    assert!(stdout.contains("--max-old-space-size=1234 1234"));
+}
+
+#[test]
+fn test_cwd_changes_detected_project() {
+    let dir = temp_project("cwd-project");
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "--cwd"])
+        .arg(&dir)
+        .arg("project")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let name = dir.file_name().unwrap().to_string_lossy();
+    assert!(stdout.contains(&format!("Project: {}", name)));
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_cwd_sets_child_directory() {
+    let dir = temp_project("cwd-child");
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "--cwd"])
+        .arg(&dir)
+        .args(["node", "-e", "console.log('cwd=' + process.cwd())"])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let expected = dir.canonicalize().unwrap();
+    assert!(stdout.contains(&format!("cwd={}", expected.display())));
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_no_auto_increase_keeps_saved_memory() {
+    let dir = temp_project("no-auto-increase");
+    let config_home = dir.join("config");
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "--cwd"])
+        .arg(&dir)
+        .args(["-m", "256", "--save", "--no-auto-increase"])
+        .args(["node", "-e", OOM_SCRIPT])
+        .env("XDG_CONFIG_HOME", &config_home)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Out of Memory Detected"));
+    assert!(!stdout.contains("Auto-increased"));
+
+    let config = fs::read_to_string(config_home.join("frc").join("config.json")).unwrap();
+    assert!(config.contains("\"memory\": \"256\""));
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_missing_runtime_reports_friendly_error() {
+    let dir = temp_project("missing-runtime");
+    let config_home = dir.join("config");
+    let empty_path = dir.join("empty-bin");
+    fs::create_dir_all(&empty_path).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_frc"))
+        .args(["--cwd"])
+        .arg(&dir)
+        .args(["node", "index.js"])
+        .env("PATH", &empty_path)
+        .env("XDG_CONFIG_HOME", &config_home)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("runtime 'node' not found on PATH; install it or use --runtime"));
+    assert!(stderr.contains("https://nodejs.org"));
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_set_default_accepts_units() {
+    let dir = temp_project("set-default-units");
+    let config_home = dir.join("config");
+    let set_default = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_frc"))
+            .arg("set-default")
+            .args(args)
+            .env("XDG_CONFIG_HOME", &config_home)
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    assert!(set_default(&["-m", "4g"]).status.success());
+    let runtime_default = set_default(&["-r", "node", "-m", "1536MB"]);
+    assert!(runtime_default.status.success());
+    assert!(!set_default(&["-m", "4x"]).status.success());
+
+    let config = fs::read_to_string(config_home.join("frc").join("config.json")).unwrap();
+    let config: serde_json::Value = serde_json::from_str(&config).unwrap();
+    assert_eq!(config["global_default"]["memory"], "4096");
+    assert_eq!(config["defaults"]["node"], "1536");
+
+    // The runtime default is what `info` recommends for that runtime only
+    let info = Command::new(env!("CARGO_BIN_EXE_frc"))
+        .args(["--json", "info", "node", "deno"])
+        .env("XDG_CONFIG_HOME", &config_home)
+        .output()
+        .expect("Failed to execute command");
+    let reports: serde_json::Value = serde_json::from_slice(&info.stdout).unwrap();
+    assert_eq!(reports[0]["recommended_mb"], 1536);
+    assert_ne!(reports[1]["recommended_mb"], 1536);
+    let info = Command::new(env!("CARGO_BIN_EXE_frc"))
+        .args(["info", "node"])
+        .env("XDG_CONFIG_HOME", &config_home)
+        .output()
+        .expect("Failed to execute command");
+    let stdout = String::from_utf8_lossy(&info.stdout);
+    assert!(stdout.contains("Your default: 1536 MB"));
+    assert!(stdout.contains("frc -m 1536 node script.js"));
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_set_default_memory_max_caps_runs() {
+    let dir = temp_project("set-default-ceiling");
+    let config_home = dir.join("config");
+    let frc = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_frc"))
+            .arg("--cwd")
+            .arg(&dir)
+            .args(args)
+            .env("XDG_CONFIG_HOME", &config_home)
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    let saved = frc(&["set-default", "--memory-max", "1GB"]);
+    assert!(saved.status.success());
+    let script = "console.log(process.env.NODE_OPTIONS)";
+    let output = frc(&["-m", "2048", "--save", "node", "-e", script]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("--max-old-space-size=1024"));
+
+    let project = String::from_utf8_lossy(&frc(&["project"]).stdout).to_string();
+    assert!(project.contains("Memory ceiling: 1024 MB"));
+    let doctor = String::from_utf8_lossy(&frc(&["doctor"]).stdout).to_string();
+    assert!(doctor.contains("memory ceiling: 1024 MB"));
+
+    assert!(frc(&["set-default", "--no-memory-max"]).status.success());
+    let project = String::from_utf8_lossy(&frc(&["project"]).stdout).to_string();
+    assert!(!project.contains("Memory ceiling"));
+    assert!(!frc(&["set-default"]).status.success());
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_explain_memory_resolves_like_a_run() {
+    let dir = temp_project("explain-memory");
+    let config_home = dir.join("config");
+    let frc = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_frc"))
+            .arg("--cwd")
+            .arg(&dir)
+            .args(args)
+            .env("XDG_CONFIG_HOME", &config_home)
+            .output()
+            .expect("Failed to execute command");
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+    fs::write(
+        dir.join(".frc.json"),
+        r#"{"runtime": "node", "memory": "3GB"}"#,
+    )
+    .unwrap();
+
+    let stdout = frc(&["explain-memory"]);
+    assert!(stdout.contains("Configured heap limit: 3072 MB (node)"));
+    assert!(!stdout.contains("Using .frc.json"));
+
+    let stdout = frc(&["-m", "2048", "explain-memory"]);
+    assert!(stdout.contains("Configured heap limit: 2048 MB (node)"));
+
+    let stdout = frc(&["explain-memory", "-r", "deno", "--probe"]);
+    // .frc.json is for node, so nothing is configured for deno
+    assert!(stdout.contains("No heap limit configured; deno derives"));
+    assert!(stdout.contains("--probe only measures Node"));
+    assert!(!stdout.contains("Measured"));
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_doctor_reports_memory_without_run_messages() {
+    let dir = temp_project("doctor");
+    let config_home = dir.join("config");
+    let frc = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_frc"))
+            .arg("--cwd")
+            .arg(&dir)
+            .args(args)
+            .env("XDG_CONFIG_HOME", &config_home)
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    let saved = frc(&["-m", "3072", "--save", "node", "-e", "0"]);
+    assert!(saved.status.success());
+
+    let output = frc(&["doctor"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("memory (node): 3072 MB"));
+    assert!(!stdout.contains("Using saved"));
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_runtime_version_is_saved_between_runs() {
+    let dir = temp_project("runtime-version");
+    let config_home = dir.join("config");
+    let config_file = config_home.join("frc").join("config.json");
+    let frc = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_frc"))
+            .arg("--cwd")
+            .arg(&dir)
+            .args(args)
+            .args(["node", "-e", "0"])
+            .env("XDG_CONFIG_HOME", &config_home)
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    assert!(frc(&["-m", "1024", "--save"]).status.success());
+    let mut config: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&config_file).unwrap()).unwrap();
+    let versions = config["runtime_versions"].as_object_mut().unwrap();
+    assert_eq!(versions.len(), 1);
+
+    // The next run trusts the saved version instead of asking node again
+    let (_, saved) = versions.iter_mut().next().unwrap();
+    saved["version"] = "10.0.0".into();
+    fs::write(&config_file, config.to_string()).unwrap();
+    let output = frc(&[]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("node 10.0.0 is older than"));
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_cwd_after_subcommand() {
+    let dir = temp_project("cwd-subcommand");
+    let config_home = dir.join("config");
+    fs::write(dir.join("index.js"), "").unwrap();
+
+    // Runtime arguments are passed through, so --cwd goes first there
+    let output = Command::new("cargo")
+        .args(["run", "--", "--cwd"])
+        .arg(&dir)
+        .args(["-m", "1536", "node", "index.js"])
+        .env("XDG_CONFIG_HOME", &config_home)
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+
+    let frc = |args: &[&str]| {
+        Command::new("cargo")
+            .args(["run", "--"])
+            .args(args)
+            .arg("--cwd")
+            .arg(&dir)
+            .env("XDG_CONFIG_HOME", &config_home)
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    let output = frc(&["project"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Memory: 1536 MB"));
+
+    // Not a terminal, so removing has to be forced
+    let output = frc(&["forget"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--force"));
+    let output = frc(&["project"]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Memory: 1536 MB"));
+
+    let output = frc(&["forget", "--force"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Removed config"));
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_report_file_records_oom() {
+    let dir = temp_project("report-file");
+    let config_home = dir.join("config");
+    let report = dir.join("oom.json");
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "--cwd"])
+        .arg(&dir)
+        .args(["-m", "256", "--save", "--report-file"])
+        .arg(&report)
+        .args(["node", "-e", OOM_SCRIPT])
+        .env("XDG_CONFIG_HOME", &config_home)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Auto-increased"));
+
+    let report: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&report).unwrap()).unwrap();
+    assert_eq!(report["event"], "oom");
+    assert_eq!(report["runtime"], "node");
+    assert_eq!(report["old_mb"], 256);
+    assert_eq!(report["new_mb"], 2304);
+    let project = dir.canonicalize().unwrap();
+    assert_eq!(report["project"], project.to_string_lossy().as_ref());
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_run_all_uses_each_projects_memory() {
+    let first = temp_project("run-all-a");
+    let second = temp_project("run-all-b");
+    let third = temp_project("run-all-c");
+    let config_home = first.join("config");
+    let frc = || {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_frc"));
+        cmd.env("XDG_CONFIG_HOME", &config_home);
+        cmd
+    };
+
+    for (dir, memory) in [(&first, "384"), (&second, "448"), (&third, "320")] {
+        let status = frc()
+            .arg("--cwd")
+            .arg(dir)
+            .args(["-m", memory, "--save", "node", "-e", "0"])
+            .status()
+            .expect("Failed to execute command");
+        assert!(status.success());
+    }
+    // .frc.json takes precedence over the saved value, as in a normal run
+    fs::write(
+        second.join(".frc.json"),
+        r#"{"runtime": "node", "memory": "512"}"#,
+    )
+    .unwrap();
+    let status = frc()
+        .arg("--cwd")
+        .arg(&third)
+        .args(["use", "deno"])
+        .status()
+        .expect("Failed to execute command");
+    assert!(status.success());
+
+    let script = "console.log('seen', process.env.NODE_OPTIONS)";
+    let output = frc()
+        .args(["run-all", "--jobs", "2", "node", "-e", script])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("seen --max-old-space-size=384"));
+    assert!(stdout.contains("seen --max-old-space-size=512"));
+    assert!(!stdout.contains("seen --max-old-space-size=320"));
+    assert!(stdout.contains("All 2 project(s) succeeded"));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("saved for deno"));
+
+    let failing = frc()
+        .args(["run-all", "node", "-e", "process.exit(3)"])
+        .output()
+        .expect("Failed to execute command");
+    assert_eq!(failing.status.code(), Some(3));
+    let stdout = String::from_utf8_lossy(&failing.stdout);
+    assert!(stdout.contains("(skipped)"));
+
+    fs::remove_dir_all(&first).ok();
+    fs::remove_dir_all(&second).ok();
+    fs::remove_dir_all(&third).ok();
+}
+
+#[test]
+fn test_run_all_applies_run_options_and_handles_oom() {
+    let ok = temp_project("run-all-env");
+    let oom = temp_project("run-all-oom");
+    let config_home = ok.join("config");
+    let frc = || {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_frc"));
+        cmd.env("XDG_CONFIG_HOME", &config_home);
+        cmd
+    };
+
+    for (dir, memory) in [(&ok, "512"), (&oom, "256")] {
+        let status = frc()
+            .arg("--cwd")
+            .arg(dir)
+            .args(["-m", memory, "--save", "node", "-e", "0"])
+            .status()
+            .expect("Failed to execute command");
+        assert!(status.success());
+    }
+    fs::write(oom.join("oom"), "").unwrap();
+
+    let script = format!(
+        "console.log('greeting', process.env.GREETING); if (require('fs').existsSync('oom')) {{ {} }}",
+        OOM_SCRIPT
+    );
+    let output = frc()
+        .args(["--env", "GREETING=hi", "run-all", "--keep-going"])
+        .args(["--jobs", "2", "node", "-e", &script])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.matches("greeting hi").count(), 2);
+    assert!(stdout.contains("Auto-increased: 256 MB"));
+    let oom = oom.canonicalize().unwrap();
+    assert!(stdout.contains(&format!("[error] {}: Out of Memory", oom.display())));
+
+    let output = frc()
+        .arg("--cwd")
+        .arg(&oom)
+        .arg("project")
+        .output()
+        .expect("Failed to execute command");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Memory: 2304 MB"));
+
+    fs::remove_dir_all(&ok).ok();
+    fs::remove_dir_all(&oom).ok();
+}
+
+#[test]
+fn test_run_all_keep_going_reports_every_failure() {
+    let ok = temp_project("keep-going-ok");
+    let failing = temp_project("keep-going-fail");
+    let broken = temp_project("keep-going-broken");
+    let config_home = ok.join("config");
+    let frc = || {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_frc"));
+        cmd.env("XDG_CONFIG_HOME", &config_home);
+        cmd
+    };
+
+    for dir in [&ok, &failing, &broken] {
+        let status = frc()
+            .arg("--cwd")
+            .arg(dir)
+            .args(["-m", "512", "--save", "node", "-e", "0"])
+            .status()
+            .expect("Failed to execute command");
+        assert!(status.success());
+    }
+    fs::write(failing.join("fail"), "").unwrap();
+    // Memory can't be resolved for this one
+    fs::write(broken.join(".frc.json"), "{").unwrap();
+
+    let script = "process.exit(require('fs').existsSync('fail') ? 2 : 0)";
+
+    // Without --keep-going the resolution error stops the batch up front
+    let output = frc()
+        .args(["run-all", "node", "-e", script])
+        .output()
+        .expect("Failed to execute command");
+    assert!(!output.status.success());
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("Summary"));
+
+    let output = frc()
+        .args(["run-all", "--keep-going", "node", "-e", script])
+        .output()
+        .expect("Failed to execute command");
+    assert_eq!(output.status.code(), Some(2));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let ok_line = format!("[ok] {}", ok.canonicalize().unwrap().display());
+    assert!(stdout.contains(&ok_line));
+    let failing = failing.canonicalize().unwrap();
+    assert!(stdout.contains(&format!("[error] {} (exit 2)", failing.display())));
+    let broken = broken.canonicalize().unwrap();
+    assert!(stdout.contains(&format!("[error] {}: Invalid", broken.display())));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("2 of 3 project(s) did not succeed"));
+
+    for dir in [&ok, &failing, &broken] {
+        fs::remove_dir_all(dir).ok();
+    }
+}
+
+#[test]
+fn test_timeout_stops_long_running_child() {
+    use std::time::{Duration, Instant};
+
+    let dir = temp_project("timeout");
+    fs::write(dir.join("sleep.js"), "setTimeout(() => {}, 60000);").unwrap();
+
+    let start = Instant::now();
+    let output = Command::new(env!("CARGO_BIN_EXE_frc"))
+        .arg("--cwd")
+        .arg(&dir)
+        .args(["--timeout", "1", "node", "sleep.js"])
+        .env("XDG_CONFIG_HOME", dir.join("config"))
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success());
+    assert!(start.elapsed() < Duration::from_secs(30));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Timed out after 1"));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!stdout.contains("Out of Memory"));
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_sigterm_is_forwarded_to_child() {
+    use std::io::{BufRead, BufReader};
+    use std::process::Stdio;
+    use std::time::{Duration, Instant};
+
+    let script = "process.on('SIGTERM', () => { console.log('child got SIGTERM'); process.exit(0); }); \
+                  console.log('ready'); setInterval(() => {}, 1000);";
+
+    // Signal the frc binary itself rather than a `cargo run` wrapper
+    let mut frc = Command::new(env!("CARGO_BIN_EXE_frc"))
+        .args(["node", "-e", script])
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Failed to execute command");
+
+    let mut stdout = BufReader::new(frc.stdout.take().unwrap());
+    let mut line = String::new();
+    // frc echoes the script first, so wait for the child's own line
+    while line.trim() != "ready" {
+        line.clear();
+        let read = stdout.read_line(&mut line).unwrap();
+        assert!(read > 0, "child never started");
+    }
+
+    let status = Command::new("kill")
+        .args(["-TERM", &frc.id().to_string()])
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while frc.try_wait().unwrap().is_none() {
+        assert!(Instant::now() < deadline, "frc did not exit after SIGTERM");
+        std::thread::sleep(Duration::from_millis(50));
+    }
+
+    let mut rest = String::new();
+    std::io::Read::read_to_string(&mut stdout, &mut rest).unwrap();
+    assert!(rest.contains("child got SIGTERM"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_on_interrupt_sends_chosen_signal() {
+    use std::io::{BufRead, BufReader};
+    use std::process::Stdio;
+    use std::time::{Duration, Instant};
+
+    let script = "process.on('SIGINT', () => { console.log('child got SIGINT'); process.exit(0); }); \
+                  process.on('SIGUSR2', () => { console.log('child got SIGUSR2'); process.exit(0); }); \
+                  console.log('ready'); setInterval(() => {}, 1000);";
+
+    let mut frc = Command::new(env!("CARGO_BIN_EXE_frc"))
+        .args(["--on-interrupt", "usr2", "node", "-e", script])
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Failed to execute command");
+
+    let mut stdout = BufReader::new(frc.stdout.take().unwrap());
+    let mut line = String::new();
+    while line.trim() != "ready" {
+        line.clear();
+        let read = stdout.read_line(&mut line).unwrap();
+        assert!(read > 0, "child never started");
+    }
+
+    let status = Command::new("kill")
+        .args(["-INT", &frc.id().to_string()])
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while frc.try_wait().unwrap().is_none() {
+        assert!(Instant::now() < deadline, "frc did not exit after SIGINT");
+        std::thread::sleep(Duration::from_millis(50));
+    }
+
+    let mut rest = String::new();
+    std::io::Read::read_to_string(&mut stdout, &mut rest).unwrap();
+    assert!(rest.contains("child got SIGUSR2"));
+    assert!(!rest.contains("child got SIGINT"));
+}
+
+/// Detection reads the OS directly, so it still works without a shell on PATH;
+/// inside a container the cgroup limit counts when it is lower
+#[cfg(target_os = "linux")]
+#[test]
+fn test_system_memory_needs_no_subprocess() {
+    let meminfo = fs::read_to_string("/proc/meminfo").unwrap();
+    let kib: u64 = meminfo
+        .lines()
+        .find_map(|line| line.strip_prefix("MemTotal:"))
+        .and_then(|rest| rest.trim().trim_end_matches("kB").trim().parse().ok())
+        .unwrap();
+    let mut bytes = kib * 1024;
+    let limits = [
+        "/sys/fs/cgroup/memory.max",
+        "/sys/fs/cgroup/memory/memory.limit_in_bytes",
+    ];
+    if let Some(limit) = limits
+        .iter()
+        .find_map(|path| fs::read_to_string(path).ok())
+        .and_then(|content| content.trim().parse::<u64>().ok())
+    {
+        bytes = bytes.min(limit);
+    }
+    let gib = 1024 * 1024 * 1024;
+    let expected = ((bytes + gib / 2) / gib).max(1);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_frc"))
+        .args(["--json", "info", "node"])
+        .env("PATH", "")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    let info: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(info[0]["system_gb"], expected);
+}
+
+#[test]
+fn test_quiet_leaves_only_child_output() {
+    let dir = temp_project("quiet");
+    let output = Command::new(env!("CARGO_BIN_EXE_frc"))
+        .arg("--cwd")
+        .arg(&dir)
+        .args(["-q", "-m", "300", "node", "-e", "console.log('from child')"])
+        .env("XDG_CONFIG_HOME", dir.join("config"))
+        .env_remove("NODE_OPTIONS")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "from child\n");
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_print_config_path() {
+    let dir = temp_project("config-path");
+    let output = Command::new(env!("CARGO_BIN_EXE_frc"))
+        .arg("--print-config-path")
+        .env("XDG_CONFIG_HOME", &dir)
+        .env_remove("FRC_CONFIG_HOME")
+        .env_remove("FRC_CONFIG_FORMAT")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let path = PathBuf::from(stdout.trim_end());
+    assert!(path.is_absolute());
+    assert!(path.ends_with("frc/config.json"));
+    assert!(path.starts_with(&dir));
+    // Nothing is created just by asking
+    assert!(!dir.join("frc").exists());
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_explicit_and_saved_memory_messages() {
+    let dir = temp_project("messages");
+    fs::write(dir.join("index.js"), "").unwrap();
+    let run = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_frc"))
+            .arg("--cwd")
+            .arg(&dir)
+            .args(args)
+            .env("XDG_CONFIG_HOME", dir.join("config"))
+            .output()
+            .expect("Failed to execute command");
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+
+    let explicit = run(&["-m", "1024", "node", "index.js"]);
+    assert!(explicit.contains("Applying 1024 MB (saved to project)"));
+    assert!(!explicit.contains("Using saved"));
+
+    let saved = run(&["node", "index.js"]);
+    assert!(saved.contains("Using saved 1024 MB"));
+    assert!(!saved.contains("Applying"));
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_save_and_no_save_flags() {
+    let dir = temp_project("save-flags");
+    fs::write(dir.join("index.js"), "").unwrap();
+    let frc = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_frc"))
+            .arg("--cwd")
+            .arg(&dir)
+            .args(args)
+            .env("XDG_CONFIG_HOME", dir.join("config"))
+            .env_remove("FRC_MEMORY")
+            .output()
+            .expect("Failed to execute command")
+    };
+    let saved = || String::from_utf8_lossy(&frc(&["project"]).stdout).into_owned();
+
+    // --no-save applies -m once
+    let output = frc(&["-m", "1024", "--no-save", "node", "index.js"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Applying 1024 MB (not saved)"));
+    assert!(!saved().contains("Saved Configuration"));
+
+    // --save without -m keeps the recommendation
+    let output = frc(&["--save", "node", "index.js"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("using recommended"));
+    assert!(stdout.contains("(saved to project)"));
+    assert!(saved().contains("Saved Configuration"));
+
+    // -m alone still saves, --no-save leaves it in place
+    assert!(frc(&["-m", "2048", "node", "index.js"]).status.success());
+    let output = frc(&["-m", "512", "--no-save", "node", "index.js"]);
+    assert!(output.status.success());
+    assert!(saved().contains("Memory: 2048 MB"));
+
+    // --save with only the saved value to go on rewrites nothing
+    let output = frc(&["--save", "node", "index.js"]);
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("Applying"));
+    assert!(saved().contains("Memory: 2048 MB"));
+
+    let output = frc(&["--save", "--no-save", "node", "index.js"]);
+    assert!(!output.status.success());
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_note_shows_in_project_and_list() {
+    let dir = temp_project("note");
+    fs::write(dir.join("index.js"), "").unwrap();
+    let frc = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_frc"))
+            .arg("--cwd")
+            .arg(&dir)
+            .args(args)
+            .env("XDG_CONFIG_HOME", dir.join("config"))
+            .output()
+            .expect("Failed to execute command");
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+
+    frc(&["-m", "1024", "node", "index.js"]);
+    frc(&["note", "webpack 4 needs the headroom"]);
+    assert!(frc(&["project"]).contains("Note: webpack 4 needs the headroom"));
+    assert!(frc(&["list"]).contains("Note: webpack 4 needs the headroom"));
+
+    frc(&["note", ""]);
+    assert!(!frc(&["project"]).contains("Note:"));
+    assert!(!frc(&["list"]).contains("Note:"));
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+/// sysfs refuses new directories even for root
+#[cfg(target_os = "linux")]
+#[test]
+fn test_read_only_config_still_runs() {
+    let dir = temp_project("read-only");
+    let script = "console.log(process.env.NODE_OPTIONS)";
+    fs::write(dir.join("index.js"), script).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_frc"))
+        .arg("--cwd")
+        .arg(&dir)
+        .args(["-m", "1024", "node", "index.js"])
+        .env("FRC_CONFIG_HOME", "/sys/frc-read-only")
+        .env_remove("NODE_OPTIONS")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Applying 1024 MB (not saved)"));
+    assert!(stdout.contains("--max-old-space-size=1024"));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("FRC_CONFIG_HOME"));
+    assert_eq!(stderr.matches("Could not save config").count(), 1);
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+/// A read-only project directory (sysfs, even for root) falls back to the
+/// user config
+#[cfg(target_os = "linux")]
+#[test]
+fn test_local_save_in_read_only_project() {
+    let config_home = temp_project("read-only-local");
+    let frc = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_frc"))
+            .args(["--cwd", "/sys/kernel"])
+            .args(args)
+            .env("XDG_CONFIG_HOME", &config_home)
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    let output = frc(&["-m", "1024", "--local", "--save", "node", "-e", "0"]);
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Can't write .frc.json in /sys/kernel"));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Applying 1024 MB (saved to project)"));
+    assert!(!std::path::Path::new("/sys/kernel/.frc.json").exists());
+
+    let output = frc(&["project"]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Memory: 1024 MB"));
+
+    fs::remove_dir_all(&config_home).ok();
+}
+
+#[test]
+fn test_list_runtimes() {
+    let output = Command::new(env!("CARGO_BIN_EXE_frc"))
+        .args(["--list-runtimes", "--json"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+
+    let runtimes: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let names: Vec<_> = runtimes
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|runtime| runtime["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["node", "deno", "bun"]);
+    for runtime in runtimes.as_array().unwrap() {
+        assert_eq!(runtime["supports_memory_config"], true);
+    }
+    let node_aliases = runtimes[0]["aliases"].as_array().unwrap();
+    assert!(node_aliases.contains(&"npm".into()));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_frc"))
+        .arg("--list-runtimes")
+        .output()
+        .expect("Failed to execute command");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("bunx"));
+}
+
+#[test]
+fn test_run_refreshes_last_used() {
+    let dir = temp_project("last-used");
+    fs::write(dir.join("index.js"), "").unwrap();
+    let config_file = dir.join("config").join("frc").join("config.json");
+    let frc = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_frc"))
+            .arg("--cwd")
+            .arg(&dir)
+            .args(args)
+            .env("XDG_CONFIG_HOME", dir.join("config"))
+            .env_remove("FRC_CONFIG_HOME")
+            .output()
+            .expect("Failed to execute command");
+        assert!(output.status.success());
+    };
+    let read = || -> serde_json::Value {
+        serde_json::from_str(&fs::read_to_string(&config_file).unwrap()).unwrap()
+    };
+
+    frc(&["-m", "512", "node", "index.js"]);
+    frc(&["--profile", "build", "-m", "768", "node", "index.js"]);
+
+    // Age both entries as if last touched long ago
+    let mut config = read();
+    let projects = config["projects"].as_object().unwrap();
+    let path = projects.keys().next().unwrap().clone();
+    config["projects"][&path]["last_used"] = 1000.into();
+    config["profiles"][&path]["build"]["last_used"] = 1000.into();
+    fs::write(&config_file, config.to_string()).unwrap();
+
+    frc(&["node", "index.js"]);
+    frc(&["--profile", "build", "node", "index.js"]);
+
+    let config = read();
+    let profile = &config["profiles"][&path]["build"];
+    assert!(config["projects"][&path]["last_used"].as_u64().unwrap() > 1000);
+    assert!(profile["last_used"].as_u64().unwrap() > 1000);
+
+    // Reading the config doesn't rewrite it
+    let before = fs::read_to_string(&config_file).unwrap();
+    frc(&["project"]);
+    frc(&["list"]);
+    assert_eq!(fs::read_to_string(&config_file).unwrap(), before);
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_frc_memory_env_precedence() {
+    let dir = temp_project("memory-env");
+    fs::write(dir.join("index.js"), "").unwrap();
+    let config_home = dir.join("config");
+    let frc = |args: &[&str], forced: Option<&str>| {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_frc"));
+        cmd.arg("--cwd")
+            .arg(&dir)
+            .args(args)
+            .env("XDG_CONFIG_HOME", &config_home)
+            .env_remove("NODE_OPTIONS")
+            .env_remove("FRC_MEMORY");
+        if let Some(mem) = forced {
+            cmd.env("FRC_MEMORY", mem);
+        }
+        let output = cmd.output().expect("Failed to execute command");
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+
+    frc(&["-m", "384", "node", "index.js"], None);
+
+    // FRC_MEMORY beats the saved config
+    let stdout = frc(&["--dry-run", "node", "index.js"], Some("448"));
+    assert!(stdout.contains("NODE_OPTIONS=--max-old-space-size=448"));
+
+    // -m beats FRC_MEMORY
+    let stdout = frc(&["--dry-run", "-m", "320", "node", "index.js"], Some("448"));
+    assert!(stdout.contains("NODE_OPTIONS=--max-old-space-size=320"));
+
+    // A run with FRC_MEMORY leaves the saved config alone
+    frc(&["node", "index.js"], Some("448"));
+    let stdout = frc(&["--dry-run", "node", "index.js"], None);
+    assert!(stdout.contains("NODE_OPTIONS=--max-old-space-size=384"));
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_dry_run_prints_memory_flags() {
+    let dir = temp_project("dry-run");
+    let config_home = dir.join("config");
+
+    let node = Command::new("cargo")
+        .args(["run", "--", "--cwd"])
+        .arg(&dir)
+        .args(["--dry-run", "-m", "4096", "node", "index.js"])
+        .env("XDG_CONFIG_HOME", &config_home)
+        .env_remove("NODE_OPTIONS")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(node.status.success());
+    let stdout = String::from_utf8_lossy(&node.stdout);
+    assert!(stdout.contains("Command: node index.js"));
+    assert!(stdout.contains("Env: NODE_OPTIONS=--max-old-space-size=4096"));
+    // Nothing is applied, so nothing claims to be
+    assert!(!stdout.contains("Setting memory limit"));
+
+    let deno = Command::new("cargo")
+        .args(["run", "--", "--cwd"])
+        .arg(&dir)
+        .args(["--dry-run", "-m", "4096", "deno", "run", "main.ts"])
+        .env("XDG_CONFIG_HOME", &config_home)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(deno.status.success());
+    let stdout = String::from_utf8_lossy(&deno.stdout);
+    assert!(stdout.contains("Command: deno --v8-flags --max-old-space-size=4096 run main.ts"));
+
+    // A dry run never writes the config
+    assert!(!config_home.join("frc").join("config.json").exists());
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_config_dir_name_override() {
+    let dir = temp_project("dir-name");
+    let config_home = dir.join("config");
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "--cwd"])
+        .arg(&dir)
+        .args(["-m", "2048", "--save", "node", "-e", "0"])
+        .env("XDG_CONFIG_HOME", &config_home)
+        .env("FRC_DIR_NAME", "acme-frc")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    assert!(config_home.join("acme-frc").join("config.json").exists());
+    assert!(!config_home.join("frc").exists());
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_json_output() {
+    let dir = temp_project("json");
+    let config_home = dir.join("config");
+    let frc = |args: &[&str]| {
+        let output = Command::new("cargo")
+            .args(["run", "--", "--cwd"])
+            .arg(&dir)
+            .args(args)
+            .env("XDG_CONFIG_HOME", &config_home)
+            .output()
+            .expect("Failed to execute command");
+        assert!(output.status.success());
+        output.stdout
+    };
+
+    let reports: serde_json::Value =
+        serde_json::from_slice(&frc(&["info", "node", "--json"])).unwrap();
+    assert_eq!(reports.as_array().unwrap().len(), 1);
+    let info = &reports[0];
+    assert_eq!(info["runtime"], "node");
+    assert!(info["system_gb"].as_u64().unwrap() > 0);
+    assert!(info["recommended_mb"].as_u64().unwrap() >= 2048);
+    assert!(info["range_mb"]["low"].as_u64() < info["range_mb"]["high"].as_u64());
+
+    frc(&["-m", "3072", "--save", "node", "-e", "0"]);
+
+    let project: serde_json::Value = serde_json::from_slice(&frc(&["--json", "project"])).unwrap();
+    let name = dir.file_name().unwrap().to_string_lossy();
+    assert_eq!(project["name"], name.as_ref());
+    assert_eq!(project["config"]["memory"], "3072");
+    assert_eq!(project["config"]["runtime"], "node");
+
+    let list: serde_json::Value = serde_json::from_slice(&frc(&["--json", "list"])).unwrap();
+    let entries = list.as_array().unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["config"]["memory"], "3072");
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_export_then_import_config() {
+    let dir = temp_project("export-config");
+    fs::write(dir.join("index.js"), "").unwrap();
+    let shared = dir.join("shared.yaml");
+    let frc = |home: &str| {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_frc"));
+        cmd.env("XDG_CONFIG_HOME", dir.join(home));
+        cmd
+    };
+    let succeeded = |cmd: &mut Command| {
+        let output = cmd.output().expect("Failed to execute command");
+        assert!(output.status.success());
+        output.stdout
+    };
+
+    let save = ["-m", "384", "node", "index.js"];
+    succeeded(frc("from").arg("--cwd").arg(&dir).args(save));
+    succeeded(frc("from").arg("export-config").arg(&shared));
+    succeeded(frc("to").arg("import-config").arg(&shared));
+
+    let list = succeeded(frc("to").args(["--json", "list"]));
+    let list: serde_json::Value = serde_json::from_slice(&list).unwrap();
+    let entries = list.as_array().unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["config"]["memory"], "384");
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_eval_snippet_is_not_saved_by_default() {
+    let dir = temp_project("eval");
+    let config_home = dir.join("config");
+    let config_file = config_home.join("frc").join("config.json");
+    let snippet = "console.log(require('v8').getHeapStatistics().heap_size_limit > 0)";
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "--cwd"])
+        .arg(&dir)
+        .args(["-m", "1024", "node", "-e", snippet])
+        .env("XDG_CONFIG_HOME", &config_home)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Eval snippet: heap limit 1024 MB (not saved"));
+    assert!(!config_file.exists());
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "--cwd"])
+        .arg(&dir)
+        .args(["-m", "1024", "--save", "node", "-e", snippet])
+        .env("XDG_CONFIG_HOME", &config_home)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    let config = fs::read_to_string(&config_file).unwrap();
+    assert!(config.contains("\"1024\""));
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_yaml_config_format() {
+    let dir = temp_project("yaml-format");
+    let config_home = dir.join("config");
+    let config_dir = config_home.join("frc");
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "--cwd"])
+        .arg(&dir)
+        .args(["--config-format", "yaml", "-m", "2048", "--save"])
+        .args(["node", "-e", "0"])
+        .env("XDG_CONFIG_HOME", &config_home)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    assert!(!config_dir.join("config.json").exists());
+    let yaml = fs::read_to_string(config_dir.join("config.yaml")).unwrap();
+    assert!(yaml.contains("memory: '2048'"));
+
+    // Without the flag the existing YAML file is picked up
+    let output = Command::new("cargo")
+        .args(["run", "--", "--cwd"])
+        .arg(&dir)
+        .args(["project", "--json"])
+        .env("XDG_CONFIG_HOME", &config_home)
+        .output()
+        .expect("Failed to execute command");
+
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["config"]["memory"], "2048");
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_toml_config_format_from_env() {
+    let dir = temp_project("toml-format");
+    let config_home = dir.join("config");
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "--cwd"])
+        .arg(&dir)
+        .args(["-m", "3072", "--save", "node", "-e", "0"])
+        .env("XDG_CONFIG_HOME", &config_home)
+        .env("FRC_CONFIG_FORMAT", "toml")
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    let toml = fs::read_to_string(config_home.join("frc").join("config.toml")).unwrap();
+    assert!(toml.contains("memory = \"3072\""));
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_old_config_is_migrated_with_backup() {
+    let dir = temp_project("migrate");
+    let config_home = dir.join("config");
+    let config_dir = config_home.join("frc");
+    fs::create_dir_all(&config_dir).unwrap();
+
+    let project = fs::canonicalize(&dir).unwrap();
+    let old = serde_json::json!({
+        "projects": { project.to_str().unwrap(): { "runtime": "node", "memory": 3072 } }
+    });
+    fs::write(config_dir.join("config.json"), old.to_string()).unwrap();
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "--cwd"])
+        .arg(&dir)
+        .args(["project", "--json"])
+        .env("XDG_CONFIG_HOME", &config_home)
+        .output()
+        .expect("Failed to execute command");
+
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["config"]["memory"], "3072");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("migrated 1 project"));
+
+    let backup = fs::read_to_string(config_dir.join("config.json.bak")).unwrap();
+    assert_eq!(backup, old.to_string());
+    let migrated = fs::read_to_string(config_dir.join("config.json")).unwrap();
+    assert!(migrated.contains("\"version\": 2"));
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_moved_project_keeps_config() {
+    let root = temp_project("moved");
+    let config_home = root.join("config");
+    let before = root.join("before");
+    let after = root.join("after");
+    fs::create_dir_all(&before).unwrap();
+    fs::write(before.join("package.json"), r#"{"name": "frc-moved-app"}"#).unwrap();
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "--cwd"])
+        .arg(&before)
+        .args(["-m", "3072", "--save", "node", "-e", "0"])
+        .env("XDG_CONFIG_HOME", &config_home)
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+
+    fs::rename(&before, &after).unwrap();
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "--cwd"])
+        .arg(&after)
+        .args(["node", "-e", "0"])
+        .env("XDG_CONFIG_HOME", &config_home)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Using saved 3072 MB"));
+
+    fs::remove_dir_all(&root).ok();
+}
+
+#[test]
+fn test_workspace_root_flag() {
+    let root = temp_project("workspace");
+    let package = root.join("packages").join("web");
+    fs::create_dir_all(&package).unwrap();
+    fs::write(root.join("pnpm-workspace.yaml"), "packages: []\n").unwrap();
+    fs::write(package.join("package.json"), "{}").unwrap();
+
+    let project = |extra: &[&str]| {
+        let output = Command::new("cargo")
+            .args(["run", "--", "--cwd"])
+            .arg(&package)
+            .args(extra)
+            .args(["project", "--json"])
+            .output()
+            .expect("Failed to execute command");
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()
+    };
+
+    let root = fs::canonicalize(&root).unwrap();
+    let nearest = project(&[]);
+    assert_eq!(nearest["name"], "web");
+    assert_eq!(nearest["workspace_root"], root.to_str().unwrap());
+
+    let top = project(&["--workspace-root"]);
+    assert_eq!(top["path"], root.to_str().unwrap());
+    assert!(top.get("workspace_root").is_none());
+
+    fs::remove_dir_all(&root).ok();
+}
+
+#[test]
+fn test_local_file_overrides_user_config() {
+    let dir = temp_project("local");
+    let config_home = dir.join("config");
+    let config_file = config_home.join("frc").join("config.json");
+
+    let frc = |args: &[&str]| {
+        Command::new("cargo")
+            .args(["run", "--", "--cwd"])
+            .arg(&dir)
+            .args(args)
+            .env("XDG_CONFIG_HOME", &config_home)
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    fs::write(dir.join("index.js"), "").unwrap();
+
+    // --local writes .frc.json and leaves the user config alone
+    let output = frc(&["-m", "2560", "--local", "node", "index.js"]);
+    assert!(output.status.success());
+    let local: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(dir.join(".frc.json")).unwrap()).unwrap();
+    assert_eq!(local["memory"], "2560");
+    assert!(!config_file.exists());
+
+    // A user config for the same project is overridden by the local file
+    frc(&["-m", "1536", "node", "index.js"]);
+    assert!(config_file.exists());
+    let output = frc(&["node", "index.js"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Using .frc.json for"));
+    assert!(stdout.contains("2560 MB"));
+
+    // An explicit -m still wins
+    let output = frc(&["-m", "1024", "node", "index.js"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Setting memory limit to 1024 MB"));
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_exit_code_is_propagated() {
+    let dir = temp_project("exit-code");
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "--cwd"])
+        .arg(&dir)
+        .args(["node", "-e", "process.exit(42)"])
+        .env("XDG_CONFIG_HOME", dir.join("config"))
+        .output()
+        .expect("Failed to execute command");
+
+    assert_eq!(output.status.code(), Some(42));
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_verbose_reports_memory_source() {
+    let dir = temp_project("verbose");
+    let config_home = dir.join("config");
+    fs::write(dir.join("index.js"), "").unwrap();
+
+    let run = |extra: &[&str]| {
+        Command::new("cargo")
+            .args(["run", "--", "--cwd"])
+            .arg(&dir)
+            .args(extra)
+            .args(["node", "index.js"])
+            .env("XDG_CONFIG_HOME", &config_home)
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    let quiet = run(&["-m", "1024"]);
+    assert!(quiet.status.success());
+    assert!(!String::from_utf8_lossy(&quiet.stderr).contains("[frc]"));
+
+    let output = run(&["--verbose"]);
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("[frc] source: saved config (1024 MB)"));
+    assert!(stderr.contains("--max-old-space-size=1024"));
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_config_home_override() {
+    let dir = temp_project("config-home");
+    let config_home = dir.join("profile");
+
+    let run = |args: &[&str]| {
+        Command::new("cargo")
+            .args(["run", "--", "--cwd"])
+            .arg(&dir)
+            .args(args)
+            .env("FRC_CONFIG_HOME", &config_home)
+            .env("XDG_CONFIG_HOME", dir.join("xdg"))
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    let output = run(&["-m", "2048", "--save", "node", "-e", "0"]);
+    assert!(output.status.success());
+    let config = fs::read_to_string(config_home.join("config.json")).unwrap();
+    assert!(config.contains("\"memory\": \"2048\""));
+    assert!(!dir.join("xdg").exists());
+
+    let output = run(&["project"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Memory: 2048 MB"));
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_list_filters() {
+    let dir = temp_project("list-filter");
+    let frc = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_frc"))
+            .args(args)
+            .env("XDG_CONFIG_HOME", dir.join("config"))
+            .output()
+            .expect("Failed to execute command");
+        assert!(output.status.success());
+        output.stdout
+    };
+    for name in ["web", "api"] {
+        let project = dir.join(name);
+        fs::create_dir_all(&project).unwrap();
+        fs::write(project.join("package.json"), "{}").unwrap();
+        let cwd = project.to_string_lossy();
+        frc(&["--cwd", &cwd, "--save", "-m", "1024", "node", "-e", "0"]);
+    }
+    let list = |args: &[&str]| {
+        let stdout = frc(&[&["--json", "list"], args].concat());
+        let list: serde_json::Value = serde_json::from_slice(&stdout).unwrap();
+        let names = list.as_array().unwrap().iter();
+        let names = names.map(|entry| entry["name"].as_str().unwrap().to_string());
+        names.collect::<Vec<_>>()
+    };
+
+    let pattern = format!("{}/*", dir.display());
+    assert_eq!(list(&[&pattern]).len(), 2);
+    assert_eq!(list(&[&format!("{}/w*", dir.display())]), ["web"]);
+    assert_eq!(list(&[&pattern, "--limit", "1"]).len(), 1);
+    assert!(list(&[&pattern, "--runtime", "deno"]).is_empty());
+
+    let stdout = frc(&["list", "--runtime", "bun"]);
+    assert!(String::from_utf8_lossy(&stdout).contains("No saved projects match the filter"));
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_memory_max_clamps_or_fails_when_strict() {
+    let dir = temp_project("memory-max");
+    let frc = |extra: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_frc"))
+            .arg("--cwd")
+            .arg(&dir)
+            .args(["--memory-max", "1GB", "--no-save", "-m", "2048"])
+            .args(extra)
+            .args(["node", "-e", "console.log(process.env.NODE_OPTIONS)"])
+            .env("XDG_CONFIG_HOME", dir.join("config"))
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    let output = frc(&[]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("--max-old-space-size=1024"));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("exceeds the memory ceiling (1024 MB)"));
+
+    let output = frc(&["--strict"]);
+    assert!(!output.status.success());
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("max-old-space-size"));
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_library_resolves_memory() -> anyhow::Result<()> {
+    assert_eq!(Runtime::from_command("npm").unwrap(), Runtime::Node);
+    assert!(Runtime::Deno.supports_memory_config());
+
+    let dir = temp_project("library");
+    let nested = dir.join("src");
+    fs::create_dir_all(&nested).unwrap();
+    let root = Project::get_id_from(&nested);
+    assert_eq!(PathBuf::from(&root), dir);
+
+    let mut config = Config::default();
+    config.set_global_default("3072".to_string());
+    let mut manager = Manager::with_config(config);
+
+    let options = RunOptions::default();
+    let memory = manager.resolve_memory(&Runtime::Node, &options, 16)?;
+    assert_eq!(memory.as_deref(), Some("3072"));
+
+    let options = RunOptions {
+        memory: Some("25%".to_string()),
+        ..Default::default()
+    };
+    let memory = manager.resolve_memory(&Runtime::Node, &options, 16)?;
+    assert_eq!(memory.as_deref(), Some("4096"));
+
+    fs::remove_dir_all(&dir).ok();
+    Ok(())
+}